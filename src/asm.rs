@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::emulator::Platform;
+use crate::instruction::{self, Instruction, Operand, ParseError};
+
+// a line of source that couldn't be assembled. line and column count from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}:{}: {}", self.line, self.column, self.message);
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+fn error(line: usize, column: usize, message: String) -> AsmError {
+    return AsmError { line, column, message };
+}
+
+// a piece of source text and the column it starts at
+#[derive(Debug, Clone)]
+struct Token {
+    text: String,
+    column: usize,
+}
+
+// an operand before labels are known, numbers stay as text until the second pass
+#[derive(Debug, Clone)]
+enum SourceOperand {
+    Fixed(Operand),
+    Value(Token),
+    Long(Token),
+}
+
+#[derive(Debug, Clone)]
+enum Statement {
+    Instruction { mnemonic: Token, operands: Vec<(SourceOperand, usize)> },
+    Db(Vec<Token>),
+    Dw(Vec<Token>),
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    number: usize,
+    address: usize,
+    statement: Statement,
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    return match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.'),
+        _ => false,
+    };
+}
+
+// names that mean something as an operand and so can't be labels or constants
+fn is_reserved(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    return register(&upper).is_some()
+        || ["I", "DT", "ST", "K", "F", "B", "HF", "R", "LONG"].contains(&upper.as_str());
+}
+
+fn register(text: &str) -> Option<usize> {
+    let upper = text.trim().to_ascii_uppercase();
+    let mut chars = upper.chars();
+    if chars.next() != Some('V') {
+        return None;
+    }
+    let digit = chars.next()?.to_digit(16)?;
+    if chars.next().is_some() {
+        return None;
+    }
+    return Some(digit as usize);
+}
+
+// a row of a sprite drawn with # and ., the same way the disassembler prints them.
+// every 8 pixels make a byte
+fn sprite_literal(text: &str) -> Option<Vec<u8>> {
+    if text.is_empty() || !text.chars().all(|c| c == '#' || c == '.') {
+        return None;
+    }
+    return Some(text.as_bytes().chunks(8).map(|row| {
+        row.iter().enumerate().fold(0, |byte, (bit, c)| if *c == b'#' { byte | (0x80 >> bit) } else { byte })
+    }).collect());
+}
+
+// splits text at commas, keeping the column each piece starts at. offset is the column before text
+fn split_operands(text: &str, offset: usize) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    for piece in text.split(',') {
+        let trimmed = piece.trim_start();
+        let column = offset + start + (piece.len() - trimmed.len()) + 1;
+        tokens.push(Token { text: trimmed.trim_end().to_string(), column });
+        start += piece.len() + 1;
+    }
+    return tokens;
+}
+
+fn parse_operand(token: &Token) -> SourceOperand {
+    let upper = token.text.to_ascii_uppercase();
+    if let Some(x) = register(&upper) {
+        return SourceOperand::Fixed(Operand::V(x));
+    }
+    if let Some((first, last)) = upper.split_once('-') {
+        if let (Some(x), Some(y)) = (register(first), register(last)) {
+            return SourceOperand::Fixed(Operand::Range(x, y));
+        }
+    }
+    let fixed = match upper.as_str() {
+        "I" => Some(Operand::I),
+        "[I]" => Some(Operand::IndirectI),
+        "DT" => Some(Operand::Dt),
+        "ST" => Some(Operand::St),
+        "K" => Some(Operand::K),
+        "F" => Some(Operand::F),
+        "B" => Some(Operand::B),
+        "HF" => Some(Operand::Hf),
+        "R" => Some(Operand::R),
+        _ => None,
+    };
+    if let Some(operand) = fixed {
+        return SourceOperand::Fixed(operand);
+    }
+    if upper.starts_with("LONG ") {
+        let rest = &token.text[4..];
+        let trimmed = rest.trim_start();
+        let column = token.column + 4 + (rest.len() - trimmed.len());
+        return SourceOperand::Long(Token { text: trimmed.to_string(), column });
+    }
+    return SourceOperand::Value(token.clone());
+}
+
+// takes the next whitespace separated word off the front of rest. offset is the column before rest
+fn next_word(rest: &mut &str, offset: &mut usize) -> Option<Token> {
+    let trimmed = rest.trim_start();
+    *offset += rest.len() - trimmed.len();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let length = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+    let token = Token { text: trimmed[..length].to_string(), column: *offset + 1 };
+    *rest = &trimmed[length..];
+    *offset += length;
+    return Some(token);
+}
+
+fn parse_number(text: &str) -> Option<i64> {
+    let lower = text.to_ascii_lowercase();
+    if let Some(hex) = lower.strip_prefix("0x") {
+        return i64::from_str_radix(hex, 16).ok();
+    }
+    if let Some(binary) = lower.strip_prefix("0b") {
+        return i64::from_str_radix(binary, 2).ok();
+    }
+    return lower.parse().ok();
+}
+
+// numbers, labels and constants joined with + and -
+fn evaluate(expression: &Token, line: usize, symbols: &HashMap<String, i64>) -> Result<i64, AsmError> {
+    if expression.text.is_empty() {
+        return Err(error(line, expression.column, "expected a value".to_string()));
+    }
+
+    let mut total: i64 = 0;
+    let mut sign = 1;
+    let mut term_start = 0;
+    let text = expression.text.as_str();
+    for (index, c) in text.char_indices().chain(std::iter::once((text.len(), '+'))) {
+        if c != '+' && c != '-' {
+            continue;
+        }
+        let raw = &text[term_start..index];
+        let term = raw.trim();
+        let column = expression.column + term_start + (raw.len() - raw.trim_start().len());
+        // a sign with nothing before it, like the - in -1
+        if term.is_empty() && index < text.len() && text[..index].trim().is_empty() {
+            sign = if c == '-' { -1 } else { 1 };
+            term_start = index + 1;
+            continue;
+        }
+        let value = if let Some(number) = parse_number(term) {
+            number
+        } else if let Some(value) = symbols.get(term) {
+            *value
+        } else if is_identifier(term) {
+            return Err(error(line, column, format!("unknown symbol '{}'", term)));
+        } else {
+            return Err(error(line, column, format!("expected a number or label, found '{}'", term)));
+        };
+        total = match value.checked_mul(sign).and_then(|value| total.checked_add(value)) {
+            Some(total) => total,
+            None => return Err(error(line, column, format!("'{}' makes the value too big", term))),
+        };
+        sign = if c == '-' { -1 } else { 1 };
+        term_start = index + 1;
+    }
+    return Ok(total);
+}
+
+fn check_range(value: i64, max: i64, line: usize, column: usize) -> Result<i64, AsmError> {
+    if !(0..=max).contains(&value) {
+        return Err(error(line, column, format!("value {} out of range 0 - {:#X}", value, max)));
+    }
+    return Ok(value);
+}
+
+// assembles source written with the mnemonics from technical_reference.htm into a rom loaded at start.
+//   label:              names the address of what follows it
+//   NAME EQU value      names a value
+//   db 1, 0xF0, ##..##.. bytes, # and . are sprite pixels, 8 to a byte
+//   dw 0x1234           big endian words
+// numbers are decimal, 0x hex or 0b binary, anywhere a number goes a label or constant can too, optionally with + and -.
+// ; starts a comment. instructions the platform doesn't have are rejected
+pub fn assemble(source: &str, start: u16, platform: Platform) -> Result<Vec<u8>, AsmError> {
+    let mut symbols: HashMap<String, i64> = HashMap::new();
+    let mut constants: Vec<(usize, Token, Token)> = Vec::new();
+    let mut lines: Vec<Line> = Vec::new();
+    let mut address = start as usize;
+
+    // first pass, find where every label is. nothing changes size depending on a value, so this needs no values
+    for (index, text) in source.lines().enumerate() {
+        let number = index + 1;
+        let code = match text.find(';') {
+            Some(comment) => &text[..comment],
+            None => text,
+        };
+
+        let mut rest = code;
+        let mut offset = 0;
+        let Some(mut first) = next_word(&mut rest, &mut offset) else {
+            continue;
+        };
+
+        if let Some(name) = first.text.strip_suffix(':') {
+            if !is_identifier(name) || is_reserved(name) {
+                return Err(error(number, first.column, format!("invalid label name '{}'", name)));
+            }
+            if symbols.insert(name.to_string(), address as i64).is_some() {
+                return Err(error(number, first.column, format!("'{}' is already defined", name)));
+            }
+            first = match next_word(&mut rest, &mut offset) {
+                Some(token) => token,
+                None => continue,
+            };
+        }
+
+        let mut lookahead = rest;
+        let mut lookahead_offset = offset;
+        if let Some(directive) = next_word(&mut lookahead, &mut lookahead_offset) {
+            if directive.text.eq_ignore_ascii_case("EQU") {
+                if !is_identifier(&first.text) || is_reserved(&first.text) {
+                    return Err(error(number, first.column, format!("invalid constant name '{}'", first.text)));
+                }
+                let value = lookahead.trim();
+                let column = lookahead_offset + (lookahead.len() - lookahead.trim_start().len()) + 1;
+                constants.push((number, first, Token { text: value.to_string(), column }));
+                continue;
+            }
+        }
+
+        let operands = if rest.trim().is_empty() { Vec::new() } else { split_operands(rest, offset) };
+        let (statement, size) = match first.text.to_ascii_uppercase().as_str() {
+            "DB" => {
+                let mut size = 0;
+                for token in &operands {
+                    size += match sprite_literal(&token.text) {
+                        Some(bytes) => bytes.len(),
+                        None => 1,
+                    };
+                }
+                (Statement::Db(operands), size)
+            }
+            "DW" => {
+                let size = operands.len() * 2;
+                (Statement::Dw(operands), size)
+            }
+            _ => {
+                let operands: Vec<(SourceOperand, usize)> = operands.iter().map(|token| (parse_operand(token), token.column)).collect();
+                let size = if operands.iter().any(|(operand, _)| matches!(operand, SourceOperand::Long(_))) { 4 } else { 2 };
+                (Statement::Instruction { mnemonic: first.clone(), operands }, size)
+            }
+        };
+        if matches!(statement, Statement::Db(ref tokens) | Statement::Dw(ref tokens) if tokens.is_empty()) {
+            return Err(error(number, first.column, format!("{} needs at least one value", first.text)));
+        }
+
+        lines.push(Line { number, address, statement });
+        address += size;
+        if address > 0x10000 {
+            return Err(error(number, first.column, "program doesn't fit in 64k of memory".to_string()));
+        }
+    }
+
+    // constants can use labels and the constants defined before them
+    for (number, name, value) in constants {
+        let value = evaluate(&value, number, &symbols)?;
+        if symbols.insert(name.text.clone(), value).is_some() {
+            return Err(error(number, name.column, format!("'{}' is already defined", name.text)));
+        }
+    }
+
+    // second pass, every symbol is known so the bytes can be written
+    let mut rom = Vec::new();
+    for line in lines {
+        debug_assert_eq!(rom.len(), line.address - start as usize);
+        let number = line.number;
+        match line.statement {
+            Statement::Db(tokens) => {
+                for token in tokens {
+                    match sprite_literal(&token.text) {
+                        Some(bytes) => rom.extend(bytes),
+                        None => rom.push(check_range(evaluate(&token, number, &symbols)?, 0xFF, number, token.column)? as u8),
+                    }
+                }
+            }
+            Statement::Dw(tokens) => {
+                for token in tokens {
+                    let word = check_range(evaluate(&token, number, &symbols)?, 0xFFFF, number, token.column)? as u16;
+                    rom.extend(word.to_be_bytes());
+                }
+            }
+            Statement::Instruction { mnemonic, operands } => {
+                let mut resolved = Vec::new();
+                for (operand, _) in &operands {
+                    resolved.push(match operand {
+                        SourceOperand::Fixed(operand) => *operand,
+                        SourceOperand::Value(token) => Operand::Value(evaluate(token, number, &symbols)?),
+                        SourceOperand::Long(token) => Operand::Long(evaluate(token, number, &symbols)?),
+                    });
+                }
+
+                let decoded = instruction::parse(&mnemonic.text, &resolved).map_err(|parse_error| match parse_error {
+                    ParseError::UnknownMnemonic => error(number, mnemonic.column, format!("unknown mnemonic '{}'", mnemonic.text)),
+                    ParseError::BadOperands => error(number, mnemonic.column, format!("invalid operands for {}", mnemonic.text.to_ascii_uppercase())),
+                    ParseError::OutOfRange { operand, max } => {
+                        let value = match resolved[operand] {
+                            Operand::Value(value) | Operand::Long(value) => value,
+                            _ => 0,
+                        };
+                        error(number, operands[operand].1, format!("value {} out of range 0 - {:#X}", value, max))
+                    }
+                })?;
+                if !decoded.is_supported(platform) {
+                    return Err(error(number, mnemonic.column, format!("{} needs the {} platform", decoded, decoded.platform().name())));
+                }
+
+                rom.extend(instruction::encode(decoded).to_be_bytes());
+                if decoded == Instruction::Ldil {
+                    let long = resolved.iter().find_map(|operand| match operand {
+                        Operand::Long(value) => Some(*value as u16),
+                        _ => None,
+                    });
+                    rom.extend(long.unwrap_or(0).to_be_bytes());
+                }
+            }
+        }
+    }
+
+    return Ok(rom);
+}
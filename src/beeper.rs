@@ -0,0 +1,126 @@
+use std::f32::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Square,
+    Sine,
+    Triangle,
+}
+
+pub const WAVEFORM_NAMES: [&str; 3] = ["square", "sine", "triangle"];
+
+impl Waveform {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name.to_ascii_lowercase().as_str() {
+            "square" => Some(Waveform::Square),
+            "sine" => Some(Waveform::Sine),
+            "triangle" => Some(Waveform::Triangle),
+            _ => None,
+        };
+    }
+
+    // value of the wave at a point in its cycle, phase goes from 0 to 1. between -1 and 1
+    pub fn sample(&self, phase: f32) -> f32 {
+        return match self {
+            Waveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+            Waveform::Sine => (phase * 2.0 * PI).sin(),
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        };
+    }
+}
+
+// what the beep sounds like
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tone {
+    // hz
+    pub frequency: f32,
+    // 0 is silent, 1 is full scale
+    pub volume: f32,
+    pub waveform: Waveform,
+}
+
+impl Default for Tone {
+    fn default() -> Self {
+        return Tone { frequency: 440.0, volume: 0.25, waveform: Waveform::Square };
+    }
+}
+
+// An xo-chip audio pattern, 128 1-bit samples played high bit first and looped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pattern {
+    pub bits: [u8; 0x10],
+    pub pitch: u8,
+}
+
+impl Pattern {
+    // bits played a second, 4000 at the default pitch of 64 and an octave higher for every 48 above it
+    pub fn rate(&self) -> f32 {
+        return 4000.0 * 2f32.powf((self.pitch as f32 - 64.0) / 48.0);
+    }
+
+    // value of the pattern at a point in its loop, phase goes from 0 to 1. 1 for a set bit, -1 for a clear one
+    pub fn sample(&self, phase: f32) -> f32 {
+        let bit = ((phase * 128.0) as usize).min(127);
+        return if self.bits[bit / 8] & (0x80 >> (bit % 8)) != 0 { 1.0 } else { -1.0 };
+    }
+}
+
+// how long the beep takes to fade in and out. starting or stopping a wave at full volume clicks
+pub const RAMP_SECONDS: f32 = 0.005;
+
+// Generates the beep played while the sound timer is running, one sample at a time, or on xo-chip the rom's audio pattern.
+// Doesn't know about any audio library, the frontend pulls samples from it with fill.
+#[derive(Debug, Clone)]
+pub struct Beeper {
+    pub tone: Tone,
+    sample_rate: f32,
+    // played in place of the tone's wave when set
+    pattern: Option<Pattern>,
+    // position in the current cycle of the wave or loop of the pattern, 0 to 1
+    phase: f32,
+    // volume multiplier moving towards 1 while playing and towards 0 while stopped
+    gain: f32,
+    playing: bool,
+}
+
+impl Beeper {
+    pub fn new(tone: Tone, sample_rate: u32) -> Self {
+        return Beeper { tone, sample_rate: sample_rate as f32, pattern: None, phase: 0.0, gain: 0.0, playing: false };
+    }
+
+    // starts or stops the beep, it fades in or out over the next RAMP_SECONDS
+    pub fn set_playing(&mut self, playing: bool) {
+        self.playing = playing;
+    }
+
+    // the xo-chip pattern to play instead of the tone, None for the tone
+    pub fn set_pattern(&mut self, pattern: Option<Pattern>) {
+        self.pattern = pattern;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        return self.playing;
+    }
+
+    // writes the next samples of the beep, silence when stopped and faded out
+    pub fn fill(&mut self, samples: &mut [f32]) {
+        let ramp_step = 1.0 / (RAMP_SECONDS * self.sample_rate).max(1.0);
+        let target = if self.playing { 1.0 } else { 0.0 };
+        for sample in samples.iter_mut() {
+            self.gain = if self.gain < target { (self.gain + ramp_step).min(target) } else { (self.gain - ramp_step).max(target) };
+            if self.gain == 0.0 {
+                // restart the wave so the next beep always starts the same way
+                self.phase = 0.0;
+                *sample = 0.0;
+                continue;
+            }
+
+            let (value, frequency) = match &self.pattern {
+                Some(pattern) => (pattern.sample(self.phase), pattern.rate() / 128.0),
+                None => (self.tone.waveform.sample(self.phase), self.tone.frequency),
+            };
+            *sample = value * self.tone.volume * self.gain;
+            self.phase = (self.phase + frequency / self.sample_rate).fract();
+        }
+    }
+}
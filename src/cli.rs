@@ -0,0 +1,496 @@
+use std::str::FromStr;
+
+use grep_chip8::beeper::{self, Tone};
+use grep_chip8::emulator::{self, Platform};
+use grep_chip8::quirks::{self, Quirks};
+use grep_chip8::rewind;
+use grep_chip8::rng::{self, RandomKind};
+use grep_chip8::screen::{self, Palette};
+use grep_chip8::trace::{self, Filter};
+use grep_chip8::tty::{self, Glyphs};
+
+// the window is the 64x32 display at this many times the size unless --scale is given.
+// it keeps its size, pixels are drawn bigger or smaller as the resolution changes
+pub const DEFAULT_SCALE: u32 = 10;
+
+pub const USAGE: &str = "\
+grep_chip8, a chip-8, super-chip and xo-chip emulator
+
+usage:
+  grep_chip8 [run] [options] rom.ch8       run a rom
+  grep_chip8 disasm [options] rom.ch8      print a listing of a rom
+  grep_chip8 asm [options] in.s [-o out]   assemble a rom
+  grep_chip8 help [command]                list the options of a command
+";
+
+pub const RUN_HELP: &str = "\
+usage: grep_chip8 [run] [options] rom.ch8
+
+machine:
+  --platform chip8|schip|xochip      machine the rom was written for, chip8 unless given
+  --quirks PRESET                    default, vip, chip48, schip or xochip, the platform's own unless given
+  --quirk NAME=on|off                one quirk over the preset: shifting, memory, jumping, vf-reset, clipping
+  --load-address ADDRESS             where the rom is loaded and starts running, 0x200 unless given
+  --speed N                          instructions per second, rounded to a whole number per frame
+  --ipf N                            instructions per 60hz frame, the platform's own unless given
+  --rng xorshift|vip                 where Cxkk's random numbers come from, xorshift unless given
+  --seed N                           seed of the random numbers, a new one every run unless given
+  --vip-interpreter FILE             dump of the vip's memory, for the vip's exact random numbers
+
+display:
+  --frontend sdl|tty                 draw in a window or in the terminal, sdl unless built without it
+  --glyphs half|braille              characters the terminal draws with, half blocks unless given
+  --scale N                          window size in multiples of 64x32, from 1 to 100, 10 unless given
+  --palette NAME|COLOURS             classic, dark, green, amber, lcd, octo or 2 or 4 RRGGBB colours
+  --grid                             draw a border around each pixel
+  --phosphor FRAMES                  fade pixels out over this many frames
+
+sound:
+  --no-audio                         run silently
+  --beep-frequency HZ                440 unless given
+  --beep-volume 0-1                  0.25 unless given
+  --beep-waveform square|sine|triangle
+
+keys:
+  --keymap FILE                      key bindings, keymap.ini in the working directory unless given
+  --key PAD=KEY[,KEY]                bind keyboard keys to a keypad key, can be repeated
+
+play:
+  --rewind SECONDS                   how far backspace goes back, 0 turns it off, 10 unless given
+  --record FILE                      write the key presses to a movie when the emulator quits
+  --play FILE                        play a movie back, then hand over to the keyboard
+  --debug                            start paused with the debugger in the terminal
+  --gdb PORT                         start paused and wait for gdb on localhost
+
+trace, in builds with the trace feature:
+  --trace FILE                       write every instruction run with I, V0-VF and the timers
+  --trace-format text|binary         text unless given
+  --trace-range START-END            only the instructions at these addresses, like 0x200-0x2FF
+  --trace-class CLASS[,CLASS]        only these: flow, skip, alu, memory, display, timer, input, sound
+
+headless:
+  --headless                         run without a window
+  --frames N                         60hz frames to run, 60 or the whole movie unless given
+  --dump FILE                        write the display to a .pbm, .png or .txt file, stdout unless given
+";
+
+pub const DISASM_HELP: &str = "\
+usage: grep_chip8 disasm [options] rom.ch8
+
+prints a listing of the rom to stdout
+
+  --platform chip8|schip|xochip      opcodes to recognise, chip8 unless given
+  --load-address ADDRESS             where the rom is loaded, 0x200 unless given
+";
+
+pub const ASM_HELP: &str = "\
+usage: grep_chip8 asm [options] in.s [-o out.ch8]
+
+writes the assembled rom next to the source unless -o is given
+
+  --platform chip8|schip|xochip      opcodes to accept, chip8 unless given
+  --load-address ADDRESS             where the rom will be loaded, 0x200 unless given
+  -o FILE                            where to write the rom
+";
+
+// where run draws and reads the keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontendKind {
+    Sdl,
+    Tty,
+}
+
+pub const FRONTEND_NAMES: [&str; 2] = ["sdl", "tty"];
+
+impl FrontendKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name.to_ascii_lowercase().as_str() {
+            "sdl" => Some(FrontendKind::Sdl),
+            "tty" => Some(FrontendKind::Tty),
+            _ => None,
+        };
+    }
+
+    // the window, or the terminal in a build without one
+    pub fn default_for_build() -> Self {
+        return if cfg!(feature = "sdl") { FrontendKind::Sdl } else { FrontendKind::Tty };
+    }
+}
+
+// what the command line asks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Run(Box<RunOptions>),
+    Disasm(DisasmOptions),
+    Asm(AsmOptions),
+    // --help or help, with the text to print
+    Help(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunOptions {
+    pub rom_path: String,
+    pub platform: Platform,
+    // None for the platform's own preset
+    pub quirks: Option<Quirks>,
+    // single quirks from --quirk, applied over the preset in order
+    pub quirk_overrides: Vec<(String, bool)>,
+    pub load_address: u16,
+    // None for the platform's own speed
+    pub instructions_per_frame: Option<u32>,
+    pub rng_kind: RandomKind,
+    // None for a new seed every run
+    pub seed: Option<u64>,
+    pub interpreter_path: Option<String>,
+    // None for the window, or the terminal in a build without sdl
+    pub frontend: Option<FrontendKind>,
+    pub glyphs: Glyphs,
+    pub scale: u32,
+    pub palette: Palette,
+    pub grid: bool,
+    pub phosphor_frames: u32,
+    pub audio: bool,
+    pub tone: Tone,
+    pub keymap_path: Option<String>,
+    // --key bindings in the order given
+    pub bindings: Vec<String>,
+    pub rewind_seconds: u32,
+    pub record_path: Option<String>,
+    pub play_path: Option<String>,
+    pub debug: bool,
+    pub gdb_port: Option<u16>,
+    pub trace_path: Option<String>,
+    pub trace_format: trace::Format,
+    pub trace_filter: Filter,
+    pub headless: bool,
+    // None for 60, or the length of the movie being played
+    pub frames: Option<u64>,
+    pub dump_path: Option<String>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        return RunOptions {
+            rom_path: String::new(),
+            platform: Platform::Chip8,
+            quirks: None,
+            quirk_overrides: Vec::new(),
+            load_address: emulator::PROGRAM_START,
+            instructions_per_frame: None,
+            rng_kind: RandomKind::Xorshift,
+            seed: None,
+            interpreter_path: None,
+            frontend: None,
+            glyphs: Glyphs::HalfBlock,
+            scale: DEFAULT_SCALE,
+            palette: Palette::default(),
+            grid: false,
+            phosphor_frames: 0,
+            audio: true,
+            tone: Tone::default(),
+            keymap_path: None,
+            bindings: Vec::new(),
+            rewind_seconds: rewind::DEFAULT_SECONDS,
+            record_path: None,
+            play_path: None,
+            debug: false,
+            gdb_port: None,
+            trace_path: None,
+            trace_format: trace::Format::Text,
+            trace_filter: Filter::default(),
+            headless: false,
+            frames: None,
+            dump_path: None,
+        };
+    }
+}
+
+impl RunOptions {
+    // the preset, or the platform's own quirks when none was given, with the single quirks over it
+    pub fn quirks(&self) -> Quirks {
+        let mut quirks = self.quirks.unwrap_or(self.platform.default_quirks());
+        for (name, on) in &self.quirk_overrides {
+            quirks.set(name, *on);
+        }
+        return quirks;
+    }
+
+    pub fn instructions_per_frame(&self) -> u32 {
+        return self.instructions_per_frame.unwrap_or(self.platform.default_instructions_per_frame());
+    }
+
+    // the frontend given, otherwise the one this build has by default
+    pub fn frontend(&self) -> FrontendKind {
+        return self.frontend.unwrap_or(FrontendKind::default_for_build());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisasmOptions {
+    pub rom_path: String,
+    pub platform: Platform,
+    pub load_address: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmOptions {
+    pub source_path: String,
+    // None to write next to the source
+    pub output_path: Option<String>,
+    pub platform: Platform,
+    pub load_address: u16,
+}
+
+// Reads the arguments after the program name. Nothing is read from disk here,
+// files named on the command line are only opened once the command runs.
+// the error is a message for the user
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args: Vec<String> = args.into_iter().collect();
+    let command = match args.first().map(String::as_str) {
+        Some("run" | "disasm" | "asm" | "help") => args.remove(0),
+        Some("--help" | "-h") => return Ok(Command::Help(USAGE)),
+        _ => "run".to_string(),
+    };
+    if command == "help" {
+        return match args.first().map(String::as_str) {
+            None => Ok(Command::Help(USAGE)),
+            Some(name) => help(name).map(Command::Help).ok_or_else(|| format!("Unknown command '{}', expected one of: run, disasm, asm", name)),
+        };
+    }
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok(Command::Help(help(&command).expect("command is one of the known ones")));
+    }
+
+    let mut args = Args { args: args.into_iter() };
+    return match command.as_str() {
+        "disasm" => parse_disasm(&mut args).map(Command::Disasm),
+        "asm" => parse_asm(&mut args).map(Command::Asm),
+        _ => parse_run(&mut args).map(|options| Command::Run(Box::new(options))),
+    };
+}
+
+// the options of a command
+pub fn help(command: &str) -> Option<&'static str> {
+    return match command {
+        "run" => Some(RUN_HELP),
+        "disasm" => Some(DISASM_HELP),
+        "asm" => Some(ASM_HELP),
+        _ => None,
+    };
+}
+
+fn parse_run(args: &mut Args) -> Result<RunOptions, String> {
+    let mut options = RunOptions::default();
+    let mut rom_path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--platform" => options.platform = args.named("--platform", "platform", Platform::from_name, &emulator::PLATFORM_NAMES)?,
+            "--quirks" => options.quirks = Some(args.named("--quirks", "quirks preset", Quirks::from_name, &quirks::PRESET_NAMES)?),
+            "--quirk" => {
+                let quirk = args.value("--quirk")?;
+                let (name, on) = match quirk.split_once('=') {
+                    Some((name, "on")) => (name, true),
+                    Some((name, "off")) => (name, false),
+                    _ => return Err(format!("Expected a quirk as NAME=on or NAME=off, found '{}'", quirk)),
+                };
+                if !Quirks::default().set(name, on) {
+                    return Err(format!("Unknown quirk '{}', expected one of: {}", name, quirks::QUIRK_NAMES.join(", ")));
+                }
+                options.quirk_overrides.push((name.to_ascii_lowercase(), on));
+            }
+            "--load-address" => options.load_address = args.address("--load-address")?,
+            "--speed" => {
+                let speed: u32 = args.parsed("--speed", "a number of instructions per second")?;
+                if speed == 0 {
+                    return Err("Expected a number of instructions per second, found '0'".to_string());
+                }
+                options.instructions_per_frame = Some((speed.saturating_add(30) / 60).max(1));
+            }
+            "--ipf" => {
+                let count: u32 = args.parsed("--ipf", "a number of instructions per frame")?;
+                if count == 0 {
+                    return Err("Expected a number of instructions per frame, found '0'".to_string());
+                }
+                options.instructions_per_frame = Some(count);
+            }
+            "--rng" => options.rng_kind = args.named("--rng", "random source", RandomKind::from_name, &rng::RANDOM_NAMES)?,
+            "--seed" => options.seed = Some(args.parsed("--seed", "a number to seed the random source with")?),
+            "--vip-interpreter" => options.interpreter_path = Some(args.value("--vip-interpreter")?),
+            "--frontend" => options.frontend = Some(args.named("--frontend", "frontend", FrontendKind::from_name, &FRONTEND_NAMES)?),
+            "--glyphs" => options.glyphs = args.named("--glyphs", "glyphs", Glyphs::from_name, &tty::GLYPH_NAMES)?,
+            "--scale" => {
+                let scale = args.value("--scale")?;
+                options.scale = match scale.parse::<u32>() {
+                    Ok(scale) if (1..=100).contains(&scale) => scale,
+                    _ => return Err(format!("Expected a scale from 1 to 100, found '{}'", scale)),
+                };
+            }
+            "--palette" => {
+                let name = args.value("--palette")?;
+                options.palette = Palette::from_name(&name).ok_or_else(|| {
+                    format!("Unknown palette '{}', expected one of: {}, or 2 or 4 RRGGBB colours", name, screen::PALETTE_NAMES.join(", "))
+                })?;
+            }
+            "--grid" => options.grid = true,
+            "--phosphor" => options.phosphor_frames = args.parsed("--phosphor", "a number of frames")?,
+            "--no-audio" => options.audio = false,
+            "--beep-frequency" => {
+                let frequency = args.value("--beep-frequency")?;
+                options.tone.frequency = match frequency.parse::<f32>() {
+                    Ok(hz) if hz > 0.0 => hz,
+                    _ => return Err(format!("Expected a frequency in hz, found '{}'", frequency)),
+                };
+            }
+            "--beep-volume" => {
+                let volume = args.value("--beep-volume")?;
+                options.tone.volume = match volume.parse::<f32>() {
+                    Ok(volume) if (0.0..=1.0).contains(&volume) => volume,
+                    _ => return Err(format!("Expected a volume from 0 to 1, found '{}'", volume)),
+                };
+            }
+            "--beep-waveform" => options.tone.waveform = args.named("--beep-waveform", "waveform", beeper::Waveform::from_name, &beeper::WAVEFORM_NAMES)?,
+            "--keymap" => options.keymap_path = Some(args.value("--keymap")?),
+            "--key" => options.bindings.push(args.value("--key")?),
+            "--rewind" => options.rewind_seconds = args.parsed("--rewind", "a number of seconds")?,
+            "--record" => options.record_path = Some(args.value("--record")?),
+            "--play" => options.play_path = Some(args.value("--play")?),
+            "--debug" => options.debug = true,
+            "--gdb" => options.gdb_port = Some(args.parsed("--gdb", "a port number")?),
+            "--trace" => options.trace_path = Some(args.value("--trace")?),
+            "--trace-format" => options.trace_format = args.named("--trace-format", "trace format", trace::Format::from_name, &trace::FORMAT_NAMES)?,
+            "--trace-range" => {
+                let range = args.value("--trace-range")?;
+                let (start, end) = range.split_once('-').ok_or_else(|| format!("Expected a range of addresses like 0x200-0x2FF, found '{}'", range))?;
+                let (start, end) = (address(start)?, address(end)?);
+                if start > end {
+                    return Err(format!("The range {} starts after it ends", range));
+                }
+                (options.trace_filter.start, options.trace_filter.end) = (start, end);
+            }
+            "--trace-class" => {
+                let classes = args.value("--trace-class")?;
+                for name in classes.split(',') {
+                    let class = trace::Class::from_name(name.trim())
+                        .ok_or_else(|| format!("Unknown instruction class '{}', expected one of: {}", name, trace::CLASS_NAMES.join(", ")))?;
+                    options.trace_filter.classes.push(class);
+                }
+            }
+            "--headless" => options.headless = true,
+            "--frames" => options.frames = Some(args.parsed("--frames", "a number of frames")?),
+            "--dump" => options.dump_path = Some(args.value("--dump")?),
+            _ => positional(&mut rom_path, arg, "rom")?,
+        }
+    }
+    options.rom_path = rom_path.ok_or("No rom given")?;
+
+    if options.interpreter_path.is_some() && options.rng_kind != RandomKind::Vip {
+        return Err("--vip-interpreter only works with --rng vip".to_string());
+    }
+    if options.debug && options.gdb_port.is_some() {
+        return Err("--debug and --gdb can't be used together".to_string());
+    }
+    if (options.record_path.is_some() || options.play_path.is_some()) && (options.debug || options.gdb_port.is_some()) {
+        return Err("Movies can't be recorded or played with --debug or --gdb".to_string());
+    }
+    if options.record_path.is_some() && (options.play_path.is_some() || options.headless) {
+        return Err("--record can't be used with --play or --headless".to_string());
+    }
+    if options.trace_path.is_none() && (options.trace_format != trace::Format::Text || options.trace_filter != Filter::default()) {
+        return Err("--trace-format, --trace-range and --trace-class need --trace".to_string());
+    }
+    if options.headless && options.frontend.is_some() {
+        return Err("--frontend can't be used with --headless".to_string());
+    }
+    // the terminal's keys go to the emulator, there's nowhere to type commands. the terminal is also
+    // what runs when a build without the window isn't told otherwise
+    if options.debug && !options.headless && options.frontend() == FrontendKind::Tty {
+        return Err("--debug can't be used with the tty frontend, run it in the window or --headless".to_string());
+    }
+    return Ok(options);
+}
+
+fn parse_disasm(args: &mut Args) -> Result<DisasmOptions, String> {
+    let mut rom_path = None;
+    let mut platform = Platform::Chip8;
+    let mut load_address = emulator::PROGRAM_START;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--platform" => platform = args.named("--platform", "platform", Platform::from_name, &emulator::PLATFORM_NAMES)?,
+            "--load-address" => load_address = args.address("--load-address")?,
+            _ => positional(&mut rom_path, arg, "rom")?,
+        }
+    }
+    return Ok(DisasmOptions { rom_path: rom_path.ok_or("No rom given")?, platform, load_address });
+}
+
+fn parse_asm(args: &mut Args) -> Result<AsmOptions, String> {
+    let mut source_path = None;
+    let mut output_path = None;
+    let mut platform = Platform::Chip8;
+    let mut load_address = emulator::PROGRAM_START;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--platform" => platform = args.named("--platform", "platform", Platform::from_name, &emulator::PLATFORM_NAMES)?,
+            "--load-address" => load_address = args.address("--load-address")?,
+            "-o" => output_path = Some(args.value("-o")?),
+            _ => positional(&mut source_path, arg, "source file")?,
+        }
+    }
+    return Ok(AsmOptions { source_path: source_path.ok_or("No source file given")?, output_path, platform, load_address });
+}
+
+// the one argument that isn't an option, anything else starting with - is a mistake
+fn positional(path: &mut Option<String>, arg: String, what: &str) -> Result<(), String> {
+    if arg.starts_with('-') && arg.len() > 1 {
+        return Err(format!("Unknown option '{}'", arg));
+    }
+    if let Some(first) = path {
+        return Err(format!("Only one {} can be given, found '{}' and '{}'", what, first, arg));
+    }
+    *path = Some(arg);
+    return Ok(());
+}
+
+// the arguments of a command, handing out the value that follows an option
+struct Args {
+    args: std::vec::IntoIter<String>,
+}
+
+impl Iterator for Args {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        return self.args.next();
+    }
+}
+
+impl Args {
+    fn value(&mut self, option: &str) -> Result<String, String> {
+        return self.args.next().ok_or_else(|| format!("{} needs a value", option));
+    }
+
+    // the value parsed as a number, expected says what it should have been
+    fn parsed<T: FromStr>(&mut self, option: &str, expected: &str) -> Result<T, String> {
+        let value = self.value(option)?;
+        return value.parse().map_err(|_| format!("Expected {}, found '{}'", expected, value));
+    }
+
+    // the value looked up by name, names are listed when it isn't one of them
+    fn named<T>(&mut self, option: &str, what: &str, lookup: fn(&str) -> Option<T>, names: &[&str]) -> Result<T, String> {
+        let name = self.value(option)?;
+        return lookup(&name).ok_or_else(|| format!("Unknown {} '{}', expected one of: {}", what, name, names.join(", ")));
+    }
+
+    fn address(&mut self, option: &str) -> Result<u16, String> {
+        return address(&self.value(option)?);
+    }
+}
+
+// an address in hex with 0x in front, or decimal
+fn address(value: &str) -> Result<u16, String> {
+    let address = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    return address.map_err(|_| format!("Expected an address like 0x200, found '{}'", value));
+}
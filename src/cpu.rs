@@ -1,69 +1,179 @@
-use crate::emulator::Emulator;
-use crate::hex_util;
-use rand::prelude::*;
+use std::fmt;
+
+use crate::display;
+use crate::emulator::{Emulator, Platform, BIG_FONTSET_ADDRESS};
+use crate::instruction::{self, Instruction};
+use crate::timer;
+#[cfg(feature = "trace")]
+use crate::trace;
+
+// what went wrong while executing an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuErrorKind {
+    UnknownOpcode,
+    // CALL with all 15 stack levels in use
+    StackOverflow,
+    // RET with nothing on the stack
+    StackUnderflow,
+    // the instruction tried to read or write past the end of ram
+    MemoryOutOfBounds { address: usize },
+    // the pc points past the end of ram, so there is no instruction to fetch
+    PcOutOfBounds,
+}
+
+// an instruction that could not be executed, with the address it was fetched from.
+// the emulator is left as it was before the failing instruction's side effects, except the pc, which has moved past it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuError {
+    pub kind: CpuErrorKind,
+    pub pc: u16,
+    pub opcode: u16,
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self.kind {
+            CpuErrorKind::UnknownOpcode => write!(f, "unknown opcode {:04X} at {:#05X}", self.opcode, self.pc),
+            CpuErrorKind::StackOverflow => write!(f, "stack overflow, opcode {:04X} at {:#05X}", self.opcode, self.pc),
+            CpuErrorKind::StackUnderflow => write!(f, "stack underflow, opcode {:04X} at {:#05X}", self.opcode, self.pc),
+            CpuErrorKind::MemoryOutOfBounds { address } => write!(f, "memory access out of bounds at {:#X}, opcode {:04X} at {:#05X}", address, self.opcode, self.pc),
+            CpuErrorKind::PcOutOfBounds => write!(f, "pc out of bounds at {:#X}", self.pc),
+        };
+    }
+}
 
-pub fn execute_next_instruction(emulator: &mut Emulator) {
-    let instruction: u16 = get_next_instruction(emulator);
-    call_instruction(emulator, instruction);
+impl std::error::Error for CpuError {}
+
+pub fn execute_next_instruction(emulator: &mut Emulator) -> Result<(), CpuError> {
+    let instruction: u16 = get_next_instruction(emulator)?;
+    return call_instruction(emulator, instruction);
+}
+
+// runs one 60hz frame of emulated time, the given number of instructions followed by a single timer tick.
+// stops at the first error without ticking the timers
+pub fn execute_frame(emulator: &mut Emulator, instructions: u32) -> Result<(), CpuError> {
+    for _ in 0..instructions {
+        execute_next_instruction(emulator)?;
+    }
+    timer::tick(emulator);
+    return Ok(());
 }
 
 // gets next instruction from memory and increments pc
-pub fn get_next_instruction(emulator: &mut Emulator) -> u16 {
+pub fn get_next_instruction(emulator: &mut Emulator) -> Result<u16, CpuError> {
     // all instructions are 2 bytes, msb first
- 
-    let instruction: u16 = ((emulator.ram[emulator.pc as usize] as u16) << 8) + emulator.ram[emulator.pc as usize + 1] as u16;
-    emulator.pc += 2;
+    let pc = emulator.pc as usize;
+    if pc + 1 >= emulator.ram.len() {
+        return Err(CpuError { kind: CpuErrorKind::PcOutOfBounds, pc: emulator.pc, opcode: 0 });
+    }
 
-    return instruction;
+    let instruction: u16 = ((emulator.ram[pc] as u16) << 8) + emulator.ram[pc + 1] as u16;
+    emulator.pc = emulator.pc.wrapping_add(2);
+
+    return Ok(instruction);
+}
+
+// decodes the instruction, then executes it.
+// expects the pc to already be past the instruction, as left by get_next_instruction
+pub fn call_instruction(emulator: &mut Emulator, instruction: u16) -> Result<(), CpuError> {
+    #[cfg(feature = "trace")]
+    trace::record(emulator, instruction);
+
+    return execute(emulator, instruction::decode(instruction));
 }
 
-// finds instruction based on the hex, then executes mapped function
-pub fn call_instruction (emulator: &mut Emulator, instruction: u16) {
-    if instruction != 0 && instruction != 5084 {
-        dbg!(instruction);
+// runs a decoded instruction through its mapped function.
+// expects the pc to already be past the instruction, errors report the address before it
+pub fn execute(emulator: &mut Emulator, instruction: Instruction) -> Result<(), CpuError> {
+    let pc = emulator.pc.wrapping_sub(2);
+    return dispatch(emulator, instruction).map_err(|kind| CpuError { kind, pc, opcode: instruction::encode(instruction) });
+}
+
+fn dispatch(emulator: &mut Emulator, instruction: Instruction) -> Result<(), CpuErrorKind> {
+    let instruction = instruction.run_on(emulator.platform);
+    if !instruction.is_supported(emulator.platform) {
+        return Err(CpuErrorKind::UnknownOpcode);
     }
 
     match instruction {
-        0x00E0 => cls(emulator),
-        0x00EE => ret(emulator),
-        _ if (instruction & 0xF000 == 0x0000) => sys(), // 0nnn
-        _ if (instruction & 0xF000 == 0x1000) => jp(emulator, instruction), // 1nnn
-        _ if (instruction & 0xF000 == 0x2000) => call(emulator, instruction), // 2nnn
-        _ if (instruction & 0xF000 == 0x3000) => se(emulator, instruction), // 3xkk
-        _ if (instruction & 0xF000 == 0x4000) => sne(emulator, instruction), // 4xkk
-        _ if (instruction & 0xF00F == 0x5000) => sev(emulator, instruction), // 5xy0
-        _ if (instruction & 0xF000 == 0x6000) => ldx(emulator, instruction), // 6xkk
-        _ if (instruction & 0xF000 == 0x7000) => addx(emulator, instruction), // 7xkk
-        _ if (instruction & 0xF00F == 0x8000) => ldxy(emulator, instruction), // 8xy0
-        _ if (instruction & 0xF00F == 0x8001) => orxy(emulator, instruction), // 8xy1
-        _ if (instruction & 0xF002 == 0x8002) => andxy(emulator, instruction), // 8xy2
-        _ if (instruction & 0xF00F == 0x8003) => xorxy(emulator, instruction), // 8xy3
-        _ if (instruction & 0xF00F == 0x8004) => addxy(emulator, instruction), // 8xy4
-        _ if (instruction & 0xF00F == 0x8005) => subxy(emulator, instruction), // 8xy5
-        _ if (instruction & 0xF00F == 0x8006) => shrxy(emulator, instruction), // 8xy6
-        _ if (instruction & 0xF00F == 0x8007) => subnxy(emulator, instruction), // 8xy7
-        _ if (instruction & 0xF00F == 0x800E) => shlxy(emulator, instruction), // 8xyE
-        _ if (instruction & 0xF00F == 0x9000) => snexy(emulator, instruction), // 9xy0
-        _ if (instruction & 0xF000 == 0xA000) => ldi(emulator, instruction), // Annn
-        _ if (instruction & 0xF000 == 0xB000) => jpv(emulator, instruction), // Bnnn
-        _ if (instruction & 0xF000 == 0xC000) => rnd(emulator, instruction), // Cxkk
-        _ if (instruction & 0xF000 == 0xD000) => drw(emulator, instruction), // Dxyn
-        _ if (instruction & 0xF0FF == 0xE09E) => skp(emulator, instruction), // Ex9E
-        _ if (instruction & 0xF0FF == 0xE0A1) => sknp(emulator, instruction), // ExA1
-        _ if (instruction & 0xF0FF == 0xF007) => ldxdt(emulator, instruction), // Fx07
-        _ if (instruction & 0xF0FF == 0xF00A) => ldk(emulator, instruction), // Fx0A
-        _ if (instruction & 0xF0FF == 0xF015) => lddt(emulator, instruction), // Fx15
-        _ if (instruction & 0xF0FF == 0xF018) => ldst(emulator, instruction), // Fx18
-        _ if (instruction & 0xF0FF == 0xF01E) => addi(emulator, instruction), // Fx1E
-        _ if (instruction & 0xF0FF == 0xF029) => ldiv(emulator, instruction), // Fx29
-        _ if (instruction & 0xF0FF == 0xF033) => ldb(emulator, instruction), // Fx33
-        _ if (instruction & 0xF0FF == 0xF055) => ldii(emulator, instruction), // Fx55
-        _ if (instruction & 0xF0FF == 0xF065) => ldvi(emulator, instruction), // Fx65
-        _ => {
-            eprintln!("Error! Instruction not supported, please contact developer. Instruction code: {:#?}", instruction);
-            std::process::exit(1);
-        }
+        Instruction::Sys { .. } => sys(),
+        Instruction::Cls => cls(emulator),
+        Instruction::Ret => ret(emulator)?,
+        Instruction::Jp { nnn } => jp(emulator, nnn),
+        Instruction::Call { nnn } => call(emulator, nnn)?,
+        Instruction::Se { x, kk } => se(emulator, x, kk)?,
+        Instruction::Sne { x, kk } => sne(emulator, x, kk)?,
+        Instruction::Sev { x, y } => sev(emulator, x, y)?,
+        Instruction::Ldx { x, kk } => ldx(emulator, x, kk),
+        Instruction::Addx { x, kk } => addx(emulator, x, kk),
+        Instruction::Ldxy { x, y } => ldxy(emulator, x, y),
+        Instruction::Orxy { x, y } => orxy(emulator, x, y),
+        Instruction::Andxy { x, y } => andxy(emulator, x, y),
+        Instruction::Xorxy { x, y } => xorxy(emulator, x, y),
+        Instruction::Addxy { x, y } => addxy(emulator, x, y),
+        Instruction::Subxy { x, y } => subxy(emulator, x, y),
+        Instruction::Shrxy { x, y } => shrxy(emulator, x, y),
+        Instruction::Subnxy { x, y } => subnxy(emulator, x, y),
+        Instruction::Shlxy { x, y } => shlxy(emulator, x, y),
+        Instruction::Snexy { x, y } => snexy(emulator, x, y)?,
+        Instruction::Ldi { nnn } => ldi(emulator, nnn),
+        Instruction::Jpv { nnn } => jpv(emulator, nnn),
+        Instruction::Rnd { x, kk } => rnd(emulator, x, kk),
+        Instruction::Drw { x, y, n } => drw(emulator, x, y, n)?,
+        Instruction::Skp { x } => skp(emulator, x)?,
+        Instruction::Sknp { x } => sknp(emulator, x)?,
+        Instruction::Ldxdt { x } => ldxdt(emulator, x),
+        Instruction::Ldk { x } => ldk(emulator, x),
+        Instruction::Lddt { x } => lddt(emulator, x),
+        Instruction::Ldst { x } => ldst(emulator, x),
+        Instruction::Addi { x } => addi(emulator, x),
+        Instruction::Ldiv { x } => ldiv(emulator, x),
+        Instruction::Ldb { x } => ldb(emulator, x)?,
+        Instruction::Ldii { x } => ldii(emulator, x)?,
+        Instruction::Ldvi { x } => ldvi(emulator, x)?,
+        Instruction::Scd { n } => scd(emulator, n),
+        Instruction::Scr => scr(emulator),
+        Instruction::Scl => scl(emulator),
+        Instruction::Exit => exit(emulator),
+        Instruction::Low => low(emulator),
+        Instruction::High => high(emulator),
+        Instruction::Ldhf { x } => ldhf(emulator, x),
+        Instruction::Ldrv { x } => ldrv(emulator, x),
+        Instruction::Ldvr { x } => ldvr(emulator, x),
+        Instruction::Scu { n } => scu(emulator, n),
+        Instruction::Ldrange { x, y } => ldrange(emulator, x, y)?,
+        Instruction::Ldvrange { x, y } => ldvrange(emulator, x, y)?,
+        Instruction::Ldil => ldil(emulator)?,
+        Instruction::Plane { n } => plane(emulator, n),
+        Instruction::Ldaudio => ldaudio(emulator)?,
+        Instruction::Ldpitch { x } => ldpitch(emulator, x),
+        Instruction::Unknown { .. } => return Err(CpuErrorKind::UnknownOpcode),
     }
+
+    return Ok(());
+}
+
+// checks that len bytes starting at address are inside ram
+fn check_memory(emulator: &Emulator, address: usize, len: usize) -> Result<(), CpuErrorKind> {
+    if address + len > emulator.ram.len() {
+        return Err(CpuErrorKind::MemoryOutOfBounds { address: emulator.ram.len().max(address) });
+    }
+    return Ok(());
+}
+
+// moves the pc over the next instruction, used by the skip instructions.
+// on xo-chip F000 nnnn is 4 bytes long, so it is skipped as a whole
+pub fn skip_next_instruction(emulator: &mut Emulator) -> Result<(), CpuErrorKind> {
+    let pc = emulator.pc as usize;
+    if emulator.platform == Platform::XoChip {
+        check_memory(emulator, pc, 2)?;
+        let next = ((emulator.ram[pc] as u16) << 8) + emulator.ram[pc + 1] as u16;
+        emulator.pc = emulator.pc.wrapping_add(instruction::decode(next).size());
+        return Ok(());
+    }
+
+    emulator.pc = emulator.pc.wrapping_add(2);
+    return Ok(());
 }
 
 ////////////////////////////////// INSTRUCTIONS ///////////////////////////////////////////////
@@ -76,124 +186,142 @@ pub fn sys() {
 
 // 00E0 - CLS
 // clears the display
+// on xo-chip only the selected planes are cleared
 pub fn cls(emulator: &mut Emulator) {
-    crate::display::clear_display(&mut emulator.display_memory);
+    for plane in emulator.selected_planes_mut() {
+        crate::display::clear_display(plane);
+    }
 }
 
 // 00EE - RET
 // Return from a subroutine.
 // The interpreter sets the program counter to the address at the top of the stack, then subtracts 1 from the stack pointer.
-pub fn ret(emulator: &mut Emulator) {
+pub fn ret(emulator: &mut Emulator) -> Result<(), CpuErrorKind> {
+    if emulator.sp == 0 {
+        return Err(CpuErrorKind::StackUnderflow);
+    }
+
     emulator.pc = emulator.stack[emulator.sp as usize];
     emulator.sp -= 1;
+
+    return Ok(());
 }
 
 // 1nnn - JP addr
 // Jump to location nnn.
 // The interpreter sets the program counter to nnn.
-pub fn jp(emulator: &mut Emulator, instruction: u16) {
-    emulator.pc = instruction & 0x0FFF;
+pub fn jp(emulator: &mut Emulator, nnn: u16) {
+    emulator.pc = nnn;
 }
 
 // 2nnn - CALL addr
 // Call subroutine at nnn.
 // The interpreter increments the stack pointer, then puts the current PC on the top of the stack. The PC is then set to nnn.
-pub fn call(emulator: &mut Emulator, instruction: u16) {
+pub fn call(emulator: &mut Emulator, nnn: u16) -> Result<(), CpuErrorKind> {
+    // stack[0] is never used, sp points at the top entry
+    if emulator.sp as usize + 1 >= emulator.stack.len() {
+        return Err(CpuErrorKind::StackOverflow);
+    }
+
     emulator.sp += 1;
     emulator.stack[emulator.sp as usize] = emulator.pc;
-    emulator.pc = instruction & 0x0FFF;
+    emulator.pc = nnn;
+
+    return Ok(());
 }
 
 // 3xkk - SE Vx, byte
 // Skip next instruction if Vx = kk.
 // The interpreter compares register Vx to kk, and if they are equal, increments the program counter by 2.
-pub fn se(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    if emulator.v[x] == ((instruction & 0xFF) as u8) {
-        emulator.pc += 2;
+pub fn se(emulator: &mut Emulator, x: usize, kk: u8) -> Result<(), CpuErrorKind> {
+    if emulator.v[x] == kk {
+        skip_next_instruction(emulator)?;
     }
+
+    return Ok(());
 }
 
 // 4xkk - SNE Vx, byte
 // Skip next instruction if Vx != kk.
 // The interpreter compares register Vx to kk, and if they are not equal, increments the program counter by 2.
-pub fn sne(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    if emulator.v[x] != ((instruction & 0xFF) as u8) {
-        emulator.pc += 2;
+pub fn sne(emulator: &mut Emulator, x: usize, kk: u8) -> Result<(), CpuErrorKind> {
+    if emulator.v[x] != kk {
+        skip_next_instruction(emulator)?;
     }
+
+    return Ok(());
 }
 
 // 5xy0 - SE Vx, Vy
 // Skip next instruction if Vx = Vy.
 // The interpreter compares register Vx to register Vy, and if they are equal, increments the program counter by 2.
-pub fn sev(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
+pub fn sev(emulator: &mut Emulator, x: usize, y: usize) -> Result<(), CpuErrorKind> {
     if emulator.v[x] == emulator.v[y] {
-        emulator.pc += 2;
+        skip_next_instruction(emulator)?;
     }
+
+    return Ok(());
 }
 
 // 6xkk - LD Vx, byte
 // Set Vx = kk
 // The interpreter puts the value kk into register Vx.
-pub fn ldx(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    emulator.v[x] = (instruction & 0xFF) as u8;
+pub fn ldx(emulator: &mut Emulator, x: usize, kk: u8) {
+    emulator.v[x] = kk;
 }
 
 // 7xkk - ADD Vx, byte
 // Set Vx = Vx + kk.
 // Adds the value kk to the value of register Vx, then stores the result in Vx. 
-pub fn addx(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    emulator.v[x] = emulator.v[x].wrapping_add((instruction & 0xFF) as u8);
+pub fn addx(emulator: &mut Emulator, x: usize, kk: u8) {
+    emulator.v[x] = emulator.v[x].wrapping_add(kk);
 }
 
 // 8xy0 - LD Vx, Vy
 // Set Vx = Vy.
 // Stores the value of register Vy in register Vx.
-pub fn ldxy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
+pub fn ldxy(emulator: &mut Emulator, x: usize, y: usize) {
     emulator.v[x] = emulator.v[y];
 }
 
 // 8xy1 - OR Vx, Vy
 // Set Vx = Vx OR Vy.
 // Performs a bitwise OR on the values of Vx and Vy, then stores the result in Vx.
-pub fn orxy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
-    emulator.v[x] = emulator.v[x] | emulator.v[y];
+pub fn orxy(emulator: &mut Emulator, x: usize, y: usize) {
+    emulator.v[x] |= emulator.v[y];
+
+    if emulator.quirks.logic_resets_vf {
+        emulator.v[0xF] = 0;
+    }
 }
 
 // 8xy2 - AND Vx, Vy
 // Set Vx = Vx AND Vy.
 // Performs a bitwise AND on the values of Vx and Vy, then stores the result in Vx.
-pub fn andxy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
-    emulator.v[x] = emulator.v[x] & emulator.v[y];
+pub fn andxy(emulator: &mut Emulator, x: usize, y: usize) {
+    emulator.v[x] &= emulator.v[y];
+
+    if emulator.quirks.logic_resets_vf {
+        emulator.v[0xF] = 0;
+    }
 }
 
 // 8xy3 - XOR Vx, Vy
 // Set Vx = Vx XOR Vy.
 // Performs a bitwise XOR on the values of Vx and Vy, then stores the result in Vx.
-pub fn xorxy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
-    emulator.v[x] = emulator.v[x] ^ emulator.v[y];
+pub fn xorxy(emulator: &mut Emulator, x: usize, y: usize) {
+    emulator.v[x] ^= emulator.v[y];
+
+    if emulator.quirks.logic_resets_vf {
+        emulator.v[0xF] = 0;
+    }
 }
 
 // 8xy4 - ADD Vx, Vy
 // Set Vx = Vx + Vy, set VF = carry.
 // The values of Vx and Vy are added together. If the result is greater than 8 bits (i.e., > 255,) 
 // VF is set to 1, otherwise 0. Only the lowest 8 bits of the result are kept, and stored in Vx.
-pub fn addxy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
+pub fn addxy(emulator: &mut Emulator, x: usize, y: usize) {
     let total: u16 = (emulator.v[x] as u16) + (emulator.v[y] as u16);
 
     emulator.v[0xF] = if total > 0xFF { 1 } else { 0 };
@@ -203,10 +331,7 @@ pub fn addxy(emulator: &mut Emulator, instruction: u16) {
 // 8xy5 - SUB Vx, Vy
 // Set Vx = Vx - Vy, set VF = NOT borrow.
 // If Vx > Vy, then VF is set to 1, otherwise 0. Then Vy is subtracted from Vx, and the results stored in Vx.
-pub fn subxy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
-
+pub fn subxy(emulator: &mut Emulator, x: usize, y: usize) {
     emulator.v[0xF] = if emulator.v[x] > emulator.v[y] { 1 } else { 0 };
     emulator.v[x] = emulator.v[x].wrapping_sub(emulator.v[y]);
 }
@@ -214,20 +339,19 @@ pub fn subxy(emulator: &mut Emulator, instruction: u16) {
 // 8xy6 - SHR Vx {, Vy}
 // Set Vx = Vx SHR 1.
 // If the least-significant bit of Vx is 1, then VF is set to 1, otherwise 0. Then Vx is divided by 2.
-pub fn shrxy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
+// With the shift_uses_vy quirk, Vy is shifted instead and the result stored in Vx.
+pub fn shrxy(emulator: &mut Emulator, x: usize, y: usize) {
+    let value = if emulator.quirks.shift_uses_vy { emulator.v[y] } else { emulator.v[x] };
 
-    emulator.v[0xF] = emulator.v[x] & 0x1;
-    emulator.v[x] = emulator.v[x] / 2;
+    // flag is written last so it wins when x is F
+    emulator.v[x] = value / 2;
+    emulator.v[0xF] = value & 0x1;
 }
 
 // 8xy7 - SUBN Vx, Vy
 // Set Vx = Vy - Vx, set VF = NOT borrow.
 // If Vy > Vx, then VF is set to 1, otherwise 0. Then Vx is subtracted from Vy, and the results stored in Vx.
-pub fn subnxy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
-
+pub fn subnxy(emulator: &mut Emulator, x: usize, y: usize) {
     emulator.v[0xF] = if emulator.v[y] > emulator.v[x] { 1 } else { 0 };
     emulator.v[x] = emulator.v[y].wrapping_sub(emulator.v[x]);
 }
@@ -235,119 +359,157 @@ pub fn subnxy(emulator: &mut Emulator, instruction: u16) {
 // 8xyE - SHL Vx {, Vy}
 // Set Vx = Vx SHL 1.
 // If the most-significant bit of Vx is 1, then VF is set to 1, otherwise to 0. Then Vx is multiplied by 2.
-pub fn shlxy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
+// With the shift_uses_vy quirk, Vy is shifted instead and the result stored in Vx.
+pub fn shlxy(emulator: &mut Emulator, x: usize, y: usize) {
+    let value = if emulator.quirks.shift_uses_vy { emulator.v[y] } else { emulator.v[x] };
 
-    emulator.v[0xF] = if  emulator.v[x] & 0x80 == 0x80 { 1 } else { 0 };
-    emulator.v[x] = emulator.v[x].wrapping_mul(2);
+    // flag is written last so it wins when x is F
+    emulator.v[x] = value.wrapping_mul(2);
+    emulator.v[0xF] = if value & 0x80 == 0x80 { 1 } else { 0 };
 }
 
 // 9xy0 - SNE Vx, Vy
 // Skip next instruction if Vx != Vy.
 // The values of Vx and Vy are compared, and if they are not equal, the program counter is increased by 2.
-pub fn snexy(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
-
+pub fn snexy(emulator: &mut Emulator, x: usize, y: usize) -> Result<(), CpuErrorKind> {
     if emulator.v[x] != emulator.v[y] {
-        emulator.pc += 2;
+        skip_next_instruction(emulator)?;
     }
+
+    return Ok(());
 }
 
 // Annn - LD I, addr
 // Set I = nnn.
 // The value of register I is set to nnn.
-pub fn ldi(emulator: &mut Emulator, instruction: u16) {
-    emulator.i = instruction & 0xFFF;
+pub fn ldi(emulator: &mut Emulator, nnn: u16) {
+    emulator.i = nnn;
 }
 
 // Bnnn - JP V0, addr
 // Jump to location nnn + V0.
 // The program counter is set to nnn plus the value of V0.
-pub fn jpv(emulator: &mut Emulator, instruction: u16) {
-    emulator.pc = (instruction & 0xFFF) + emulator.v[0] as u16;
+// With the jump_uses_vx quirk this is read as Bxnn, and Vx is added instead of V0.
+pub fn jpv(emulator: &mut Emulator, nnn: u16) {
+    let register = if emulator.quirks.jump_uses_vx { (nnn >> 8) as usize } else { 0 };
+    emulator.pc = nnn + emulator.v[register] as u16;
 }
 
 // Cxkk - RND Vx, byte
 // Set Vx = random byte AND kk.
 // The interpreter generates a random number from 0 to 255, which is then ANDed with the value kk. 
 // The results are stored in Vx. See instruction 8xy2 for more information on AND.
-pub fn rnd(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let rng = rand::thread_rng().gen_range(0..=0xFF);
-    emulator.v[x] = ((instruction & 0xFF) as u8) & rng;
+pub fn rnd(emulator: &mut Emulator, x: usize, kk: u8) {
+    let rng = emulator.rng.next_byte();
+    emulator.v[x] = kk & rng;
 }
 
-// Dxyn - DRW Vx, Vy, nibbler
+// Dxyn - DRW Vx, Vy, nibble
 // Display n-byte sprite starting at memory location I at (Vx, Vy), set VF = collision.
 /* 
-he interpreter reads n bytes from memory, starting at the address stored in I. 
+The interpreter reads n bytes from memory, starting at the address stored in I. 
 These bytes are then displayed as sprites on screen at coordinates (Vx, Vy). 
 Sprites are XORed onto the existing screen. If this causes any pixels to be erased, VF is set to 1, otherwise it is set to 0. 
 If the sprite is positioned so part of it is outside the coordinates of the display, it wraps around to the opposite side of the screen.
  See instruction 8xy3 for more information on XOR, and section 2.4, Display, for more information on the Chip-8 screen and sprites.
+With the clip_sprites quirk, the parts of the sprite outside the display are not drawn instead of wrapping.
+The starting coordinates always wrap.
+On super-chip, Dxy0 draws a 16x16 sprite made of 32 bytes, two bytes per row.
 */
-pub fn drw(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let y = hex_util::get_nth_nibble(instruction, 2);
-    let n = hex_util::get_nth_nibble(instruction, 1);
+pub fn drw(emulator: &mut Emulator, x: usize, y: usize, n: usize) -> Result<(), CpuErrorKind> {
+    let display_width = emulator.display_width();
+    let display_height = emulator.display_height();
+    let (sprite_width, rows) = if n == 0 && emulator.platform != Platform::Chip8 { (16, 16) } else { (8, n) };
+    let bytes_per_row = sprite_width / 8;
+
+    let start_x = emulator.v[x] as usize % display_width;
+    let start_y = emulator.v[y] as usize % display_height;
+
+    let planes = (emulator.plane_mask & 0x3).count_ones() as usize;
+    check_memory(emulator, emulator.i as usize, rows * bytes_per_row * planes)?;
 
     emulator.v[0xF] = 0;
 
-    for i in 0..=n {
-        let height = ((y + i) % 32) * 64;
-        // 8 pixels per sprite line
-        for j in 0..8 {
-            let width = (x + j) % 64;
-            let old = emulator.display_memory[width + height];
-            let new = ((emulator.ram[emulator.i as usize + i] >> j) & 0x1) == 0x1;
-
-            // if erased, set vF flag
-            if old && new {
-                emulator.v[0xF] = 1;
-            }
+    // xo-chip draws into every selected plane, the sprite data for each plane follows the previous one in memory
+    let mut address = emulator.i as usize;
+    for plane in 0..2 {
+        if emulator.plane_mask & (1 << plane) == 0 {
+            continue;
+        }
+        let plane_memory = if plane == 0 { &mut emulator.display_memory } else { &mut emulator.second_plane };
 
-            emulator.display_memory[width + height] ^= new;
+        for i in 0..rows {
+            if emulator.quirks.clip_sprites && start_y + i >= display_height {
+                break;
+            }
+            let height = ((start_y + i) % display_height) * display_width;
+
+            // sprite rows are read msb first into the top of a u16, so 8 and 16 pixel wide sprites are handled the same way
+            let row_address = address + i * bytes_per_row;
+            let sprite_line: u16 = if sprite_width == 16 {
+                ((emulator.ram[row_address] as u16) << 8) + emulator.ram[row_address + 1] as u16
+            } else {
+                (emulator.ram[row_address] as u16) << 8
+            };
+
+            for j in 0..sprite_width {
+                if emulator.quirks.clip_sprites && start_x + j >= display_width {
+                    break;
+                }
+                let width = (start_x + j) % display_width;
+                let old = plane_memory[width + height];
+                let new = ((sprite_line >> (15 - j)) & 0x1) == 0x1;
+
+                // if erased, set vF flag
+                if old && new {
+                    emulator.v[0xF] = 1;
+                }
+
+                plane_memory[width + height] ^= new;
+            }
         }
+
+        address += rows * bytes_per_row;
     }
+
+    return Ok(());
 }
 
 // Ex9E - SKP Vx
 // Skip next instruction if key with the value of Vx is pressed.
 // Checks the keyboard, and if the key corresponding to the value of Vx is currently in the down position, PC is increased by 2.
-pub fn skp(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let key = emulator.keys[emulator.v[x] as usize];
+pub fn skp(emulator: &mut Emulator, x: usize) -> Result<(), CpuErrorKind> {
+    let key = emulator.keys[(emulator.v[x] & 0xF) as usize];
     if key {
-        emulator.pc += 2;
+        skip_next_instruction(emulator)?;
     }
+
+    return Ok(());
 }
 
 // ExA1 - SKNP Vx
 // Skip next instruction if key with the value of Vx is not pressed.
 // Checks the keyboard, and if the key corresponding to the value of Vx is currently in the up position, PC is increased by 2.
-pub fn sknp(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    let key = emulator.keys[emulator.v[x] as usize];
+pub fn sknp(emulator: &mut Emulator, x: usize) -> Result<(), CpuErrorKind> {
+    let key = emulator.keys[(emulator.v[x] & 0xF) as usize];
     if !key {
-        emulator.pc += 2;
+        skip_next_instruction(emulator)?;
     }
+
+    return Ok(());
 }
 
 // Fx07 - LD Vx, DT
 // Set Vx = delay timer value.
 // The value of DT is placed into Vx.
-pub fn ldxdt(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
+pub fn ldxdt(emulator: &mut Emulator, x: usize) {
     emulator.v[x] = emulator.delay_timer;
 }
 
 // Fx0A - LD Vx, K
 // Wait for a key press, store the value of the key in Vx.
 // All execution stops until a key is pressed, then the value of that key is stored in Vx.
-pub fn ldk(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-
+pub fn ldk(emulator: &mut Emulator, x: usize) {
     let mut pressed = false;
     for i in 0..emulator.keys.len() {
         if emulator.keys[i] {
@@ -359,40 +521,36 @@ pub fn ldk(emulator: &mut Emulator, instruction: u16) {
 
     if !pressed {
         // Redo opcode
-        emulator.pc -= 2;
+        emulator.pc = emulator.pc.wrapping_sub(2);
     }
 }
 
 // Fx15 - LD DT, Vx
 // Set delay timer = Vx.
 // DT is set equal to the value of Vx.
-pub fn lddt(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
+pub fn lddt(emulator: &mut Emulator, x: usize) {
     emulator.delay_timer = emulator.v[x];
 }
 
 // Fx18 - LD ST, Vx
 // Set sound timer = Vx.
 // ST is set equal to the value of Vx.
-pub fn ldst(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
+pub fn ldst(emulator: &mut Emulator, x: usize) {
     emulator.sound_timer = emulator.v[x];
 }
 
 // Fx1E - ADD I, Vx
 // Set I = I + Vx.
 // The values of I and Vx are added, and the results are stored in I.
-pub fn addi(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
-    emulator.i += emulator.v[x] as u16;
+pub fn addi(emulator: &mut Emulator, x: usize) {
+    emulator.i = emulator.i.wrapping_add(emulator.v[x] as u16);
 }
 
 // Fx29 - LD F, Vx
 // Set I = location of sprite for digit Vx.
 // The value of I is set to the location for the hexadecimal sprite corresponding to the value of Vx. 
 // See section 2.4, Display, for more information on the Chip-8 hexadecimal font.
-pub fn ldiv(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
+pub fn ldiv(emulator: &mut Emulator, x: usize) {
     let c = emulator.v[x] as u16;
     emulator.i = c * 5;
 }
@@ -401,33 +559,196 @@ pub fn ldiv(emulator: &mut Emulator, instruction: u16) {
 // Store BCD representation of Vx in memory locations I, I+1, and I+2.
 // The interpreter takes the decimal value of Vx, and places the hundreds digit in memory at location in I,
 // the tens digit at location I+1, and the ones digit at location I+2.
-pub fn ldb(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
+pub fn ldb(emulator: &mut Emulator, x: usize) -> Result<(), CpuErrorKind> {
     let num = emulator.v[x];
+    check_memory(emulator, emulator.i as usize, 3)?;
 
     emulator.ram[emulator.i as usize] = num / 100;
-    emulator.ram[(emulator.i + 1) as usize] = (num % 100) / 10;
-    emulator.ram[(emulator.i + 2) as usize] = num % 10;
+    emulator.ram[emulator.i as usize + 1] = (num % 100) / 10;
+    emulator.ram[emulator.i as usize + 2] = num % 10;
+
+    return Ok(());
 }
 
 // Fx55 - LD [I], Vx
 // Store registers V0 through Vx in memory starting at location I.
 // The interpreter copies the values of registers V0 through Vx into memory, starting at the address in I.
-pub fn ldii(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
+// With the load_store_increments_i quirk, I is left at I + x + 1.
+pub fn ldii(emulator: &mut Emulator, x: usize) -> Result<(), CpuErrorKind> {
+    check_memory(emulator, emulator.i as usize, x + 1)?;
 
     for i in 0..=x {
         emulator.ram[emulator.i as usize + i] = emulator.v[i];
     }
+
+    if emulator.quirks.load_store_increments_i {
+        emulator.i = emulator.i.wrapping_add(x as u16 + 1);
+    }
+
+    return Ok(());
 }
 
 // Fx65 - LD Vx, [I]
 // Read registers V0 through Vx from memory starting at location I.
 // The interpreter reads values from memory starting at location I into registers V0 through Vx.
-pub fn ldvi(emulator: &mut Emulator, instruction: u16) {
-    let x = hex_util::get_nth_nibble(instruction, 3);
+// With the load_store_increments_i quirk, I is left at I + x + 1.
+pub fn ldvi(emulator: &mut Emulator, x: usize) -> Result<(), CpuErrorKind> {
+    check_memory(emulator, emulator.i as usize, x + 1)?;
 
     for i in 0..=x {
         emulator.v[i] = emulator.ram[emulator.i as usize + i];
     }
+
+    if emulator.quirks.load_store_increments_i {
+        emulator.i = emulator.i.wrapping_add(x as u16 + 1);
+    }
+
+    return Ok(());
+}
+
+////////////////////////////////// SUPER-CHIP INSTRUCTIONS ///////////////////////////////////////////////
+
+// 00Cn - SCD nibble
+// Scroll the display down n lines.
+pub fn scd(emulator: &mut Emulator, n: usize) {
+    let width = emulator.display_width();
+    for plane in emulator.selected_planes_mut() {
+        display::scroll_down(plane, width, n);
+    }
+}
+
+// 00FB - SCR
+// Scroll the display right 4 pixels.
+pub fn scr(emulator: &mut Emulator) {
+    let width = emulator.display_width();
+    for plane in emulator.selected_planes_mut() {
+        display::scroll_right(plane, width, 4);
+    }
+}
+
+// 00FC - SCL
+// Scroll the display left 4 pixels.
+pub fn scl(emulator: &mut Emulator) {
+    let width = emulator.display_width();
+    for plane in emulator.selected_planes_mut() {
+        display::scroll_left(plane, width, 4);
+    }
+}
+
+// 00FD - EXIT
+// Exit the interpreter.
+// There is nothing to return to, so the program counter is kept on this instruction and the program stops here.
+pub fn exit(emulator: &mut Emulator) {
+    emulator.pc = emulator.pc.wrapping_sub(2);
+}
+
+// 00FE - LOW
+// Disable high resolution mode, the display goes back to 64x32.
+pub fn low(emulator: &mut Emulator) {
+    emulator.set_hires(false);
+}
+
+// 00FF - HIGH
+// Enable high resolution mode, the display becomes 128x64.
+pub fn high(emulator: &mut Emulator) {
+    emulator.set_hires(true);
+}
+
+// Fx30 - LD HF, Vx
+// Set I = location of the 8x10 sprite for digit Vx.
+pub fn ldhf(emulator: &mut Emulator, x: usize) {
+    let c = (emulator.v[x] & 0xF) as u16;
+    emulator.i = BIG_FONTSET_ADDRESS as u16 + c * 10;
+}
+
+// Fx75 - LD R, Vx
+// Store registers V0 through Vx in the RPL user flags.
+pub fn ldrv(emulator: &mut Emulator, x: usize) {
+    emulator.rpl[..=x].copy_from_slice(&emulator.v[..=x]);
+}
+
+// Fx85 - LD Vx, R
+// Read registers V0 through Vx from the RPL user flags.
+pub fn ldvr(emulator: &mut Emulator, x: usize) {
+    emulator.v[..=x].copy_from_slice(&emulator.rpl[..=x]);
+}
+
+////////////////////////////////// XO-CHIP INSTRUCTIONS ///////////////////////////////////////////////
+
+// 00Dn - SCU nibble
+// Scroll the display up n lines.
+pub fn scu(emulator: &mut Emulator, n: usize) {
+    let width = emulator.display_width();
+    for plane in emulator.selected_planes_mut() {
+        display::scroll_up(plane, width, n);
+    }
+}
+
+// 5xy2 - LD [I], Vx - Vy
+// Store registers Vx through Vy in memory starting at location I, I is not changed.
+// If x is greater than y the registers are stored in reverse order.
+pub fn ldrange(emulator: &mut Emulator, x: usize, y: usize) -> Result<(), CpuErrorKind> {
+    check_memory(emulator, emulator.i as usize, x.abs_diff(y) + 1)?;
+
+    for (offset, register) in register_range(x, y).enumerate() {
+        emulator.ram[emulator.i as usize + offset] = emulator.v[register];
+    }
+
+    return Ok(());
+}
+
+// 5xy3 - LD Vx - Vy, [I]
+// Read registers Vx through Vy from memory starting at location I, I is not changed.
+// If x is greater than y the registers are loaded in reverse order.
+pub fn ldvrange(emulator: &mut Emulator, x: usize, y: usize) -> Result<(), CpuErrorKind> {
+    check_memory(emulator, emulator.i as usize, x.abs_diff(y) + 1)?;
+
+    for (offset, register) in register_range(x, y).enumerate() {
+        emulator.v[register] = emulator.ram[emulator.i as usize + offset];
+    }
+
+    return Ok(());
+}
+
+// registers x to y inclusive, counting down when x > y
+fn register_range(x: usize, y: usize) -> Box<dyn Iterator<Item = usize>> {
+    if x <= y {
+        return Box::new(x..=y);
+    }
+    return Box::new((y..=x).rev());
+}
+
+// F000 nnnn - LD I, long addr
+// Set I = nnnn, the 16 bit address stored in the two bytes after the instruction.
+pub fn ldil(emulator: &mut Emulator) -> Result<(), CpuErrorKind> {
+    let pc = emulator.pc as usize;
+    check_memory(emulator, pc, 2)?;
+
+    emulator.i = ((emulator.ram[pc] as u16) << 8) + emulator.ram[pc + 1] as u16;
+    emulator.pc = emulator.pc.wrapping_add(2);
+
+    return Ok(());
+}
+
+// Fn01 - PLANE n
+// Select the drawing planes with the bitmask n, 0 draws nothing and 3 draws to both planes.
+pub fn plane(emulator: &mut Emulator, n: usize) {
+    emulator.plane_mask = (n & 0x3) as u8;
+}
+
+// F002 - AUDIO
+// Load the 16 byte audio pattern buffer from memory starting at location I.
+pub fn ldaudio(emulator: &mut Emulator) -> Result<(), CpuErrorKind> {
+    let start = emulator.i as usize;
+    check_memory(emulator, start, 0x10)?;
+
+    emulator.audio_pattern.copy_from_slice(&emulator.ram[start..start + 0x10]);
+
+    return Ok(());
+}
+
+// Fx3A - PITCH Vx
+// Set the playback rate of the audio pattern to Vx.
+pub fn ldpitch(emulator: &mut Emulator, x: usize) {
+    emulator.pitch = emulator.v[x];
 }
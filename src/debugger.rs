@@ -0,0 +1,308 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::cpu::{self, CpuError};
+use crate::emulator::Emulator;
+use crate::instruction;
+use crate::timer;
+
+pub const PROMPT: &str = "(chip8) ";
+
+const HELP: &str = "\
+step [N]             s  run N instructions, 1 if not given
+continue             c  run until a breakpoint or error
+break [ADDR]         b  stop when the pc reaches ADDR, lists breakpoints if not given
+delete ADDR|all      d  remove a breakpoint
+registers            r  show the pc, I, stack, V0-VF and the timers
+memory ADDR [LEN]    x  dump LEN bytes of memory, 64 if not given
+list [ADDR] [N]      l  disassemble N instructions around ADDR, the pc if not given
+set REG VALUE           set pc, i, sp, dt, st or V0-VF
+poke ADDR BYTE...       write bytes to memory
+quit                 q  leave the emulator
+numbers are decimal or 0x hex, an empty line repeats the last command";
+
+// why running stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Breakpoint,
+    Error(CpuError),
+}
+
+// A command line debugger driving the cpu one instruction at a time through get_next_instruction and call_instruction.
+// Frontends feed it lines with command and, while running is set, call run_frame once per 60hz frame.
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    pub breakpoints: BTreeSet<u16>,
+    // set by continue, cleared when a breakpoint or error stops the cpu
+    pub running: bool,
+    // set by quit
+    pub quit: bool,
+    pub instructions_per_frame: u32,
+    // instructions run since the timers last ticked, stepping keeps the timers at the same pace as running
+    frame_position: u32,
+    last_command: String,
+    // don't stop at the breakpoint the pc is sitting on when continuing from it
+    resuming: bool,
+}
+
+impl Debugger {
+    pub fn new(instructions_per_frame: u32) -> Self {
+        return Debugger {
+            breakpoints: BTreeSet::new(),
+            running: false,
+            quit: false,
+            instructions_per_frame: instructions_per_frame.max(1),
+            frame_position: 0,
+            last_command: String::new(),
+            resuming: false,
+        };
+    }
+
+    // runs a single instruction, ticking the timers after every instructions_per_frame of them
+    pub fn step_instruction(&mut self, emulator: &mut Emulator) -> Result<(), CpuError> {
+        let opcode = cpu::get_next_instruction(emulator)?;
+        cpu::call_instruction(emulator, opcode)?;
+        self.frame_position += 1;
+        if self.frame_position >= self.instructions_per_frame {
+            self.frame_position = 0;
+            timer::tick(emulator);
+        }
+        return Ok(());
+    }
+
+    // instructions left to run before the timers tick at the end of the frame
+    pub fn instructions_until_tick(&self) -> u32 {
+        return self.instructions_per_frame.saturating_sub(self.frame_position);
+    }
+
+    // starts running, without stopping at a breakpoint the pc is already sitting on
+    pub fn resume(&mut self) {
+        self.running = true;
+        self.resuming = true;
+    }
+
+    // runs what is left of the current frame while running. returns why the cpu stopped, if it did
+    pub fn continue_frame(&mut self, emulator: &mut Emulator) -> Option<Stop> {
+        while self.running {
+            if !self.resuming && self.breakpoints.contains(&emulator.pc) {
+                self.running = false;
+                return Some(Stop::Breakpoint);
+            }
+            self.resuming = false;
+
+            let last_of_frame = self.instructions_until_tick() <= 1;
+            if let Err(error) = self.step_instruction(emulator) {
+                self.running = false;
+                return Some(Stop::Error(error));
+            }
+            if last_of_frame {
+                break;
+            }
+        }
+        return None;
+    }
+
+    // continue_frame with the reason the cpu stopped written out for the command line
+    pub fn run_frame(&mut self, emulator: &mut Emulator) -> Option<String> {
+        return match self.continue_frame(emulator)? {
+            Stop::Breakpoint => Some(format!("breakpoint at {:#05X}\n{}", emulator.pc, list(emulator, emulator.pc, 1))),
+            Stop::Error(error) => Some(format!("stopped: {}", error)),
+        };
+    }
+
+    // runs one command line and returns what to print
+    pub fn command(&mut self, emulator: &mut Emulator, line: &str) -> String {
+        let line = if line.trim().is_empty() { self.last_command.clone() } else { line.trim().to_string() };
+        self.last_command = line.clone();
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((name, args)) = words.split_first() else {
+            return String::new();
+        };
+        let result = match name.to_ascii_lowercase().as_str() {
+            "step" | "s" => self.step(emulator, args),
+            "continue" | "c" => {
+                self.resume();
+                Ok(String::new())
+            }
+            "break" | "b" => self.add_breakpoint(args),
+            "delete" | "d" => self.delete_breakpoint(args),
+            "registers" | "r" => Ok(registers(emulator)),
+            "memory" | "x" => memory(emulator, args),
+            "list" | "l" => {
+                let address = args.first().map(|arg| number(arg)).transpose();
+                let count = args.get(1).map(|arg| number(arg)).transpose();
+                match (address, count) {
+                    (Ok(address), Ok(count)) => Ok(list(emulator, address.map_or(emulator.pc, |a| a as u16), count.unwrap_or(11) as usize)),
+                    (Err(error), _) | (_, Err(error)) => Err(error),
+                }
+            }
+            "set" => set(emulator, args),
+            "poke" => poke(emulator, args),
+            "help" | "h" | "?" => Ok(HELP.to_string()),
+            "quit" | "q" => {
+                self.quit = true;
+                Ok(String::new())
+            }
+            _ => Err(format!("unknown command '{}', try help", name)),
+        };
+        return result.unwrap_or_else(|error| format!("error: {}", error));
+    }
+
+    fn step(&mut self, emulator: &mut Emulator, args: &[&str]) -> Result<String, String> {
+        let count = match args.first() {
+            Some(arg) => number(arg)?,
+            None => 1,
+        };
+        for _ in 0..count {
+            if let Err(error) = self.step_instruction(emulator) {
+                return Ok(format!("stopped: {}", error));
+            }
+        }
+        return Ok(list(emulator, emulator.pc, 1));
+    }
+
+    fn add_breakpoint(&mut self, args: &[&str]) -> Result<String, String> {
+        let Some(arg) = args.first() else {
+            if self.breakpoints.is_empty() {
+                return Ok("no breakpoints".to_string());
+            }
+            let addresses: Vec<String> = self.breakpoints.iter().map(|address| format!("{:#05X}", address)).collect();
+            return Ok(addresses.join("\n"));
+        };
+        let address = address(arg)?;
+        self.breakpoints.insert(address);
+        return Ok(format!("breakpoint at {:#05X}", address));
+    }
+
+    fn delete_breakpoint(&mut self, args: &[&str]) -> Result<String, String> {
+        match args.first() {
+            Some(arg) if arg.eq_ignore_ascii_case("all") => self.breakpoints.clear(),
+            Some(arg) => {
+                let address = address(arg)?;
+                if !self.breakpoints.remove(&address) {
+                    return Err(format!("no breakpoint at {:#05X}", address));
+                }
+            }
+            None => return Err("delete needs an address or all".to_string()),
+        }
+        return Ok(String::new());
+    }
+}
+
+fn number(text: &str) -> Result<u32, String> {
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    return parsed.map_err(|_| format!("'{}' is not a number", text));
+}
+
+fn as_address(value: u32) -> Result<u16, String> {
+    return u16::try_from(value).map_err(|_| format!("{:#X} is not an address", value));
+}
+
+fn as_byte(value: u32) -> Result<u8, String> {
+    return u8::try_from(value).map_err(|_| format!("{:#X} doesn't fit in a byte", value));
+}
+
+fn address(text: &str) -> Result<u16, String> {
+    return as_address(number(text)?);
+}
+
+fn byte(text: &str) -> Result<u8, String> {
+    return as_byte(number(text)?);
+}
+
+pub fn registers(emulator: &Emulator) -> String {
+    let mut text = format!("pc {:#05X}  i {:#05X}  sp {}  dt {}  st {}\n", emulator.pc, emulator.i, emulator.sp, emulator.delay_timer, emulator.sound_timer);
+    for row in 0..2 {
+        let registers: Vec<String> = (row * 8..row * 8 + 8).map(|x| format!("v{:X} {:02X}", x, emulator.v[x])).collect();
+        let _ = writeln!(text, "{}", registers.join("  "));
+    }
+    // stack[0] is never used, the entries are stack[1..=sp]
+    let stack: Vec<String> = emulator.stack[1..=(emulator.sp as usize).min(emulator.stack.len() - 1)].iter().map(|address| format!("{:#05X}", address)).collect();
+    let _ = write!(text, "stack [{}]", stack.join(", "));
+    return text;
+}
+
+// hex dump, 16 bytes to a line
+fn memory(emulator: &Emulator, args: &[&str]) -> Result<String, String> {
+    let Some(start) = args.first() else {
+        return Err("memory needs an address".to_string());
+    };
+    let start = number(start)? as usize;
+    let len = match args.get(1) {
+        Some(len) => number(len)? as usize,
+        None => 64,
+    };
+    if start >= emulator.ram.len() {
+        return Err(format!("{:#X} is past the end of memory", start));
+    }
+    let end = (start + len).min(emulator.ram.len());
+
+    let mut text = String::new();
+    for line_start in (start..end).step_by(16) {
+        let bytes: Vec<String> = emulator.ram[line_start..(line_start + 16).min(end)].iter().map(|byte| format!("{:02X}", byte)).collect();
+        let _ = writeln!(text, "{:04X}: {}", line_start, bytes.join(" "));
+    }
+    return Ok(text.trim_end().to_string());
+}
+
+// count instructions starting a few before address, the one at the pc is marked with >
+pub fn list(emulator: &Emulator, address: u16, count: usize) -> String {
+    let before = (count / 2) as u16;
+    let mut lines = Vec::new();
+    let mut pc = address.saturating_sub(before * 2);
+    for _ in 0..count {
+        let pc_index = pc as usize;
+        if pc_index + 1 >= emulator.ram.len() {
+            break;
+        }
+        let opcode = ((emulator.ram[pc_index] as u16) << 8) | emulator.ram[pc_index + 1] as u16;
+        let marker = if pc == emulator.pc { ">" } else { " " };
+        lines.push(format!("{} {:04X}: {:04X}  {}", marker, pc, opcode, instruction::decode(opcode)));
+        pc = pc.wrapping_add(2);
+    }
+    return lines.join("\n");
+}
+
+fn set(emulator: &mut Emulator, args: &[&str]) -> Result<String, String> {
+    let [register, value] = args else {
+        return Err("set needs a register and a value".to_string());
+    };
+    let value = number(value)?;
+    let register = register.to_ascii_lowercase();
+    match register.as_str() {
+        "pc" => emulator.pc = as_address(value)?,
+        "i" => emulator.i = as_address(value)?,
+        "sp" if (value as usize) < emulator.stack.len() => emulator.sp = value as u8,
+        "sp" => return Err(format!("sp goes up to {}", emulator.stack.len() - 1)),
+        "dt" => emulator.delay_timer = as_byte(value)?,
+        "st" => emulator.sound_timer = as_byte(value)?,
+        _ => {
+            let x = register.strip_prefix('v').and_then(|x| usize::from_str_radix(x, 16).ok()).filter(|x| *x < 0x10);
+            let Some(x) = x else {
+                return Err(format!("unknown register '{}'", register));
+            };
+            emulator.v[x] = as_byte(value)?;
+        }
+    }
+    return Ok(String::new());
+}
+
+fn poke(emulator: &mut Emulator, args: &[&str]) -> Result<String, String> {
+    let Some((start, bytes)) = args.split_first() else {
+        return Err("poke needs an address and bytes".to_string());
+    };
+    let start = number(start)? as usize;
+    let bytes: Vec<u8> = bytes.iter().map(|text| byte(text)).collect::<Result<_, _>>()?;
+    if bytes.is_empty() {
+        return Err("poke needs bytes to write".to_string());
+    }
+    if start + bytes.len() > emulator.ram.len() {
+        return Err(format!("{:#X} is past the end of memory", start + bytes.len() - 1));
+    }
+    emulator.ram[start..start + bytes.len()].copy_from_slice(&bytes);
+    return Ok(String::new());
+}
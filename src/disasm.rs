@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::emulator::Platform;
+use crate::instruction::{self, Instruction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LabelKind {
+    // target of CALL
+    Subroutine,
+    // target of JP or JP V0
+    Jump,
+    // address loaded into I, usually sprite data
+    Data,
+}
+
+// what recursive descent found out about a rom
+#[derive(Debug, Default)]
+pub struct Analysis {
+    // addresses of the first byte of every reachable instruction
+    pub code: BTreeSet<u16>,
+    // addresses referenced by the code, the most specific kind wins when there are several references
+    pub labels: BTreeMap<u16, LabelKind>,
+}
+
+fn read_word(rom: &[u8], start: u16, address: u16) -> Option<u16> {
+    let offset = address.checked_sub(start)? as usize;
+    if offset + 1 >= rom.len() {
+        return None;
+    }
+    return Some(((rom[offset] as u16) << 8) + rom[offset + 1] as u16);
+}
+
+fn add_label(labels: &mut BTreeMap<u16, LabelKind>, address: u16, kind: LabelKind) {
+    let entry = labels.entry(address).or_insert(kind);
+    *entry = (*entry).min(kind);
+}
+
+// follows control flow from the start of the program through every JP, CALL and skip target,
+// so whatever is never reached is treated as data
+pub fn analyze(rom: &[u8], start: u16, platform: Platform) -> Analysis {
+    let mut analysis = Analysis::default();
+    let mut pending = vec![start];
+    let mut claimed: BTreeSet<u16> = BTreeSet::new();
+
+    while let Some(address) = pending.pop() {
+        let mut pc = address;
+        loop {
+            if analysis.code.contains(&pc) || claimed.contains(&pc) {
+                break;
+            }
+            let Some(opcode) = read_word(rom, start, pc) else {
+                break;
+            };
+            // opcodes the platform doesn't have in the 0nnn space run as SYS and carry on, like on the cpu
+            let decoded = instruction::decode(opcode).run_on(platform);
+            if !decoded.is_supported(platform) {
+                break;
+            }
+            if decoded == Instruction::Ldil && read_word(rom, start, pc.wrapping_add(2)).is_none() {
+                break;
+            }
+
+            analysis.code.insert(pc);
+            for offset in 0..decoded.size() {
+                claimed.insert(pc.wrapping_add(offset));
+            }
+            let next = pc.wrapping_add(decoded.size());
+
+            match decoded {
+                Instruction::Jp { nnn } => {
+                    add_label(&mut analysis.labels, nnn, LabelKind::Jump);
+                    pending.push(nnn);
+                    break;
+                }
+                // the target depends on V0, nnn itself is the best guess at the start of a jump table
+                Instruction::Jpv { nnn } => {
+                    add_label(&mut analysis.labels, nnn, LabelKind::Jump);
+                    pending.push(nnn);
+                    break;
+                }
+                Instruction::Call { nnn } => {
+                    add_label(&mut analysis.labels, nnn, LabelKind::Subroutine);
+                    pending.push(nnn);
+                }
+                Instruction::Ret | Instruction::Exit => break,
+                // both outcomes of a skip are reachable, the skipped instruction might be 4 bytes long on xo-chip
+                Instruction::Se { .. } | Instruction::Sne { .. } | Instruction::Sev { .. } | Instruction::Snexy { .. }
+                    | Instruction::Skp { .. } | Instruction::Sknp { .. } => {
+                    let skipped = match read_word(rom, start, next) {
+                        Some(following) if platform == Platform::XoChip => instruction::decode(following).size(),
+                        _ => 2,
+                    };
+                    pending.push(next.wrapping_add(skipped));
+                }
+                Instruction::Ldi { nnn } => add_label(&mut analysis.labels, nnn, LabelKind::Data),
+                Instruction::Ldil => {
+                    let long = read_word(rom, start, pc.wrapping_add(2)).unwrap_or(0);
+                    add_label(&mut analysis.labels, long, LabelKind::Data);
+                }
+                _ => {}
+            }
+
+            pc = next;
+        }
+    }
+
+    // only keep labels for addresses inside the rom
+    let end = start as usize + rom.len();
+    analysis.labels.retain(|address, _| (*address as usize) >= start as usize && (*address as usize) < end);
+
+    return analysis;
+}
+
+pub fn label_name(address: u16, kind: LabelKind) -> String {
+    return match kind {
+        LabelKind::Subroutine => format!("sub_{:03X}", address),
+        LabelKind::Jump => format!("label_{:03X}", address),
+        LabelKind::Data => format!("data_{:03X}", address),
+    };
+}
+
+// one byte of sprite data drawn as 8 pixels
+pub fn sprite_art(byte: u8) -> String {
+    return (0..8).map(|bit| if byte & (0x80 >> bit) != 0 { '#' } else { '.' }).collect();
+}
+
+// readable listing of a rom loaded at start, with labels for every referenced address.
+// code is printed with the mnemonics from technical_reference.htm, data as db lines with the sprite it would draw
+pub fn disassemble(rom: &[u8], start: u16, platform: Platform) -> String {
+    let analysis = analyze(rom, start, platform);
+
+    // a label can only be printed where a line starts, anything pointing into the middle of a line stays a number
+    let mut line_starts = BTreeSet::new();
+    let mut address = start as usize;
+    while address < start as usize + rom.len() {
+        line_starts.insert(address as u16);
+        address += if analysis.code.contains(&(address as u16)) {
+            instruction::decode(read_word(rom, start, address as u16).unwrap_or(0)).size() as usize
+        } else {
+            1
+        };
+    }
+    let name = |address: u16| match analysis.labels.get(&address) {
+        Some(kind) if line_starts.contains(&address) => label_name(address, *kind),
+        _ => format!("{:#05X}", address),
+    };
+
+    let mut listing = String::new();
+    let end = start as usize + rom.len();
+    let mut address = start as usize;
+    while address < end {
+        let pc = address as u16;
+        if let Some(kind) = analysis.labels.get(&pc) {
+            let _ = writeln!(listing, "\n{}:", label_name(pc, *kind));
+        }
+
+        if analysis.code.contains(&pc) {
+            let opcode = read_word(rom, start, pc).unwrap_or(0);
+            let decoded = instruction::decode(opcode).run_on(platform);
+            let (text, raw) = if decoded == Instruction::Ldil {
+                let long = read_word(rom, start, pc.wrapping_add(2)).unwrap_or(0);
+                (format!("LD I, LONG {}", name(long)), format!("{:04X} {:04X}", opcode, long))
+            } else {
+                (instruction::format(decoded, &name), format!("{:04X}", opcode))
+            };
+            let _ = writeln!(listing, "    {:<28}; {:03X}: {}", text, pc, raw);
+            address += decoded.size() as usize;
+        } else {
+            let byte = rom[address - start as usize];
+            let _ = writeln!(listing, "    {:<28}; {:03X}: {}", format!("db {:#04X}", byte), pc, sprite_art(byte));
+            address += 1;
+        }
+    }
+
+    return listing;
+}
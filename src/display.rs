@@ -1,5 +1,49 @@
-pub fn clear_display(display_memory: &mut [bool; 0x800]) {
-    for i in 0..display_memory.len() {
-        display_memory[i] = false;
+pub const LORES_WIDTH: usize = 64;
+pub const LORES_HEIGHT: usize = 32;
+pub const HIRES_WIDTH: usize = 128;
+pub const HIRES_HEIGHT: usize = 64;
+
+// rgb colour of each pixel value, index 0 is the background. plain chip8 only uses the first two, xo-chip uses all four
+pub const PALETTE: [[u8; 3]; 4] = [
+    [255, 255, 255],
+    [0, 0, 0],
+    [170, 170, 170],
+    [85, 85, 85],
+];
+
+pub fn clear_display(display_memory: &mut [bool]) {
+    display_memory.fill(false);
+}
+
+// moves every row down by n pixels, rows scrolled in at the top are blank
+pub fn scroll_down(display_memory: &mut [bool], width: usize, n: usize) {
+    let shift = (n * width).min(display_memory.len());
+    display_memory.copy_within(..display_memory.len() - shift, shift);
+    display_memory[..shift].fill(false);
+}
+
+// moves every row up by n pixels, rows scrolled in at the bottom are blank
+pub fn scroll_up(display_memory: &mut [bool], width: usize, n: usize) {
+    let shift = (n * width).min(display_memory.len());
+    let len = display_memory.len();
+    display_memory.copy_within(shift.., 0);
+    display_memory[len - shift..].fill(false);
+}
+
+// moves every row right by n pixels, columns scrolled in at the left are blank
+pub fn scroll_right(display_memory: &mut [bool], width: usize, n: usize) {
+    let n = n.min(width);
+    for row in display_memory.chunks_mut(width) {
+        row.copy_within(..width - n, n);
+        row[..n].fill(false);
     }
-}
\ No newline at end of file
+}
+
+// moves every row left by n pixels, columns scrolled in at the right are blank
+pub fn scroll_left(display_memory: &mut [bool], width: usize, n: usize) {
+    let n = n.min(width);
+    for row in display_memory.chunks_mut(width) {
+        row.copy_within(n.., 0);
+        row[width - n..].fill(false);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::display;
+use crate::emulator::Emulator;
+use crate::hex_util;
+
+// file formats the display can be written as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // plain (P1) portable bitmap, any lit plane is black
+    Pbm,
+    // indexed png using the display palette, one image pixel per emulator pixel
+    Png,
+    // one character per pixel, see ascii
+    Ascii,
+}
+
+impl Format {
+    // picks the format from a file extension, .pbm, .png or .txt
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = std::path::Path::new(path).extension()?.to_string_lossy().to_ascii_lowercase();
+        return match extension.as_str() {
+            "pbm" => Some(Format::Pbm),
+            "png" => Some(Format::Png),
+            "txt" => Some(Format::Ascii),
+            _ => None,
+        };
+    }
+}
+
+// character for each palette index, the first two match the disassembler's sprite art
+const ASCII_PIXELS: [char; 4] = ['.', '#', '+', '*'];
+
+pub fn dump(emulator: &Emulator, format: Format) -> Vec<u8> {
+    return match format {
+        Format::Pbm => pbm(emulator),
+        Format::Png => png(emulator),
+        Format::Ascii => ascii(emulator).into_bytes(),
+    };
+}
+
+// the display as rows of text, . for background and # for plane 1. xo-chip uses + for plane 2 and * for both
+pub fn ascii(emulator: &Emulator) -> String {
+    let width = emulator.display_width();
+    let mut text = String::new();
+    for y in 0..emulator.display_height() {
+        for x in 0..width {
+            text.push(ASCII_PIXELS[emulator.pixel(y * width + x) as usize]);
+        }
+        text.push('\n');
+    }
+    return text;
+}
+
+pub fn pbm(emulator: &Emulator) -> Vec<u8> {
+    let width = emulator.display_width();
+    let mut text = format!("P1\n{} {}\n", width, emulator.display_height());
+    for y in 0..emulator.display_height() {
+        let row: Vec<&str> = (0..width).map(|x| if emulator.pixel(y * width + x) != 0 { "1" } else { "0" }).collect();
+        text.push_str(&row.join(" "));
+        text.push('\n');
+    }
+    return text.into_bytes();
+}
+
+pub fn png(emulator: &Emulator) -> Vec<u8> {
+    let width = emulator.display_width();
+    let height = emulator.display_height();
+
+    let mut header = Vec::new();
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bit palette indexes, default compression, filtering and no interlacing
+    header.extend([8, 3, 0, 0, 0]);
+
+    let palette: Vec<u8> = display::PALETTE.iter().flatten().copied().collect();
+
+    // every row starts with filter type 0, none
+    let mut pixels = Vec::with_capacity((width + 1) * height);
+    for y in 0..height {
+        pixels.push(0);
+        pixels.extend((0..width).map(|x| emulator.pixel(y * width + x)));
+    }
+
+    let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+    png_chunk(&mut png, b"IHDR", &header);
+    png_chunk(&mut png, b"PLTE", &palette);
+    png_chunk(&mut png, b"IDAT", &zlib_stored(&pixels));
+    png_chunk(&mut png, b"IEND", &[]);
+    return png;
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = hex_util::crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+// zlib stream of uncompressed deflate blocks, the display is small enough that compressing isn't worth the code
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if data.is_empty() { vec![data] } else { data.chunks(0xFFFF).collect() };
+    for (index, block) in blocks.iter().enumerate() {
+        let last = index == blocks.len() - 1;
+        stream.push(last as u8);
+        stream.extend((block.len() as u16).to_le_bytes());
+        stream.extend((!(block.len() as u16)).to_le_bytes());
+        stream.extend(*block);
+    }
+    stream.extend(adler32(data).to_be_bytes());
+    return stream;
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    return (b << 16) | a;
+}
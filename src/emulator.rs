@@ -1,3 +1,12 @@
+use std::fmt;
+
+use crate::cpu::{self, CpuError};
+use crate::display;
+use crate::quirks::Quirks;
+use crate::rng::{Random, Xorshift};
+#[cfg(feature = "trace")]
+use crate::trace::Tracer;
+
 const FONTSET_SIZE: usize = 80;
 
 const FONTSET: [u8; FONTSET_SIZE] = [
@@ -19,13 +28,137 @@ const FONTSET: [u8; FONTSET_SIZE] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80  // F
 ];
 
+// super-chip 8x10 font used by Fx30, stored in ram right after the small font
+pub const BIG_FONTSET_ADDRESS: usize = FONTSET_SIZE;
+const BIG_FONTSET_SIZE: usize = 160;
+
+const BIG_FONTSET: [u8; BIG_FONTSET_SIZE] = [
+    0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, // 0
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+    0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, // 2
+    0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, // 3
+    0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, // 5
+    0x3E, 0x7C, 0xE0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, // 6
+    0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, // 8
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, // 9
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, // B
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0  // F
+];
+
+// which machine the rom was written for, decides which opcodes are available.
+// ordered so that each platform has every opcode of the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Platform {
+    Chip8,
+    SuperChip,
+    XoChip,
+}
+
+pub const PLATFORM_NAMES: [&str; 3] = ["chip8", "schip", "xochip"];
+
+pub const RAM_SIZE: usize = 0x1000;
+// xo-chip can address 64k of memory through F000 nnnn
+pub const XO_RAM_SIZE: usize = 0x10000;
+
+impl Platform {
+    // looks up a platform by the name used on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name.to_ascii_lowercase().as_str() {
+            "chip8" | "chip-8" => Some(Platform::Chip8),
+            "schip" | "superchip" | "super-chip" => Some(Platform::SuperChip),
+            "xochip" | "xo-chip" => Some(Platform::XoChip),
+            _ => None,
+        };
+    }
+
+    // the name used on the command line
+    pub fn name(&self) -> &'static str {
+        return PLATFORM_NAMES[*self as usize];
+    }
+
+    // quirks used when none are picked explicitly
+    pub fn default_quirks(&self) -> Quirks {
+        return match self {
+            Platform::Chip8 => Quirks::default(),
+            Platform::SuperChip => Quirks::schip(),
+            Platform::XoChip => Quirks::xochip(),
+        };
+    }
+
+    // how many instructions run in each 60hz frame unless told otherwise, roughly the speed roms for the platform expect
+    pub fn default_instructions_per_frame(&self) -> u32 {
+        return match self {
+            Platform::Chip8 => 11,
+            Platform::SuperChip => 30,
+            Platform::XoChip => 1000,
+        };
+    }
+
+    pub fn ram_size(&self) -> usize {
+        return match self {
+            Platform::XoChip => XO_RAM_SIZE,
+            _ => RAM_SIZE,
+        };
+    }
+}
+
+// where roms are loaded unless told otherwise
+pub const PROGRAM_START: u16 = 0x200;
+// the fonts take up memory below this, roms can't be loaded over them
+pub const FONT_END: usize = BIG_FONTSET_ADDRESS + BIG_FONTSET_SIZE;
+
+// why a rom couldn't be loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadError {
+    Empty,
+    // the load address is over the fonts or past the end of memory
+    BadAddress { address: u16, ram_size: usize },
+    // the rom runs past the end of the platform's memory
+    TooBig { size: usize, address: u16, platform: Platform },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            LoadError::Empty => write!(f, "rom is empty"),
+            LoadError::BadAddress { address, ram_size } => {
+                write!(f, "can't load at {:#05X}, roms go between the fonts ending at {:#05X} and the end of memory at {:#05X}", address, FONT_END, ram_size)
+            }
+            LoadError::TooBig { size, address, platform } => {
+                let available = platform.ram_size() - *address as usize;
+                write!(f, "rom is {} bytes but only {} fit between {:#05X} and the end of {}'s {} bytes of memory", size, available, address, platform.name(), platform.ram_size())?;
+                // the usual reason is a rom for a platform with more memory
+                if *size <= XO_RAM_SIZE - *address as usize && *platform != Platform::XoChip {
+                    write!(f, ", xo-chip roms can be up to {} bytes", XO_RAM_SIZE - *address as usize)?;
+                }
+                return Ok(());
+            }
+        };
+    }
+}
+
+impl std::error::Error for LoadError {}
+
 #[derive(Debug)]
 pub struct Emulator {
     pub pc: u16,
     pub sp: u8,
     pub stack: [u16; 0x10], 
-    pub ram: [u8; 0x1000],
-    pub display_memory: [bool; 0x800],
+    // 4k normally, 64k on xo-chip
+    pub ram: Vec<u8>,
+    // one bool per pixel, row by row. 64x32 normally, 128x64 in super-chip high resolution mode
+    pub display_memory: Vec<bool>,
+    // xo-chip second bitplane, same layout as display_memory
+    pub second_plane: Vec<bool>,
+    // bitmask of the planes drawing instructions work on, set by xo-chip Fn01. bit 0 is display_memory
+    pub plane_mask: u8,
+    pub hires: bool,
     pub v: [u8; 0x10],
     pub i: u16,
 
@@ -34,6 +167,25 @@ pub struct Emulator {
     pub sound_timer: u8,
 
     pub keys: [bool; 0x10],
+
+    // which reading of the ambiguous opcodes to use, see quirks.rs
+    pub quirks: Quirks,
+    pub platform: Platform,
+
+    // super-chip RPL user flags, saved and restored by Fx75 / Fx85
+    pub rpl: [u8; 0x10],
+
+    // xo-chip 1-bit audio sample played while the sound timer is running, loaded by F002
+    pub audio_pattern: [u8; 0x10],
+    // xo-chip playback rate of the audio pattern, 4000 * 2^((pitch - 64) / 48) bits per second, set by Fx3A
+    pub pitch: u8,
+
+    // source of Cxkk's random numbers, seeded so runs can be replayed
+    pub rng: Box<dyn Random>,
+
+    // gets every instruction before it runs, see trace.rs. not part of save states
+    #[cfg(feature = "trace")]
+    pub tracer: Option<Box<Tracer>>,
 }
 
 impl Default for Emulator {
@@ -42,17 +194,100 @@ impl Default for Emulator {
             pc: 0x200,
             sp: 0,
             stack: [0; 0x10],
-            ram: [0; 0x1000],
-            display_memory: [false; 0x800],
+            ram: vec![0; RAM_SIZE],
+            display_memory: vec![false; display::LORES_WIDTH * display::LORES_HEIGHT],
+            second_plane: vec![false; display::LORES_WIDTH * display::LORES_HEIGHT],
+            plane_mask: 0x1,
+            hires: false,
             v: [0; 0x10],
             i: 0,
             delay_timer:0,
             sound_timer: 0,
-            keys: [false; 0x10]
+            keys: [false; 0x10],
+            quirks: Quirks::default(),
+            platform: Platform::Chip8,
+            rpl: [0; 0x10],
+            audio_pattern: [0; 0x10],
+            pitch: 64,
+            rng: Box::new(Xorshift::default()),
+            #[cfg(feature = "trace")]
+            tracer: None,
         };
 
         emu.ram[..FONTSET_SIZE].copy_from_slice(&FONTSET);
+        emu.ram[BIG_FONTSET_ADDRESS..BIG_FONTSET_ADDRESS + BIG_FONTSET_SIZE].copy_from_slice(&BIG_FONTSET);
         
         return emu;
     }
-}
\ No newline at end of file
+}
+
+impl Emulator {
+    pub fn display_width(&self) -> usize {
+        return if self.hires { display::HIRES_WIDTH } else { display::LORES_WIDTH };
+    }
+
+    pub fn display_height(&self) -> usize {
+        return if self.hires { display::HIRES_HEIGHT } else { display::LORES_HEIGHT };
+    }
+
+    // switches between 64x32 and 128x64, the display is cleared when the resolution changes
+    pub fn set_hires(&mut self, hires: bool) {
+        self.hires = hires;
+        self.display_memory = vec![false; self.display_width() * self.display_height()];
+        self.second_plane = vec![false; self.display_width() * self.display_height()];
+    }
+
+    // picks the machine to emulate, ram is grown or shrunk to that platform's size keeping what was loaded
+    pub fn set_platform(&mut self, platform: Platform) {
+        self.platform = platform;
+        self.ram.resize(platform.ram_size(), 0);
+    }
+
+    // copies the rom into memory at address and starts running it from there.
+    // set the platform first, the rom has to fit in its memory. nothing is changed if it doesn't
+    pub fn load_rom(&mut self, rom: &[u8], address: u16) -> Result<(), LoadError> {
+        let start = address as usize;
+        if start < FONT_END || start >= self.ram.len() {
+            return Err(LoadError::BadAddress { address, ram_size: self.ram.len() });
+        }
+        if rom.is_empty() {
+            return Err(LoadError::Empty);
+        }
+        if rom.len() > self.ram.len() - start {
+            return Err(LoadError::TooBig { size: rom.len(), address, platform: self.platform });
+        }
+        self.ram[start..start + rom.len()].copy_from_slice(rom);
+        self.pc = address;
+        return Ok(());
+    }
+
+    // runs the instruction at the pc
+    pub fn step(&mut self) -> Result<(), CpuError> {
+        return cpu::execute_next_instruction(self);
+    }
+
+    // runs one 60hz frame, instructions followed by a timer tick, see cpu::execute_frame
+    pub fn run_frame(&mut self, instructions: u32) -> Result<(), CpuError> {
+        return cpu::execute_frame(self, instructions);
+    }
+
+    // the palette index of every pixel row by row, display_width wide and display_height high
+    pub fn framebuffer(&self) -> Vec<u8> {
+        return (0..self.display_memory.len()).map(|index| self.pixel(index)).collect();
+    }
+
+    // palette index of a pixel, bit 0 comes from display_memory and bit 1 from the xo-chip second plane
+    pub fn pixel(&self, index: usize) -> u8 {
+        return (self.display_memory[index] as u8) | ((self.second_plane[index] as u8) << 1);
+    }
+
+    // the planes selected by plane_mask, which drawing, clearing and scrolling apply to
+    pub fn selected_planes_mut(&mut self) -> impl Iterator<Item = &mut Vec<bool>> {
+        let mask = self.plane_mask;
+        return [&mut self.display_memory, &mut self.second_plane]
+            .into_iter()
+            .enumerate()
+            .filter(move |(plane, _)| mask & (1 << plane) != 0)
+            .map(|(_, memory)| memory);
+    }
+}
@@ -0,0 +1,479 @@
+use std::fs;
+use std::time::{Duration, Instant};
+
+use crate::beeper::Pattern;
+use crate::cpu::{self, CpuError};
+use crate::debugger::Debugger;
+use crate::emulator::{Emulator, LoadError, Platform};
+use crate::gdbstub::GdbStub;
+use crate::movie::{self, Movie};
+use crate::quirks::Quirks;
+use crate::rewind::Rewind;
+use crate::rng::Random;
+use crate::savestate;
+use crate::scheduler::Scheduler;
+use crate::screen::Screen;
+#[cfg(feature = "trace")]
+use crate::trace::Tracer;
+
+// Where the display goes. A frontend draws the screen however it likes, a window, a terminal or nowhere.
+pub trait VideoSink {
+    // called after every pass of the run loop, with the screen up to date
+    fn present(&mut self, screen: &Screen);
+
+    // a line about what just happened, such as a state being saved. None once there's nothing to tell
+    fn set_status(&mut self, status: Option<&str>);
+}
+
+// Where the beep goes.
+pub trait AudioSink {
+    // called after every pass of the run loop, true while the beep should sound
+    fn set_playing(&mut self, playing: bool);
+
+    // called before set_playing, the xo-chip audio pattern to sound instead of the beep. None on the other platforms
+    fn set_pattern(&mut self, pattern: Option<Pattern>);
+}
+
+// Where key presses and commands come from.
+pub trait InputSource {
+    // everything that happened since the last poll, oldest first
+    fn poll(&mut self) -> Vec<Input>;
+}
+
+// Where debugger commands come from and where what they print goes. The library never reads or writes
+// the process's stdio itself, the binary hands in a console that does.
+pub trait Console {
+    // lines typed since the last poll, oldest first
+    fn poll(&mut self) -> Vec<String>;
+
+    // what a command printed, or why a running rom stopped
+    fn print(&mut self, output: &str);
+
+    // the debugger is paused and waiting for a command
+    fn prompt(&mut self);
+}
+
+// what a frontend's keys and buttons do, each frontend picks its own keys for them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Quit,
+    // a keypad key went down or up
+    Key { pad: usize, held: bool },
+    // starts the rom again after it stopped on an error, ignored while it runs
+    Reset,
+    SaveState,
+    LoadState,
+    NextSlot,
+    PreviousSlot,
+    Faster,
+    Slower,
+    // frames run backwards while held
+    Rewind { held: bool },
+}
+
+// shows nothing, for running without a display
+pub struct NoVideo;
+
+impl VideoSink for NoVideo {
+    fn present(&mut self, _screen: &Screen) {}
+
+    fn set_status(&mut self, _status: Option<&str>) {}
+}
+
+// stays silent
+pub struct NoAudio;
+
+impl AudioSink for NoAudio {
+    fn set_playing(&mut self, _playing: bool) {}
+
+    fn set_pattern(&mut self, _pattern: Option<Pattern>) {}
+}
+
+// never presses anything
+pub struct NoInput;
+
+impl InputSource for NoInput {
+    fn poll(&mut self) -> Vec<Input> {
+        return Vec::new();
+    }
+}
+
+// takes no commands, for running without a debugger
+pub struct NoConsole;
+
+impl Console for NoConsole {
+    fn poll(&mut self) -> Vec<String> {
+        return Vec::new();
+    }
+
+    fn print(&mut self, _output: &str) {}
+
+    fn prompt(&mut self) {}
+}
+
+// fresh emulator with the rom loaded, used at start up and when resetting after an error
+pub fn create_emulator(platform: Platform, quirks: Quirks, rom: &[u8], load_address: u16, rng: &dyn Random) -> Result<Emulator, LoadError> {
+    let mut emulator = Emulator::default();
+    emulator.set_platform(platform);
+    emulator.quirks = quirks;
+    emulator.rng = rng.duplicate();
+    emulator.load_rom(rom, load_address)?;
+    return Ok(emulator);
+}
+
+// how to start a session, from the command line
+pub struct SessionOptions {
+    pub platform: Platform,
+    pub quirks: Quirks,
+    pub load_address: u16,
+    // the random source the emulator starts with, again after a reset
+    pub rng: Box<dyn Random>,
+    pub scheduler: Scheduler,
+    pub screen: Screen,
+    // Some with --debug, the rom starts paused and the console takes debugger commands
+    pub debugger: Option<Debugger>,
+    // where the debugger's commands come from, NoConsole without one
+    pub console: Box<dyn Console>,
+    // Some with --gdb once gdb has connected, the rom starts paused until gdb continues
+    pub gdb: Option<GdbStub>,
+    // None with --rewind 0
+    pub rewind: Option<Rewind>,
+    // --record writes the session's key presses here when finished
+    pub record_path: Option<String>,
+    // --play runs this movie's key presses, then hands over to the input
+    pub playback: Option<Movie>,
+    // --trace, kept by the emulator through resets, loaded states and rewinding
+    #[cfg(feature = "trace")]
+    pub tracer: Option<Box<Tracer>>,
+}
+
+// A rom being played, with everything around it that doesn't depend on the frontend:
+// running frames as they come due, save state slots, rewinding, movies, the debugger and gdb.
+// A frontend feeds it time and input and shows what comes out, update does one pass of that.
+pub struct Session {
+    pub emulator: Emulator,
+    pub scheduler: Scheduler,
+    pub screen: Screen,
+    rom: Vec<u8>,
+    rom_path: String,
+    rom_hash: u32,
+    platform: Platform,
+    quirks: Quirks,
+    load_address: u16,
+    rng: Box<dyn Random>,
+    debugger: Option<Debugger>,
+    console: Box<dyn Console>,
+    gdb: Option<GdbStub>,
+    rewind: Option<Rewind>,
+    // while held frames go backwards through the rewind buffer
+    rewinding: bool,
+    slot: u8,
+    record_path: Option<String>,
+    recording: Option<Movie>,
+    playback: Option<Movie>,
+    // next frame of the movie being played
+    playback_frame: usize,
+    // set when the rom hits an instruction the cpu can't execute, emulation stops until reset
+    halted: Option<CpuError>,
+}
+
+impl Session {
+    // loads the rom, rom_path is where save states go next to
+    pub fn new(rom: Vec<u8>, rom_path: &str, options: SessionOptions) -> Result<Self, LoadError> {
+        let SessionOptions { platform, quirks, load_address, rng, scheduler, screen, debugger, console, gdb, rewind, record_path, playback, #[cfg(feature = "trace")] tracer } = options;
+        let emulator = create_emulator(platform, quirks, &rom, load_address, rng.as_ref())?;
+        #[cfg(feature = "trace")]
+        let emulator = Emulator { tracer, ..emulator };
+        let rom_hash = savestate::rom_hash(&rom);
+        let recording = record_path.as_ref().map(|_| Movie::new(rom_hash, rng.as_ref(), platform, quirks, load_address, scheduler.instructions_per_frame));
+        let mut console = console;
+        if debugger.is_some() {
+            console.prompt();
+        }
+        return Ok(Session {
+            emulator,
+            scheduler,
+            screen,
+            rom,
+            rom_path: rom_path.to_string(),
+            rom_hash,
+            platform,
+            quirks,
+            load_address,
+            rng,
+            debugger,
+            console,
+            gdb,
+            rewind,
+            rewinding: false,
+            slot: 0,
+            record_path,
+            recording,
+            playback,
+            playback_frame: 0,
+            halted: None,
+        });
+    }
+
+    // the error the rom stopped on, until it's reset or rewound
+    pub fn halted(&self) -> Option<CpuError> {
+        return self.halted;
+    }
+
+    // One pass of the run loop: handles the input, runs the frames that came due in elapsed and shows the result.
+    // false once the input or the debugger asks to quit
+    pub fn update(&mut self, elapsed: Duration, video: &mut impl VideoSink, audio: &mut impl AudioSink, input: &mut impl InputSource) -> bool {
+        for event in input.poll() {
+            if !self.handle(event, video) {
+                return false;
+            }
+        }
+
+        // debugger commands typed since the last pass
+        if let Some(debugger) = &mut self.debugger {
+            for line in self.console.poll() {
+                let output = debugger.command(&mut self.emulator, &line);
+                if !output.is_empty() {
+                    self.console.print(&output);
+                }
+                if debugger.quit {
+                    return false;
+                }
+                if !debugger.running {
+                    self.console.prompt();
+                }
+            }
+        }
+
+        // gdb packets sent since the last pass. once gdb is gone the rom carries on by itself
+        if let Some(stub) = &mut self.gdb {
+            if let Err(error) = stub.poll(&mut self.emulator) {
+                video.set_status(Some(&format!("lost the gdb connection: {}", error)));
+                stub.closed = true;
+            }
+            if stub.closed {
+                video.set_status(Some("gdb detached"));
+                self.gdb = None;
+            }
+        }
+
+        // run every frame that has come due since the last pass, the timers tick once per frame
+        for _ in 0..self.scheduler.frames_due(elapsed) {
+            if !self.frame(video) {
+                break;
+            }
+        }
+
+        // draw, and beep for as long as the sound timer is running, xo-chip roms choose what the beep sounds like
+        self.screen.update(&self.emulator);
+        video.present(&self.screen);
+        let paused = self.debugger.as_ref().is_some_and(|debugger| !debugger.running) || self.gdb.as_ref().is_some_and(|stub| !stub.debugger.running);
+        let pattern = Pattern { bits: self.emulator.audio_pattern, pitch: self.emulator.pitch };
+        audio.set_pattern(if self.emulator.platform == Platform::XoChip { Some(pattern) } else { None });
+        audio.set_playing(self.emulator.sound_timer > 0 && self.halted.is_none() && !paused);
+        return true;
+    }
+
+    // Writes the movie being recorded and the end of the trace. Ok with where the movie went if there was one,
+    // otherwise what couldn't be written, both are tried either way
+    pub fn finish(self) -> Result<Option<String>, String> {
+        let mut errors = Vec::new();
+        #[cfg(feature = "trace")]
+        if let Err(error) = finish_trace(self.emulator.tracer) {
+            errors.push(format!("Couldn't write the trace: {}", error));
+        }
+        let mut recorded = None;
+        if let (Some(path), Some(movie)) = (self.record_path, self.recording) {
+            match fs::write(&path, movie::save(&movie)) {
+                Ok(()) => recorded = Some(format!("Recorded {} frames to {}", movie.frames.len(), path)),
+                Err(error) => errors.push(format!("Couldn't write movie '{}': {}", path, error)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+        return Ok(recorded);
+    }
+
+    // swaps in a reset, loaded or rewound machine, a trace carries on into it
+    fn replace_emulator(&mut self, emulator: Emulator) {
+        #[cfg(feature = "trace")]
+        let tracer = self.emulator.tracer.take();
+        self.emulator = emulator;
+        #[cfg(feature = "trace")]
+        {
+            self.emulator.tracer = tracer;
+        }
+    }
+
+    // loading states and changing speed are off while a movie records or plays. false to quit
+    fn handle(&mut self, event: Input, video: &mut impl VideoSink) -> bool {
+        let movie = self.recording.is_some() || self.playback.is_some();
+        match event {
+            Input::Quit => return false,
+            Input::Reset if self.halted.is_some() => {
+                let emulator = create_emulator(self.platform, self.quirks, &self.rom, self.load_address, self.rng.as_ref()).expect("the rom loaded at start up");
+                self.replace_emulator(emulator);
+                self.halted = None;
+                if let Some(rewind) = &mut self.rewind {
+                    rewind.clear();
+                }
+                // a movie starts again from the top along with the rom
+                if let Some(movie) = &mut self.recording {
+                    movie.frames.clear();
+                }
+                self.playback_frame = 0;
+                video.set_status(None);
+            }
+            Input::Reset => {}
+            Input::SaveState => {
+                let path = savestate::slot_path(&self.rom_path, self.slot);
+                let message = match fs::write(&path, savestate::save(&self.emulator, self.rom_hash)) {
+                    Ok(()) => format!("saved slot {}", self.slot),
+                    Err(error) => format!("couldn't save slot {}: {}", self.slot, error),
+                };
+                video.set_status(Some(&message));
+            }
+            Input::LoadState | Input::Faster | Input::Slower if movie => {
+                video.set_status(Some("loading states and changing speed would put the movie out of step"));
+            }
+            Input::LoadState => {
+                let path = savestate::slot_path(&self.rom_path, self.slot);
+                let loaded = fs::read(&path).map_err(|error| error.to_string())
+                    .and_then(|data| savestate::load(&data, self.rom_hash).map_err(|error| error.to_string()));
+                // a state that can't be loaded leaves the running game as it was
+                let message = match loaded {
+                    Ok(state) => {
+                        self.replace_emulator(state);
+                        self.halted = None;
+                        if let Some(rewind) = &mut self.rewind {
+                            rewind.clear();
+                        }
+                        format!("loaded slot {}", self.slot)
+                    }
+                    Err(error) => format!("couldn't load slot {}: {}", self.slot, error),
+                };
+                video.set_status(Some(&message));
+            }
+            Input::NextSlot | Input::PreviousSlot => {
+                let step = if event == Input::NextSlot { 1 } else { savestate::SLOTS - 1 };
+                self.slot = (self.slot + step) % savestate::SLOTS;
+                video.set_status(Some(&format!("slot {}", self.slot)));
+            }
+            Input::Faster | Input::Slower => {
+                if event == Input::Faster {
+                    self.scheduler.faster();
+                } else {
+                    self.scheduler.slower();
+                }
+                video.set_status(Some(&format!("{} instructions per frame", self.scheduler.instructions_per_frame)));
+            }
+            Input::Rewind { held } => {
+                self.rewinding = held && self.rewind.is_some() && self.playback.is_none();
+            }
+            Input::Key { pad, held } => {
+                if let Some(key) = self.emulator.keys.get_mut(pad) {
+                    *key = held;
+                }
+            }
+        }
+        return true;
+    }
+
+    // runs one frame that came due, or takes one back while rewinding. false when there's nothing to run this pass
+    fn frame(&mut self, video: &mut impl VideoSink) -> bool {
+        // a frame back for every frame due, which also backs out of an error
+        if let Some(rewind) = self.rewind.as_mut().filter(|_| self.rewinding) {
+            if let Some(mut state) = rewind.step_back() {
+                state.keys = self.emulator.keys;
+                self.replace_emulator(state);
+                // the frame taken back is taken out of the movie too
+                if let Some(movie) = &mut self.recording {
+                    movie.frames.pop();
+                }
+                if self.halted.take().is_some() {
+                    video.set_status(None);
+                }
+            }
+            return true;
+        }
+        if self.halted.is_some() {
+            return false;
+        }
+        // the debugger runs the cpu itself and only while continuing
+        if let Some(debugger) = &mut self.debugger {
+            debugger.instructions_per_frame = self.scheduler.instructions_per_frame;
+            if !debugger.running {
+                return false;
+            }
+            if let Some(message) = debugger.run_frame(&mut self.emulator) {
+                self.console.print(&message);
+                self.console.prompt();
+            }
+            return true;
+        }
+        // so does gdb
+        if let Some(stub) = &mut self.gdb {
+            stub.debugger.instructions_per_frame = self.scheduler.instructions_per_frame;
+            if !stub.debugger.running {
+                return false;
+            }
+            if let Err(error) = stub.run_frame(&mut self.emulator) {
+                video.set_status(Some(&format!("lost the gdb connection: {}", error)));
+                stub.closed = true;
+            }
+            return true;
+        }
+
+        // a movie holds its keys until it ends, then the input takes over
+        if self.playback.as_ref().is_some_and(|movie| self.playback_frame >= movie.frames.len()) {
+            video.set_status(Some(&format!("movie finished after {} frames", self.playback_frame)));
+            self.playback = None;
+            self.emulator.keys = [false; 0x10];
+        }
+        if let Some(movie) = &mut self.recording {
+            movie.record(&self.emulator.keys);
+        }
+        let result = match &self.playback {
+            Some(movie) => {
+                self.playback_frame += 1;
+                movie.play_frame(&mut self.emulator, self.playback_frame - 1).map(|_| ())
+            }
+            None => cpu::execute_frame(&mut self.emulator, self.scheduler.instructions_per_frame),
+        };
+        if let Err(error) = result {
+            video.set_status(Some(&format!("{} - press enter to reset", error)));
+            self.halted = Some(error);
+        }
+        // every frame run is kept, the one that stopped on an error too, so rewinding lines up with the movie
+        if let Some(rewind) = &mut self.rewind {
+            rewind.push(&self.emulator);
+        }
+        return true;
+    }
+}
+
+// Runs the session in real time until the input asks to quit, sleeping between passes until the next frame is due.
+// Frontends that keep time some other way call update themselves.
+pub fn run(session: &mut Session, video: &mut impl VideoSink, audio: &mut impl AudioSink, input: &mut impl InputSource) {
+    let mut last_frame = Instant::now();
+    loop {
+        let now = Instant::now();
+        let elapsed = now - last_frame;
+        last_frame = now;
+        if !session.update(elapsed, video, audio, input) {
+            break;
+        }
+        // measured from now so the time spent drawing counts
+        std::thread::sleep(session.scheduler.until_next_frame().saturating_sub(last_frame.elapsed()));
+    }
+}
+
+// writes out what's left of a trace, taken from the emulator that ran it
+#[cfg(feature = "trace")]
+pub fn finish_trace(tracer: Option<Box<Tracer>>) -> std::io::Result<()> {
+    return match tracer {
+        Some(tracer) => tracer.finish(),
+        None => Ok(()),
+    };
+}
@@ -0,0 +1,370 @@
+use std::fmt::Write as _;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use crate::debugger::{Debugger, Stop};
+use crate::emulator::Emulator;
+
+// stop signals sent back to gdb
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+// interrupt byte gdb sends outside of a packet when ctrl-c is pressed
+const INTERRUPT: u8 = 0x03;
+
+// register numbers as laid out in target_xml and in g packets
+const REGISTER_I: usize = 16;
+const REGISTER_PC: usize = 17;
+const REGISTER_SP: usize = 18;
+const REGISTER_DT: usize = 19;
+const REGISTER_ST: usize = 20;
+const REGISTER_COUNT: usize = 21;
+
+// the register file gdb is told about, V0-VF first. multi byte registers go over the wire little endian
+pub fn target_xml() -> String {
+    let mut xml = String::from("<?xml version=\"1.0\"?>\n<!DOCTYPE target SYSTEM \"gdb-target.dtd\">\n<target version=\"1.0\">\n");
+    xml.push_str("  <feature name=\"org.grep-chip8.cpu\">\n");
+    for x in 0..0x10 {
+        let _ = writeln!(xml, "    <reg name=\"v{:x}\" bitsize=\"8\" type=\"uint8\" regnum=\"{}\"/>", x, x);
+    }
+    let _ = writeln!(xml, "    <reg name=\"i\" bitsize=\"16\" type=\"data_ptr\" regnum=\"{}\"/>", REGISTER_I);
+    let _ = writeln!(xml, "    <reg name=\"pc\" bitsize=\"16\" type=\"code_ptr\" regnum=\"{}\"/>", REGISTER_PC);
+    let _ = writeln!(xml, "    <reg name=\"sp\" bitsize=\"8\" type=\"uint8\" regnum=\"{}\"/>", REGISTER_SP);
+    let _ = writeln!(xml, "    <reg name=\"dt\" bitsize=\"8\" type=\"uint8\" regnum=\"{}\"/>", REGISTER_DT);
+    let _ = writeln!(xml, "    <reg name=\"st\" bitsize=\"8\" type=\"uint8\" regnum=\"{}\"/>", REGISTER_ST);
+    xml.push_str("  </feature>\n</target>\n");
+    return xml;
+}
+
+// frames a packet as $data#checksum
+pub fn packet(data: &str) -> String {
+    return format!("${}#{:02x}", data, checksum(data.as_bytes()));
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    return data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    return bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    return (0..text.len()).step_by(2).map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok()).collect();
+}
+
+fn parse_hex(text: &str) -> Option<usize> {
+    return usize::from_str_radix(text, 16).ok();
+}
+
+// A gdb remote serial protocol server for one connected gdb.
+// Like the debugger it's driven by the frontend: poll handles whatever gdb sent since the last pass and,
+// while the debugger is running, run_frame runs the cpu for a frame and tells gdb when it stops.
+pub struct GdbStub {
+    stream: TcpStream,
+    // breakpoints, the instruction stepping that keeps the timers ticking and whether c has the cpu running
+    pub debugger: Debugger,
+    // set when gdb detaches, kills the program or hangs up
+    pub closed: bool,
+    // bytes received that don't make a whole packet yet
+    input: Vec<u8>,
+    // resent when gdb asks for it with a -
+    last_reply: String,
+    // gdb turned off + / - acknowledgements with QStartNoAckMode
+    no_ack: bool,
+}
+
+impl GdbStub {
+    // waits on localhost for gdb to connect
+    pub fn listen(port: u16, instructions_per_frame: u32) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let (stream, _) = listener.accept()?;
+        return GdbStub::new(stream, instructions_per_frame);
+    }
+
+    pub fn new(stream: TcpStream, instructions_per_frame: u32) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        return Ok(GdbStub {
+            stream,
+            debugger: Debugger::new(instructions_per_frame),
+            closed: false,
+            input: Vec::new(),
+            last_reply: String::new(),
+            no_ack: false,
+        });
+    }
+
+    // reads and answers everything gdb has sent without waiting for more
+    pub fn poll(&mut self, emulator: &mut Emulator) -> io::Result<()> {
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.closed = true;
+                    break;
+                }
+                Ok(count) => self.input.extend_from_slice(&buffer[..count]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            }
+        }
+
+        while let Some(packet) = self.next_packet()? {
+            if let Some(reply) = self.handle(emulator, &packet) {
+                self.reply(&reply)?;
+            }
+        }
+        return Ok(());
+    }
+
+    // takes the next whole packet off the input, answering acks and interrupts on the way
+    fn next_packet(&mut self) -> io::Result<Option<String>> {
+        while let Some(&first) = self.input.first() {
+            match first {
+                b'$' => {
+                    let Some(end) = self.input.iter().position(|byte| *byte == b'#') else {
+                        return Ok(None);
+                    };
+                    if self.input.len() < end + 3 {
+                        return Ok(None);
+                    }
+                    let data = self.input[1..end].to_vec();
+                    let sent = std::str::from_utf8(&self.input[end + 1..end + 3]).ok().and_then(|sum| u8::from_str_radix(sum, 16).ok());
+                    self.input.drain(..end + 3);
+
+                    if !self.no_ack {
+                        let ack = if sent == Some(checksum(&data)) { b"+" } else { b"-" };
+                        self.send(ack)?;
+                        if ack == b"-" {
+                            continue;
+                        }
+                    }
+                    return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+                }
+                b'-' => {
+                    self.input.remove(0);
+                    let last = packet(&self.last_reply);
+                    self.send(last.as_bytes())?;
+                }
+                INTERRUPT => {
+                    self.input.remove(0);
+                    if self.debugger.running {
+                        self.debugger.running = false;
+                        self.reply(&format!("S{:02x}", SIGINT))?;
+                    }
+                }
+                // + acks and noise between packets
+                _ => {
+                    self.input.remove(0);
+                }
+            }
+        }
+        return Ok(None);
+    }
+
+    fn reply(&mut self, data: &str) -> io::Result<()> {
+        self.last_reply = data.to_string();
+        let framed = packet(data);
+        return self.send(framed.as_bytes());
+    }
+
+    // writes all of data, waiting out a full socket buffer
+    fn send(&mut self, data: &[u8]) -> io::Result<()> {
+        let mut written = 0;
+        while written < data.len() {
+            match self.stream.write(&data[written..]) {
+                Ok(count) => written += count,
+                Err(error) if error.kind() == ErrorKind::WouldBlock || error.kind() == ErrorKind::Interrupted => {
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        return Ok(());
+    }
+
+    // runs what is left of the current frame while running, sends gdb the stop reply if the cpu stopped
+    pub fn run_frame(&mut self, emulator: &mut Emulator) -> io::Result<()> {
+        return match self.debugger.continue_frame(emulator) {
+            Some(Stop::Breakpoint) => self.reply(&format!("S{:02x}", SIGTRAP)),
+            Some(Stop::Error(_)) => self.reply(&format!("S{:02x}", SIGILL)),
+            None => Ok(()),
+        };
+    }
+
+    // answers one packet, None when the reply comes later, as for c
+    pub fn handle(&mut self, emulator: &mut Emulator, packet: &str) -> Option<String> {
+        // split after the first character, which might not be ascii in a packet from something that isn't gdb
+        let (command, args) = packet.split_at(packet.chars().next().map_or(0, char::len_utf8));
+        let reply = match command {
+            "?" => format!("S{:02x}", SIGTRAP),
+            "g" => to_hex(&(0..REGISTER_COUNT).flat_map(|register| read_register(emulator, register)).collect::<Vec<u8>>()),
+            "G" => match from_hex(args) {
+                Some(bytes) => write_registers(emulator, &bytes),
+                None => "E01".to_string(),
+            },
+            "p" => match parse_hex(args).filter(|register| *register < REGISTER_COUNT) {
+                Some(register) => to_hex(&read_register(emulator, register)),
+                None => "E01".to_string(),
+            },
+            "P" => {
+                let parsed = args.split_once('=').and_then(|(register, value)| Some((parse_hex(register)?, from_hex(value)?)));
+                match parsed {
+                    Some((register, value)) if register < REGISTER_COUNT => write_register(emulator, register, &value),
+                    _ => "E01".to_string(),
+                }
+            }
+            "m" => {
+                let range = args.split_once(',').and_then(|(address, len)| Some((parse_hex(address)?, parse_hex(len)?)));
+                match range {
+                    Some((address, len)) if address < emulator.ram.len() => to_hex(&emulator.ram[address..address.saturating_add(len).min(emulator.ram.len())]),
+                    _ => "E01".to_string(),
+                }
+            }
+            "M" => {
+                let parsed = args.split_once(':').and_then(|(range, data)| {
+                    let (address, len) = range.split_once(',')?;
+                    return Some((parse_hex(address)?, parse_hex(len)?, from_hex(data)?));
+                });
+                match parsed {
+                    Some((address, len, data)) if data.len() == len && address.saturating_add(len) <= emulator.ram.len() => {
+                        emulator.ram[address..address + len].copy_from_slice(&data);
+                        "OK".to_string()
+                    }
+                    _ => "E01".to_string(),
+                }
+            }
+            "Z" | "z" => self.breakpoint(command == "Z", args),
+            "s" => {
+                self.jump(emulator, args);
+                match self.debugger.step_instruction(emulator) {
+                    Ok(()) => format!("S{:02x}", SIGTRAP),
+                    Err(_) => format!("S{:02x}", SIGILL),
+                }
+            }
+            "c" => {
+                self.jump(emulator, args);
+                self.debugger.resume();
+                return None;
+            }
+            "D" => {
+                self.closed = true;
+                "OK".to_string()
+            }
+            "k" => {
+                self.closed = true;
+                return None;
+            }
+            "H" => "OK".to_string(),
+            _ => self.query(packet),
+        };
+        return Some(reply);
+    }
+
+    // s and c can say where to carry on from
+    fn jump(&mut self, emulator: &mut Emulator, args: &str) {
+        if let Some(address) = parse_hex(args) {
+            emulator.pc = address as u16;
+        }
+    }
+
+    // Z0 and z0 set and clear software breakpoints, other kinds aren't supported
+    fn breakpoint(&mut self, insert: bool, args: &str) -> String {
+        let mut fields = args.split(',');
+        let (Some("0"), Some(address)) = (fields.next(), fields.next().and_then(parse_hex)) else {
+            return String::new();
+        };
+        let Ok(address) = u16::try_from(address) else {
+            return "E01".to_string();
+        };
+        if insert {
+            self.debugger.breakpoints.insert(address);
+        } else {
+            self.debugger.breakpoints.remove(&address);
+        }
+        return "OK".to_string();
+    }
+
+    // general queries, an empty reply tells gdb a packet isn't supported
+    fn query(&mut self, packet: &str) -> String {
+        if packet.starts_with("qSupported") {
+            return "PacketSize=4000;qXfer:features:read+;QStartNoAckMode+".to_string();
+        }
+        if packet == "QStartNoAckMode" {
+            self.no_ack = true;
+            return "OK".to_string();
+        }
+        if let Some(range) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+            let Some((offset, len)) = range.split_once(',').and_then(|(offset, len)| Some((parse_hex(offset)?, parse_hex(len)?))) else {
+                return "E01".to_string();
+            };
+            let xml = target_xml();
+            let start = offset.min(xml.len());
+            let end = start.saturating_add(len).min(xml.len());
+            let marker = if end == xml.len() { 'l' } else { 'm' };
+            return format!("{}{}", marker, &xml[start..end]);
+        }
+        return match packet {
+            "qAttached" => "1".to_string(),
+            "qC" => "QC1".to_string(),
+            "qfThreadInfo" => "m1".to_string(),
+            "qsThreadInfo" => "l".to_string(),
+            _ => String::new(),
+        };
+    }
+}
+
+fn read_register(emulator: &Emulator, register: usize) -> Vec<u8> {
+    return match register {
+        REGISTER_I => emulator.i.to_le_bytes().to_vec(),
+        REGISTER_PC => emulator.pc.to_le_bytes().to_vec(),
+        REGISTER_SP => vec![emulator.sp],
+        REGISTER_DT => vec![emulator.delay_timer],
+        REGISTER_ST => vec![emulator.sound_timer],
+        x => vec![emulator.v[x]],
+    };
+}
+
+fn register_size(register: usize) -> usize {
+    return if register == REGISTER_I || register == REGISTER_PC { 2 } else { 1 };
+}
+
+fn write_register(emulator: &mut Emulator, register: usize, value: &[u8]) -> String {
+    if value.len() != register_size(register) {
+        return "E01".to_string();
+    }
+    match register {
+        REGISTER_I => emulator.i = u16::from_le_bytes([value[0], value[1]]),
+        REGISTER_PC => emulator.pc = u16::from_le_bytes([value[0], value[1]]),
+        REGISTER_SP if (value[0] as usize) < emulator.stack.len() => emulator.sp = value[0],
+        REGISTER_SP => return "E01".to_string(),
+        REGISTER_DT => emulator.delay_timer = value[0],
+        REGISTER_ST => emulator.sound_timer = value[0],
+        x => emulator.v[x] = value[0],
+    }
+    return "OK".to_string();
+}
+
+// a G packet, every register in g order
+fn write_registers(emulator: &mut Emulator, bytes: &[u8]) -> String {
+    let total: usize = (0..REGISTER_COUNT).map(register_size).sum();
+    if bytes.len() != total {
+        return "E01".to_string();
+    }
+    let mut offset = 0;
+    for register in 0..REGISTER_COUNT {
+        let size = register_size(register);
+        let reply = write_register(emulator, register, &bytes[offset..offset + size]);
+        if reply != "OK" {
+            return reply;
+        }
+        offset += size;
+    }
+    return "OK".to_string();
+}
@@ -1,10 +1,22 @@
 pub fn get_nth_nibble(instruction: u16, n: usize) -> usize {
-    if n > 4 || n <= 0 {
+    if n > 4 || n == 0 {
         println!("Invalid attempt to get nibble, returning 0");
         return 0;
     }
     
     // & the instruction to isolate the nibble, then bitshift to get the value of it
-    return ((instruction & ((0xF << ((n - 1) * 4))))
+    return ((instruction & (0xF << ((n - 1) * 4)))
         >> ((n - 1) * 4)).into();
-}
\ No newline at end of file
+}
+
+// crc-32 as used by png and zip, for checksums of files the emulator writes
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    return !crc;
+}
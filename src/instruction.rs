@@ -0,0 +1,420 @@
+use std::fmt;
+
+use crate::emulator::Platform;
+use crate::hex_util;
+
+// A decoded instruction with its operands.
+// Variant names match the functions in cpu.rs that execute them, see technical_reference.htm for each opcode.
+//   x, y - register indexes
+//   n    - 4 bit nibble
+//   kk   - 8 bit byte
+//   nnn  - 12 bit address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Sys { nnn: u16 },               // 0nnn
+    Cls,                            // 00E0
+    Ret,                            // 00EE
+    Jp { nnn: u16 },                // 1nnn
+    Call { nnn: u16 },              // 2nnn
+    Se { x: usize, kk: u8 },        // 3xkk
+    Sne { x: usize, kk: u8 },       // 4xkk
+    Sev { x: usize, y: usize },     // 5xy0
+    Ldx { x: usize, kk: u8 },       // 6xkk
+    Addx { x: usize, kk: u8 },      // 7xkk
+    Ldxy { x: usize, y: usize },    // 8xy0
+    Orxy { x: usize, y: usize },    // 8xy1
+    Andxy { x: usize, y: usize },   // 8xy2
+    Xorxy { x: usize, y: usize },   // 8xy3
+    Addxy { x: usize, y: usize },   // 8xy4
+    Subxy { x: usize, y: usize },   // 8xy5
+    Shrxy { x: usize, y: usize },   // 8xy6
+    Subnxy { x: usize, y: usize },  // 8xy7
+    Shlxy { x: usize, y: usize },   // 8xyE
+    Snexy { x: usize, y: usize },   // 9xy0
+    Ldi { nnn: u16 },               // Annn
+    Jpv { nnn: u16 },               // Bnnn
+    Rnd { x: usize, kk: u8 },       // Cxkk
+    Drw { x: usize, y: usize, n: usize }, // Dxyn
+    Skp { x: usize },               // Ex9E
+    Sknp { x: usize },              // ExA1
+    Ldxdt { x: usize },             // Fx07
+    Ldk { x: usize },               // Fx0A
+    Lddt { x: usize },              // Fx15
+    Ldst { x: usize },              // Fx18
+    Addi { x: usize },              // Fx1E
+    Ldiv { x: usize },              // Fx29
+    Ldb { x: usize },               // Fx33
+    Ldii { x: usize },              // Fx55
+    Ldvi { x: usize },              // Fx65
+
+    // super-chip
+    Scd { n: usize },               // 00Cn
+    Scr,                            // 00FB
+    Scl,                            // 00FC
+    Exit,                           // 00FD
+    Low,                            // 00FE
+    High,                           // 00FF
+    Ldhf { x: usize },              // Fx30
+    Ldrv { x: usize },              // Fx75
+    Ldvr { x: usize },              // Fx85
+
+    // xo-chip
+    Scu { n: usize },               // 00Dn
+    Ldrange { x: usize, y: usize }, // 5xy2
+    Ldvrange { x: usize, y: usize }, // 5xy3
+    Ldil,                           // F000 nnnn, the address is the next word in memory
+    Plane { n: usize },             // Fn01
+    Ldaudio,                        // F002
+    Ldpitch { x: usize },           // Fx3A
+
+    // any opcode that doesn't match the above
+    Unknown { opcode: u16 },
+}
+
+// finds the instruction for an opcode. every opcode of every platform is decoded,
+// use is_supported to check whether the instruction exists on the platform being emulated
+pub fn decode(opcode: u16) -> Instruction {
+    let x = hex_util::get_nth_nibble(opcode, 3);
+    let y = hex_util::get_nth_nibble(opcode, 2);
+    let n = hex_util::get_nth_nibble(opcode, 1);
+    let kk = (opcode & 0xFF) as u8;
+    let nnn = opcode & 0xFFF;
+
+    return match opcode {
+        0x00E0 => Instruction::Cls,
+        0x00EE => Instruction::Ret,
+        0x00FB => Instruction::Scr,
+        0x00FC => Instruction::Scl,
+        0x00FD => Instruction::Exit,
+        0x00FE => Instruction::Low,
+        0x00FF => Instruction::High,
+        0xF000 => Instruction::Ldil,
+        0xF002 => Instruction::Ldaudio,
+        _ => match (opcode & 0xF000) >> 12 {
+            0x0 if opcode & 0xFFF0 == 0x00C0 => Instruction::Scd { n },
+            0x0 if opcode & 0xFFF0 == 0x00D0 => Instruction::Scu { n },
+            0x0 => Instruction::Sys { nnn },
+            0x1 => Instruction::Jp { nnn },
+            0x2 => Instruction::Call { nnn },
+            0x3 => Instruction::Se { x, kk },
+            0x4 => Instruction::Sne { x, kk },
+            0x5 => match n {
+                0x0 => Instruction::Sev { x, y },
+                0x2 => Instruction::Ldrange { x, y },
+                0x3 => Instruction::Ldvrange { x, y },
+                _ => Instruction::Unknown { opcode },
+            },
+            0x6 => Instruction::Ldx { x, kk },
+            0x7 => Instruction::Addx { x, kk },
+            0x8 => match n {
+                0x0 => Instruction::Ldxy { x, y },
+                0x1 => Instruction::Orxy { x, y },
+                0x2 => Instruction::Andxy { x, y },
+                0x3 => Instruction::Xorxy { x, y },
+                0x4 => Instruction::Addxy { x, y },
+                0x5 => Instruction::Subxy { x, y },
+                0x6 => Instruction::Shrxy { x, y },
+                0x7 => Instruction::Subnxy { x, y },
+                0xE => Instruction::Shlxy { x, y },
+                _ => Instruction::Unknown { opcode },
+            },
+            0x9 if n == 0 => Instruction::Snexy { x, y },
+            0xA => Instruction::Ldi { nnn },
+            0xB => Instruction::Jpv { nnn },
+            0xC => Instruction::Rnd { x, kk },
+            0xD => Instruction::Drw { x, y, n },
+            0xE if kk == 0x9E => Instruction::Skp { x },
+            0xE if kk == 0xA1 => Instruction::Sknp { x },
+            0xF => match kk {
+                0x01 => Instruction::Plane { n: x },
+                0x07 => Instruction::Ldxdt { x },
+                0x0A => Instruction::Ldk { x },
+                0x15 => Instruction::Lddt { x },
+                0x18 => Instruction::Ldst { x },
+                0x1E => Instruction::Addi { x },
+                0x29 => Instruction::Ldiv { x },
+                0x30 => Instruction::Ldhf { x },
+                0x33 => Instruction::Ldb { x },
+                0x3A => Instruction::Ldpitch { x },
+                0x55 => Instruction::Ldii { x },
+                0x65 => Instruction::Ldvi { x },
+                0x75 => Instruction::Ldrv { x },
+                0x85 => Instruction::Ldvr { x },
+                _ => Instruction::Unknown { opcode },
+            },
+            _ => Instruction::Unknown { opcode },
+        },
+    };
+}
+
+// the inverse of decode, builds the opcode for an instruction.
+// operands are masked to the bits they occupy
+pub fn encode(instruction: Instruction) -> u16 {
+    let xy = |base: u16, x: usize, y: usize| base | (((x & 0xF) as u16) << 8) | (((y & 0xF) as u16) << 4);
+    let xkk = |base: u16, x: usize, kk: u8| base | (((x & 0xF) as u16) << 8) | kk as u16;
+    let fx = |x: usize, kk: u16| 0xF000 | (((x & 0xF) as u16) << 8) | kk;
+
+    return match instruction {
+        Instruction::Sys { nnn } => nnn & 0xFFF,
+        Instruction::Cls => 0x00E0,
+        Instruction::Ret => 0x00EE,
+        Instruction::Jp { nnn } => 0x1000 | (nnn & 0xFFF),
+        Instruction::Call { nnn } => 0x2000 | (nnn & 0xFFF),
+        Instruction::Se { x, kk } => xkk(0x3000, x, kk),
+        Instruction::Sne { x, kk } => xkk(0x4000, x, kk),
+        Instruction::Sev { x, y } => xy(0x5000, x, y),
+        Instruction::Ldx { x, kk } => xkk(0x6000, x, kk),
+        Instruction::Addx { x, kk } => xkk(0x7000, x, kk),
+        Instruction::Ldxy { x, y } => xy(0x8000, x, y),
+        Instruction::Orxy { x, y } => xy(0x8001, x, y),
+        Instruction::Andxy { x, y } => xy(0x8002, x, y),
+        Instruction::Xorxy { x, y } => xy(0x8003, x, y),
+        Instruction::Addxy { x, y } => xy(0x8004, x, y),
+        Instruction::Subxy { x, y } => xy(0x8005, x, y),
+        Instruction::Shrxy { x, y } => xy(0x8006, x, y),
+        Instruction::Subnxy { x, y } => xy(0x8007, x, y),
+        Instruction::Shlxy { x, y } => xy(0x800E, x, y),
+        Instruction::Snexy { x, y } => xy(0x9000, x, y),
+        Instruction::Ldi { nnn } => 0xA000 | (nnn & 0xFFF),
+        Instruction::Jpv { nnn } => 0xB000 | (nnn & 0xFFF),
+        Instruction::Rnd { x, kk } => xkk(0xC000, x, kk),
+        Instruction::Drw { x, y, n } => xy(0xD000, x, y) | (n & 0xF) as u16,
+        Instruction::Skp { x } => fx(x, 0x9E) & 0xEFFF,
+        Instruction::Sknp { x } => fx(x, 0xA1) & 0xEFFF,
+        Instruction::Ldxdt { x } => fx(x, 0x07),
+        Instruction::Ldk { x } => fx(x, 0x0A),
+        Instruction::Lddt { x } => fx(x, 0x15),
+        Instruction::Ldst { x } => fx(x, 0x18),
+        Instruction::Addi { x } => fx(x, 0x1E),
+        Instruction::Ldiv { x } => fx(x, 0x29),
+        Instruction::Ldb { x } => fx(x, 0x33),
+        Instruction::Ldii { x } => fx(x, 0x55),
+        Instruction::Ldvi { x } => fx(x, 0x65),
+        Instruction::Scd { n } => 0x00C0 | (n & 0xF) as u16,
+        Instruction::Scr => 0x00FB,
+        Instruction::Scl => 0x00FC,
+        Instruction::Exit => 0x00FD,
+        Instruction::Low => 0x00FE,
+        Instruction::High => 0x00FF,
+        Instruction::Ldhf { x } => fx(x, 0x30),
+        Instruction::Ldrv { x } => fx(x, 0x75),
+        Instruction::Ldvr { x } => fx(x, 0x85),
+        Instruction::Scu { n } => 0x00D0 | (n & 0xF) as u16,
+        Instruction::Ldrange { x, y } => xy(0x5002, x, y),
+        Instruction::Ldvrange { x, y } => xy(0x5003, x, y),
+        Instruction::Ldil => 0xF000,
+        Instruction::Plane { n } => fx(n, 0x01),
+        Instruction::Ldaudio => 0xF002,
+        Instruction::Ldpitch { x } => fx(x, 0x3A),
+        Instruction::Unknown { opcode } => opcode,
+    };
+}
+
+impl Instruction {
+    // the first platform that has this instruction, each platform has every instruction of the ones before it
+    pub fn platform(&self) -> Platform {
+        return match self {
+            Instruction::Scd { .. } | Instruction::Scr | Instruction::Scl | Instruction::Exit | Instruction::Low | Instruction::High
+                | Instruction::Ldhf { .. } | Instruction::Ldrv { .. } | Instruction::Ldvr { .. } => Platform::SuperChip,
+            Instruction::Scu { .. } | Instruction::Ldrange { .. } | Instruction::Ldvrange { .. } | Instruction::Ldil
+                | Instruction::Plane { .. } | Instruction::Ldaudio | Instruction::Ldpitch { .. } => Platform::XoChip,
+            _ => Platform::Chip8,
+        };
+    }
+
+    pub fn is_supported(&self, platform: Platform) -> bool {
+        return match self {
+            Instruction::Unknown { .. } => false,
+            _ => self.platform() <= platform,
+        };
+    }
+
+    // what the platform runs it as, the 0nnn space belongs to SYS on platforms that don't have the newer opcodes in it
+    pub fn run_on(self, platform: Platform) -> Self {
+        let opcode = encode(self);
+        if !self.is_supported(platform) && opcode & 0xF000 == 0x0000 {
+            return Instruction::Sys { nnn: opcode };
+        }
+        return self;
+    }
+
+    // number of bytes the instruction takes up in memory
+    pub fn size(&self) -> u16 {
+        return match self {
+            Instruction::Ldil => 4,
+            _ => 2,
+        };
+    }
+}
+
+// mnemonic for an instruction, as written in technical_reference.htm.
+// xo-chip mnemonics follow octo. address formats the nnn operands, so a disassembler can put labels there
+pub fn format(instruction: Instruction, address: &dyn Fn(u16) -> String) -> String {
+    return match instruction {
+        Instruction::Sys { nnn } => format!("SYS {}", address(nnn)),
+        Instruction::Cls => "CLS".to_string(),
+        Instruction::Ret => "RET".to_string(),
+        Instruction::Jp { nnn } => format!("JP {}", address(nnn)),
+        Instruction::Call { nnn } => format!("CALL {}", address(nnn)),
+        Instruction::Se { x, kk } => format!("SE V{:X}, {:#04X}", x, kk),
+        Instruction::Sne { x, kk } => format!("SNE V{:X}, {:#04X}", x, kk),
+        Instruction::Sev { x, y } => format!("SE V{:X}, V{:X}", x, y),
+        Instruction::Ldx { x, kk } => format!("LD V{:X}, {:#04X}", x, kk),
+        Instruction::Addx { x, kk } => format!("ADD V{:X}, {:#04X}", x, kk),
+        Instruction::Ldxy { x, y } => format!("LD V{:X}, V{:X}", x, y),
+        Instruction::Orxy { x, y } => format!("OR V{:X}, V{:X}", x, y),
+        Instruction::Andxy { x, y } => format!("AND V{:X}, V{:X}", x, y),
+        Instruction::Xorxy { x, y } => format!("XOR V{:X}, V{:X}", x, y),
+        Instruction::Addxy { x, y } => format!("ADD V{:X}, V{:X}", x, y),
+        Instruction::Subxy { x, y } => format!("SUB V{:X}, V{:X}", x, y),
+        Instruction::Shrxy { x, y } => format!("SHR V{:X}, V{:X}", x, y),
+        Instruction::Subnxy { x, y } => format!("SUBN V{:X}, V{:X}", x, y),
+        Instruction::Shlxy { x, y } => format!("SHL V{:X}, V{:X}", x, y),
+        Instruction::Snexy { x, y } => format!("SNE V{:X}, V{:X}", x, y),
+        Instruction::Ldi { nnn } => format!("LD I, {}", address(nnn)),
+        Instruction::Jpv { nnn } => format!("JP V0, {}", address(nnn)),
+        Instruction::Rnd { x, kk } => format!("RND V{:X}, {:#04X}", x, kk),
+        Instruction::Drw { x, y, n } => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+        Instruction::Skp { x } => format!("SKP V{:X}", x),
+        Instruction::Sknp { x } => format!("SKNP V{:X}", x),
+        Instruction::Ldxdt { x } => format!("LD V{:X}, DT", x),
+        Instruction::Ldk { x } => format!("LD V{:X}, K", x),
+        Instruction::Lddt { x } => format!("LD DT, V{:X}", x),
+        Instruction::Ldst { x } => format!("LD ST, V{:X}", x),
+        Instruction::Addi { x } => format!("ADD I, V{:X}", x),
+        Instruction::Ldiv { x } => format!("LD F, V{:X}", x),
+        Instruction::Ldb { x } => format!("LD B, V{:X}", x),
+        Instruction::Ldii { x } => format!("LD [I], V{:X}", x),
+        Instruction::Ldvi { x } => format!("LD V{:X}, [I]", x),
+        Instruction::Scd { n } => format!("SCD {}", n),
+        Instruction::Scr => "SCR".to_string(),
+        Instruction::Scl => "SCL".to_string(),
+        Instruction::Exit => "EXIT".to_string(),
+        Instruction::Low => "LOW".to_string(),
+        Instruction::High => "HIGH".to_string(),
+        Instruction::Ldhf { x } => format!("LD HF, V{:X}", x),
+        Instruction::Ldrv { x } => format!("LD R, V{:X}", x),
+        Instruction::Ldvr { x } => format!("LD V{:X}, R", x),
+        Instruction::Scu { n } => format!("SCU {}", n),
+        Instruction::Ldrange { x, y } => format!("LD [I], V{:X} - V{:X}", x, y),
+        Instruction::Ldvrange { x, y } => format!("LD V{:X} - V{:X}, [I]", x, y),
+        Instruction::Ldil => "LD I, LONG".to_string(),
+        Instruction::Plane { n } => format!("PLANE {}", n),
+        Instruction::Ldaudio => "AUDIO".to_string(),
+        Instruction::Ldpitch { x } => format!("PITCH V{:X}", x),
+        Instruction::Unknown { opcode } => format!("DW {:#06X}", opcode),
+    };
+}
+
+// an operand as written after a mnemonic, numbers and labels already resolved to their value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    V(usize),               // Vx
+    Range(usize, usize),    // Vx - Vy
+    I,                      // I
+    IndirectI,              // [I]
+    Dt,                     // DT
+    St,                     // ST
+    K,                      // K
+    F,                      // F
+    B,                      // B
+    Hf,                     // HF
+    R,                      // R
+    Long(i64),              // LONG nnnn
+    Value(i64),             // a number
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    UnknownMnemonic,
+    // the mnemonic exists, but not with these operands
+    BadOperands,
+    // the operand at this index doesn't fit the bits the opcode has for it
+    OutOfRange { operand: usize, max: i64 },
+}
+
+// every mnemonic parse understands, case doesn't matter
+pub const MNEMONICS: [&str; 30] = [
+    "SYS", "CLS", "RET", "JP", "CALL", "SE", "SNE", "LD", "ADD", "OR", "AND", "XOR", "SUB", "SHR", "SUBN", "SHL",
+    "RND", "DRW", "SKP", "SKNP", "SCD", "SCR", "SCL", "EXIT", "LOW", "HIGH", "SCU", "PLANE", "AUDIO", "PITCH",
+];
+
+// the inverse of format, finds the instruction for a mnemonic and its operands.
+// LD I, LONG gives Ldil, the address word that follows it is left to the caller
+pub fn parse(mnemonic: &str, operands: &[Operand]) -> Result<Instruction, ParseError> {
+    let value = |index: usize, max: i64| -> Result<i64, ParseError> {
+        return match operands[index] {
+            Operand::Value(value) | Operand::Long(value) if (0..=max).contains(&value) => Ok(value),
+            _ => Err(ParseError::OutOfRange { operand: index, max }),
+        };
+    };
+    let nnn = |index: usize| value(index, 0xFFF).map(|v| v as u16);
+    let kk = |index: usize| value(index, 0xFF).map(|v| v as u8);
+    let n = |index: usize| value(index, 0xF).map(|v| v as usize);
+
+    let mnemonic = mnemonic.to_ascii_uppercase();
+    return match (mnemonic.as_str(), operands) {
+        ("SYS", [Operand::Value(_)]) => Ok(Instruction::Sys { nnn: nnn(0)? }),
+        ("CLS", []) => Ok(Instruction::Cls),
+        ("RET", []) => Ok(Instruction::Ret),
+        ("JP", [Operand::Value(_)]) => Ok(Instruction::Jp { nnn: nnn(0)? }),
+        ("JP", [Operand::V(0), Operand::Value(_)]) => Ok(Instruction::Jpv { nnn: nnn(1)? }),
+        ("CALL", [Operand::Value(_)]) => Ok(Instruction::Call { nnn: nnn(0)? }),
+        ("SE", [Operand::V(x), Operand::Value(_)]) => Ok(Instruction::Se { x: *x, kk: kk(1)? }),
+        ("SE", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Sev { x: *x, y: *y }),
+        ("SNE", [Operand::V(x), Operand::Value(_)]) => Ok(Instruction::Sne { x: *x, kk: kk(1)? }),
+        ("SNE", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Snexy { x: *x, y: *y }),
+        ("LD", [Operand::V(x), Operand::Value(_)]) => Ok(Instruction::Ldx { x: *x, kk: kk(1)? }),
+        ("LD", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Ldxy { x: *x, y: *y }),
+        ("LD", [Operand::I, Operand::Value(_)]) => Ok(Instruction::Ldi { nnn: nnn(1)? }),
+        ("LD", [Operand::I, Operand::Long(_)]) => value(1, 0xFFFF).map(|_| Instruction::Ldil),
+        ("LD", [Operand::V(x), Operand::Dt]) => Ok(Instruction::Ldxdt { x: *x }),
+        ("LD", [Operand::V(x), Operand::K]) => Ok(Instruction::Ldk { x: *x }),
+        ("LD", [Operand::Dt, Operand::V(x)]) => Ok(Instruction::Lddt { x: *x }),
+        ("LD", [Operand::St, Operand::V(x)]) => Ok(Instruction::Ldst { x: *x }),
+        ("LD", [Operand::F, Operand::V(x)]) => Ok(Instruction::Ldiv { x: *x }),
+        ("LD", [Operand::B, Operand::V(x)]) => Ok(Instruction::Ldb { x: *x }),
+        ("LD", [Operand::IndirectI, Operand::V(x)]) => Ok(Instruction::Ldii { x: *x }),
+        ("LD", [Operand::V(x), Operand::IndirectI]) => Ok(Instruction::Ldvi { x: *x }),
+        ("LD", [Operand::Hf, Operand::V(x)]) => Ok(Instruction::Ldhf { x: *x }),
+        ("LD", [Operand::R, Operand::V(x)]) => Ok(Instruction::Ldrv { x: *x }),
+        ("LD", [Operand::V(x), Operand::R]) => Ok(Instruction::Ldvr { x: *x }),
+        ("LD", [Operand::IndirectI, Operand::Range(x, y)]) => Ok(Instruction::Ldrange { x: *x, y: *y }),
+        ("LD", [Operand::Range(x, y), Operand::IndirectI]) => Ok(Instruction::Ldvrange { x: *x, y: *y }),
+        ("ADD", [Operand::V(x), Operand::Value(_)]) => Ok(Instruction::Addx { x: *x, kk: kk(1)? }),
+        ("ADD", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Addxy { x: *x, y: *y }),
+        ("ADD", [Operand::I, Operand::V(x)]) => Ok(Instruction::Addi { x: *x }),
+        ("OR", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Orxy { x: *x, y: *y }),
+        ("AND", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Andxy { x: *x, y: *y }),
+        ("XOR", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Xorxy { x: *x, y: *y }),
+        ("SUB", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Subxy { x: *x, y: *y }),
+        ("SUBN", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Subnxy { x: *x, y: *y }),
+        // SHR Vx shifts Vx into itself, so the result is the same whichever register the shift quirk reads
+        ("SHR", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Shrxy { x: *x, y: *y }),
+        ("SHR", [Operand::V(x)]) => Ok(Instruction::Shrxy { x: *x, y: *x }),
+        ("SHL", [Operand::V(x), Operand::V(y)]) => Ok(Instruction::Shlxy { x: *x, y: *y }),
+        ("SHL", [Operand::V(x)]) => Ok(Instruction::Shlxy { x: *x, y: *x }),
+        ("RND", [Operand::V(x), Operand::Value(_)]) => Ok(Instruction::Rnd { x: *x, kk: kk(1)? }),
+        ("DRW", [Operand::V(x), Operand::V(y), Operand::Value(_)]) => Ok(Instruction::Drw { x: *x, y: *y, n: n(2)? }),
+        ("SKP", [Operand::V(x)]) => Ok(Instruction::Skp { x: *x }),
+        ("SKNP", [Operand::V(x)]) => Ok(Instruction::Sknp { x: *x }),
+        ("SCD", [Operand::Value(_)]) => Ok(Instruction::Scd { n: n(0)? }),
+        ("SCR", []) => Ok(Instruction::Scr),
+        ("SCL", []) => Ok(Instruction::Scl),
+        ("EXIT", []) => Ok(Instruction::Exit),
+        ("LOW", []) => Ok(Instruction::Low),
+        ("HIGH", []) => Ok(Instruction::High),
+        ("SCU", [Operand::Value(_)]) => Ok(Instruction::Scu { n: n(0)? }),
+        ("PLANE", [Operand::Value(_)]) => Ok(Instruction::Plane { n: n(0)? }),
+        ("AUDIO", []) => Ok(Instruction::Ldaudio),
+        ("PITCH", [Operand::V(x)]) => Ok(Instruction::Ldpitch { x: *x }),
+        _ if MNEMONICS.contains(&mnemonic.as_str()) => Err(ParseError::BadOperands),
+        _ => Err(ParseError::UnknownMnemonic),
+    };
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", format(*self, &|nnn| format!("{:#05X}", nnn)));
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// Which keyboard keys press which keys of the 16 key hex keypad.
+// Keys are named the way SDL names them ("Q", "1", "Up", "Space"), case doesn't matter.
+// A keypad key can have several keyboard keys, a keyboard key presses at most one keypad key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: HashMap<String, usize>,
+}
+
+// the cosmac vip keypad laid over the left of a qwerty keyboard
+//   1 2 3 C      1 2 3 4
+//   4 5 6 D      Q W E R
+//   7 8 9 E      A S D F
+//   A 0 B F      Z X C V
+const DEFAULT_LAYOUT: [(&str, usize); 16] = [
+    ("1", 0x1), ("2", 0x2), ("3", 0x3), ("4", 0xC),
+    ("Q", 0x4), ("W", 0x5), ("E", 0x6), ("R", 0xD),
+    ("A", 0x7), ("S", 0x8), ("D", 0x9), ("F", 0xE),
+    ("Z", 0xA), ("X", 0x0), ("C", 0xB), ("V", 0xF),
+];
+
+// a binding or config file line that couldn't be read. line counts from 1 and is 0 for command line bindings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeymapError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self.line {
+            0 => write!(f, "{}", self.message),
+            line => write!(f, "line {}: {}", line, self.message),
+        };
+    }
+}
+
+impl std::error::Error for KeymapError {}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Keymap { bindings: HashMap::new() };
+        for (key, pad) in DEFAULT_LAYOUT {
+            keymap.bindings.insert(key.to_ascii_lowercase(), pad);
+        }
+        return keymap;
+    }
+}
+
+impl Keymap {
+    // the keypad key a keyboard key presses
+    pub fn pad_key(&self, key_name: &str) -> Option<usize> {
+        return self.bindings.get(&key_name.to_ascii_lowercase()).copied();
+    }
+
+    // the keyboard keys bound to a keypad key, sorted
+    pub fn keys_for(&self, pad: usize) -> Vec<String> {
+        let mut keys: Vec<String> = self.bindings.iter().filter(|(_, bound)| **bound == pad).map(|(key, _)| key.clone()).collect();
+        keys.sort();
+        return keys;
+    }
+
+    // binds keyboard keys to a keypad key, replacing whatever was bound to it before
+    pub fn bind(&mut self, pad: usize, keys: &[&str]) {
+        self.bindings.retain(|_, bound| *bound != pad);
+        for key in keys {
+            self.bindings.insert(key.to_ascii_lowercase(), pad);
+        }
+    }
+
+    // applies a binding written as PAD=KEY[,KEY...], for example "5=W" or "a = Keypad 8, Space"
+    pub fn apply(&mut self, binding: &str) -> Result<(), KeymapError> {
+        let Some((pad, keys)) = binding.split_once('=') else {
+            return Err(KeymapError { line: 0, message: format!("expected PAD=KEY, found '{}'", binding) });
+        };
+        let pad = pad.trim();
+        let index = match usize::from_str_radix(pad, 16) {
+            Ok(index) if index < 0x10 => index,
+            _ => return Err(KeymapError { line: 0, message: format!("'{}' is not a keypad key, expected 0-F", pad) }),
+        };
+        let keys: Vec<&str> = keys.split(',').map(str::trim).filter(|key| !key.is_empty()).collect();
+        if keys.is_empty() {
+            return Err(KeymapError { line: 0, message: format!("no keyboard key given for keypad key {:X}", index) });
+        }
+        self.bind(index, &keys);
+        return Ok(());
+    }
+
+    // reads bindings from a config file on top of this keymap. bindings before the first section apply to every rom,
+    // the ones in a [section] only when the section names the rom, either its file name or its crc-32 in hex
+    //   ; keypad = keyboard keys
+    //   5 = Up
+    //   [pong.ch8]
+    //   1 = W
+    //   4 = S
+    pub fn apply_config(&mut self, config: &str, rom_name: &str, rom_hash: u32) -> Result<(), KeymapError> {
+        let mut active = true;
+        for (index, line) in config.lines().enumerate() {
+            let line = match line.find(';') {
+                Some(comment) => &line[..comment],
+                None => line,
+            }.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[') {
+                let Some(section) = section.strip_suffix(']') else {
+                    return Err(KeymapError { line: index + 1, message: format!("unclosed section '{}'", line) });
+                };
+                let section = section.trim();
+                active = section.eq_ignore_ascii_case(rom_name) || u32::from_str_radix(section, 16) == Ok(rom_hash);
+                continue;
+            }
+            if active {
+                self.apply(line).map_err(|error| KeymapError { line: index + 1, message: error.message })?;
+            }
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,37 @@
+#![allow(clippy::needless_return, clippy::field_reassign_with_default)]
+
+// The emulator without a frontend. Emulator holds the machine, load_rom puts a rom in it,
+// step and run_frame run it and framebuffer reads the display, everything else builds on those.
+// The command line, the window and the terminal live in the grep_chip8 binary, the last two behind the
+// sdl and tty features, and nothing here reads or writes the process's stdio, so depending on this
+// pulls in no other crates.
+
+#[cfg(test)]
+mod tests;
+pub mod asm;
+pub mod beeper;
+pub mod cpu;
+pub mod debugger;
+pub mod disasm;
+pub mod display;
+pub mod dump;
+pub mod emulator;
+pub mod frontend;
+pub mod gdbstub;
+pub mod hex_util;
+pub mod instruction;
+pub mod keymap;
+pub mod movie;
+pub mod quirks;
+pub mod rewind;
+pub mod rng;
+pub mod savestate;
+pub mod scheduler;
+pub mod screen;
+pub mod timer;
+pub mod trace;
+pub mod tty;
+
+pub use cpu::CpuError;
+pub use emulator::{Emulator, LoadError, Platform};
+pub use quirks::Quirks;
@@ -1,72 +1,403 @@
-pub mod tests;
-pub mod cpu;
-pub mod display;
-pub mod emulator;
-pub mod hex_util;
+#![allow(clippy::needless_return, clippy::field_reassign_with_default)]
+
+mod cli;
+#[cfg(feature = "tty")]
+mod terminal;
+#[cfg(test)]
+#[path = "tests/test_cli.rs"]
+mod test_cli;
+#[cfg(feature = "sdl")]
+mod window;
 
 use std::env;
 use std::fs;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{self, BufRead, Write};
+#[cfg(any(feature = "sdl", feature = "tty"))]
+use std::sync::mpsc;
+use std::time::SystemTime;
+
+use grep_chip8::frontend::{self, NoAudio, NoConsole, NoInput, NoVideo, Session, SessionOptions};
+use grep_chip8::{asm, debugger, disasm, dump, emulator, gdbstub, movie, rng, savestate, scheduler, screen};
+#[cfg(any(feature = "sdl", feature = "tty"))]
+use grep_chip8::frontend::Console;
+#[cfg(any(feature = "sdl", feature = "tty"))]
+use grep_chip8::{keymap, rewind, timer};
+#[cfg(feature = "trace")]
+use grep_chip8::trace;
+
+// the standard library's hasher keys are random for every process, hashing the time with them makes a seed
+fn new_seed() -> u64 {
+    return RandomState::new().hash_one(SystemTime::now());
+}
+
+fn read_rom(path: &str) -> Vec<u8> {
+    return fs::read(path).unwrap_or_else(|error| {
+        eprintln!("Unable to read '{}': {}", path, error);
+        std::process::exit(1);
+    });
+}
+
+// the default layout, then the config file (keymap.ini in the working directory if none is given), then --key bindings
+#[cfg(any(feature = "sdl", feature = "tty"))]
+fn load_keymap(path: Option<String>, bindings: &[String], rom_path: &str, rom: &[u8]) -> keymap::Keymap {
+    let mut keymap = keymap::Keymap::default();
+
+    let config = match &path {
+        Some(path) => Some(fs::read_to_string(path).unwrap_or_else(|error| {
+            eprintln!("Unable to read keymap '{}': {}", path, error);
+            std::process::exit(1);
+        })),
+        None => fs::read_to_string("keymap.ini").ok(),
+    };
+    if let Some(config) = config {
+        let rom_name = std::path::Path::new(rom_path).file_name().unwrap_or_default().to_string_lossy();
+        if let Err(error) = keymap.apply_config(&config, &rom_name, savestate::rom_hash(rom)) {
+            eprintln!("Invalid keymap '{}': {}", path.as_deref().unwrap_or("keymap.ini"), error);
+            std::process::exit(1);
+        }
+    }
+
+    for binding in bindings {
+        if let Err(error) = keymap.apply(binding) {
+            eprintln!("Invalid --key: {}", error);
+            std::process::exit(1);
+        }
+    }
+    return keymap;
+}
+
+// the --trace file, nothing runs if it can't be made
+#[cfg(feature = "trace")]
+fn open_tracer(options: &cli::RunOptions) -> Option<Box<trace::Tracer>> {
+    let path = options.trace_path.as_ref()?;
+    let tracer = trace::Tracer::create(path, options.trace_format, options.trace_filter.clone()).unwrap_or_else(|error| {
+        eprintln!("Couldn't create the trace '{}': {}", path, error);
+        std::process::exit(1);
+    });
+    return Some(Box::new(tracer));
+}
+
+fn print_output(output: &str) {
+    if !output.is_empty() {
+        println!("{}", output);
+    }
+}
+
+fn prompt() {
+    print!("{}", debugger::PROMPT);
+    io::stdout().flush().expect("couldnt write to stdout");
+}
+
+// the debugger's console while a frontend runs: stdin lines are read on a thread of their own so the
+// frontend keeps drawing while waiting for a command, output goes to stdout
+#[cfg(any(feature = "sdl", feature = "tty"))]
+struct StdioConsole {
+    lines: mpsc::Receiver<String>,
+}
+
+#[cfg(any(feature = "sdl", feature = "tty"))]
+impl StdioConsole {
+    fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        return StdioConsole { lines: receiver };
+    }
+}
+
+#[cfg(any(feature = "sdl", feature = "tty"))]
+impl Console for StdioConsole {
+    fn poll(&mut self) -> Vec<String> {
+        return self.lines.try_iter().collect();
+    }
 
-use sdl2::pixels::Color;
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::rect;
-use std::time::Duration;
+    fn print(&mut self, output: &str) {
+        print_output(output);
+    }
+
+    fn prompt(&mut self) {
+        prompt();
+    }
+}
+
+// writes the movie being recorded and the end of the trace, saying where the movie went
+fn finish(session: Session) {
+    match session.finish() {
+        Ok(Some(message)) => println!("{}", message),
+        Ok(None) => {}
+        Err(error) => eprintln!("{}", error),
+    }
+}
+
+// writes the end of the trace for the frontends that run the emulator without a session
+#[cfg(feature = "trace")]
+fn finish_trace(emulator: emulator::Emulator) {
+    if let Err(error) = frontend::finish_trace(emulator.tracer) {
+        eprintln!("Couldn't write the trace: {}", error);
+    }
+}
+
+// prints a listing of the rom to stdout
+fn disasm(options: cli::DisasmOptions) {
+    let rom = read_rom(&options.rom_path);
+    println!("; {} ({} bytes)", options.rom_path, rom.len());
+    print!("{}", disasm::disassemble(&rom, options.load_address, options.platform));
+}
+
+// writes the assembled rom next to the source unless -o is given
+fn asm(options: cli::AsmOptions) {
+    let source_path = options.source_path;
+    let output_path = options.output_path.unwrap_or_else(|| std::path::Path::new(&source_path).with_extension("ch8").to_string_lossy().into_owned());
+
+    let source = fs::read_to_string(&source_path).unwrap_or_else(|error| {
+        eprintln!("Unable to read '{}': {}", source_path, error);
+        std::process::exit(1);
+    });
+    match asm::assemble(&source, options.load_address, options.platform) {
+        Ok(rom) => fs::write(&output_path, rom).expect("unable to write file"),
+        Err(error) => {
+            eprintln!("{}:{}", source_path, error);
+            std::process::exit(1);
+        }
+    }
+}
 
 fn main() {
-    let mut emulator = emulator::Emulator::default();
-
-    let args: Vec<String> = env::args().collect();
-    let metadata = fs::metadata(&args[1]).expect("unable to read metadata");
-    let len = (metadata.len() as usize) + 0x200;
-    emulator.ram[0x200..len].copy_from_slice(&fs::read(&args[1]).expect("unable to read file"));
-
-    // window init
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-    let window = video_subsystem.window("Grep Chip8", 64 * 10, 32 * 10)
-        .position_centered()
-        .build()
-        .unwrap();
-
-    let mut canvas = window.into_canvas().build().unwrap();
-
-    canvas.set_draw_color(Color::RGB(255, 255, 255));
-    canvas.clear();
-    canvas.present();
-
-    let mut event_pump = sdl_context.event_pump().unwrap();
-    'running: loop {
-        canvas.set_draw_color(Color::RGB(255, 255, 255));
-        canvas.clear();
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit {..} |
-                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running
-                },
-                _ => {}
+    let command = cli::parse(env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        eprintln!("Run 'grep_chip8 --help' to see the options");
+        std::process::exit(1);
+    });
+    match command {
+        cli::Command::Run(options) => run(options),
+        cli::Command::Disasm(options) => disasm(options),
+        cli::Command::Asm(options) => asm(options),
+        cli::Command::Help(text) => print!("{}", text),
+    }
+}
+
+fn run(options: Box<cli::RunOptions>) {
+    let frontend = options.frontend();
+    // a frontend left out of the build can't run
+    if !options.headless {
+        match frontend {
+            cli::FrontendKind::Sdl if !cfg!(feature = "sdl") => {
+                eprintln!("This build has no window, it was made without the sdl feature. Run with --frontend tty or --headless");
+                std::process::exit(1);
+            }
+            cli::FrontendKind::Tty if !cfg!(feature = "tty") => {
+                eprintln!("This build can't draw in the terminal, it was made without the tty feature. Run with --headless");
+                std::process::exit(1);
+            }
+            _ => {}
+        }
+    }
+    if options.trace_path.is_some() && !cfg!(feature = "trace") {
+        eprintln!("This build can't trace, it was made without the trace feature. Build it with --features trace");
+        std::process::exit(1);
+    }
+    let mut platform = options.platform;
+    let mut quirks = options.quirks();
+    let mut instructions_per_frame = options.instructions_per_frame();
+    let mut load_address = options.load_address;
+    // a new seed every run unless one is given
+    let seed = options.seed.unwrap_or_else(new_seed);
+    let mut random: Box<dyn rng::Random> = match &options.interpreter_path {
+        Some(path) => {
+            let interpreter = fs::read(path).unwrap_or_else(|error| {
+                eprintln!("Unable to read the vip interpreter '{}': {}", path, error);
+                std::process::exit(1);
+            });
+            Box::new(rng::Vip::with_interpreter(seed, &interpreter).unwrap_or_else(|| {
+                eprintln!("'{}' is too short to be the vip interpreter, expected at least 512 bytes", path);
+                std::process::exit(1);
+            }))
+        }
+        None => options.rng_kind.create(seed),
+    };
+
+    let rom = read_rom(&options.rom_path);
+    // a movie plays back with the settings it was recorded with
+    let playback = options.play_path.as_ref().map(|path| {
+        let data = fs::read(path).unwrap_or_else(|error| {
+            eprintln!("Unable to read movie '{}': {}", path, error);
+            std::process::exit(1);
+        });
+        return movie::load(&data, savestate::rom_hash(&rom)).unwrap_or_else(|error| {
+            eprintln!("Couldn't play movie '{}': {}", path, error);
+            std::process::exit(1);
+        });
+    });
+    if let Some(movie) = &playback {
+        platform = movie.platform;
+        quirks = movie.quirks;
+        instructions_per_frame = movie.instructions_per_frame;
+        load_address = movie.load_address;
+        random = movie.start_rng();
+    }
+    let emulator = frontend::create_emulator(platform, quirks, &rom, load_address, random.as_ref()).unwrap_or_else(|error| {
+        eprintln!("Couldn't load '{}': {}", options.rom_path, error);
+        std::process::exit(1);
+    });
+    #[cfg(feature = "trace")]
+    let emulator = emulator::Emulator { tracer: open_tracer(&options), ..emulator };
+    // gdb has to be connected before anything runs
+    let gdb = options.gdb_port.map(|port| {
+        println!("Waiting for gdb on 127.0.0.1:{}", port);
+        gdbstub::GdbStub::listen(port, instructions_per_frame).unwrap_or_else(|error| {
+            eprintln!("Couldn't start the gdb server: {}", error);
+            std::process::exit(1);
+        })
+    });
+    let session_options = SessionOptions {
+        platform,
+        quirks,
+        load_address,
+        rng: random,
+        scheduler: scheduler::Scheduler::new(instructions_per_frame),
+        screen: screen::Screen::new(options.palette, options.grid, options.phosphor_frames),
+        debugger: None,
+        console: Box::new(NoConsole),
+        gdb: None,
+        rewind: None,
+        record_path: None,
+        playback: None,
+        #[cfg(feature = "trace")]
+        tracer: None,
+    };
+    if options.headless {
+        match gdb {
+            Some(gdb) => run_gdb(emulator, gdb),
+            None if options.debug => run_debugger(emulator, options.frames.unwrap_or(60), instructions_per_frame),
+            // a movie runs to its end unless told otherwise
+            None => {
+                let frames = options.frames.or(playback.as_ref().map(|movie| movie.frames.len() as u64)).unwrap_or(60);
+                let session = Session::new(rom, &options.rom_path, SessionOptions {
+                    playback,
+                    #[cfg(feature = "trace")]
+                    tracer: emulator.tracer,
+                    ..session_options
+                }).expect("the rom loaded above");
+                run_headless(session, frames, options.dump_path);
             }
         }
-        
-        // testing at 10 cycles per frame (600hz)
+        return;
+    }
 
-        for _ in 0..1 {
-            cpu::execute_next_instruction(&mut emulator);
+    #[cfg(any(feature = "sdl", feature = "tty"))]
+    {
+        let keymap = load_keymap(options.keymap_path, &options.bindings, &options.rom_path, &rom);
+        let session_options = SessionOptions {
+            debugger: options.debug.then(|| debugger::Debugger::new(instructions_per_frame)),
+            console: if options.debug { Box::new(StdioConsole::spawn()) } else { Box::new(NoConsole) },
+            gdb,
+            rewind: (options.rewind_seconds > 0).then(|| rewind::Rewind::new(options.rewind_seconds as usize * timer::TIMER_FREQUENCY as usize)),
+            record_path: options.record_path,
+            playback,
+            #[cfg(feature = "trace")]
+            tracer: emulator.tracer,
+            ..session_options
+        };
+        let session = Session::new(rom, &options.rom_path, session_options).expect("the rom loaded above");
+        match frontend {
+            #[cfg(feature = "sdl")]
+            cli::FrontendKind::Sdl => window::run_window(session, keymap, options.audio.then_some(options.tone), options.scale),
+            #[cfg(feature = "tty")]
+            cli::FrontendKind::Tty => terminal::run_terminal(session, keymap, options.glyphs, options.audio),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("frontends left out of the build are refused above"),
         }
-        
-        canvas.set_draw_color(Color::RGB(0, 0, 0));
-        // draw current state to frame
-        for i in 0..64 {
-            for j in 0..32 {
-                if emulator.display_memory[(j * 64) + i] {
-                    canvas.fill_rect(rect::Rect::new(i as i32 * 10, j as i32 * 10, 10, 10)).expect("couldnt draw rect :(");
-                }
+    }
+}
+
+// runs the rom for a number of 60hz frames without opening a window, then writes the display to the dump file
+// or stdout as text. exits with 1 if the cpu stopped on an error, the display is still written so it can be inspected.
+// with a movie its keys are held each frame, frames past its end run with no keys held
+fn run_headless(mut session: Session, frames: u64, dump_path: Option<String>) {
+    let format = match &dump_path {
+        Some(path) => dump::Format::from_path(path).unwrap_or_else(|| {
+            eprintln!("Unknown dump format '{}', expected a .pbm, .png or .txt file", path);
+            std::process::exit(1);
+        }),
+        None => dump::Format::Ascii,
+    };
+
+    // as fast as it goes, each pass is given just the time for the next frame
+    for frame in 0..frames {
+        let elapsed = session.scheduler.until_next_frame();
+        session.update(elapsed, &mut NoVideo, &mut NoAudio, &mut NoInput);
+        if let Some(error) = session.halted() {
+            eprintln!("Emulation stopped in frame {}: {}", frame, error);
+            break;
+        }
+    }
+
+    let image = dump::dump(&session.emulator, format);
+    match dump_path {
+        Some(path) => fs::write(&path, image).expect("unable to write file"),
+        None => print!("{}", String::from_utf8_lossy(&image)),
+    }
+
+    let halted = session.halted().is_some();
+    finish(session);
+    if halted {
+        std::process::exit(1);
+    }
+}
+
+// the debugger on its own, reading commands from stdin until quit or the end of input.
+// continue runs at most frames frames before pausing again, so a rom that never hits a breakpoint still gives the prompt back
+fn run_debugger(mut emulator: emulator::Emulator, frames: u64, instructions_per_frame: u32) {
+    let mut debugger = debugger::Debugger::new(instructions_per_frame);
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        prompt();
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        print_output(&debugger.command(&mut emulator, &line));
+        if debugger.quit {
+            break;
+        }
+
+        let mut frame = 0;
+        while debugger.running {
+            if frame == frames {
+                debugger.running = false;
+                print_output(&format!("paused after {} frames\n{}", frames, debugger::list(&emulator, emulator.pc, 1)));
+                break;
+            }
+            if let Some(message) = debugger.run_frame(&mut emulator) {
+                print_output(&message);
             }
+            frame += 1;
         }
+    }
+    #[cfg(feature = "trace")]
+    finish_trace(emulator);
+}
 
-        // to do: sound
-        canvas.present();
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+// serves gdb without a window until it detaches, running as fast as the cpu goes while gdb has it continuing
+fn run_gdb(mut emulator: emulator::Emulator, mut gdb: gdbstub::GdbStub) {
+    while !gdb.closed {
+        let result = gdb.poll(&mut emulator).and_then(|_| gdb.run_frame(&mut emulator));
+        if let Err(error) = result {
+            eprintln!("Lost the gdb connection: {}", error);
+            break;
+        }
+        if !gdb.debugger.running {
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
     }
+    #[cfg(feature = "trace")]
+    finish_trace(emulator);
 }
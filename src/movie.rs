@@ -0,0 +1,189 @@
+use std::fmt;
+
+use crate::cpu::{self, CpuError};
+use crate::emulator::{Emulator, Platform};
+use crate::hex_util;
+use crate::quirks::Quirks;
+use crate::rng::{self, Random, RandomKind};
+
+// Movie file layout, all numbers big endian:
+//   "GC8M"            magic
+//   u16               format version
+//   u32               crc-32 of the rom
+//   u8                rng kind
+//   u32               length of the rng state
+//   ...               the rng's state when the movie starts, as Random::save gives it
+//   u8                platform
+//   5 x u8            quirks, in the order of the Quirks fields
+//   u16               address the rom was loaded at
+//   u32               instructions per frame
+//   u32               number of frames
+//   u16 per frame     keypad, bit n set while key n is held
+//   u32               crc-32 of everything before it
+pub const MAGIC: [u8; 4] = *b"GC8M";
+pub const VERSION: u16 = 3;
+
+// why a movie couldn't be loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovieError {
+    NotAMovie,
+    WrongVersion { found: u16 },
+    ChecksumMismatch,
+    // recorded with a different rom, it would play out differently
+    WrongRom { expected: u32, found: u32 },
+    Corrupt,
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            MovieError::NotAMovie => write!(f, "not a movie file"),
+            MovieError::WrongVersion { found } => write!(f, "movie is format version {}, this emulator reads version {}", found, VERSION),
+            MovieError::ChecksumMismatch => write!(f, "movie is damaged, checksum doesn't match"),
+            MovieError::WrongRom { expected, found } => write!(f, "movie was recorded with a different rom (crc {:08X}, running {:08X})", found, expected),
+            MovieError::Corrupt => write!(f, "movie holds invalid values"),
+        };
+    }
+}
+
+impl std::error::Error for MovieError {}
+
+// The key presses of a session with everything else needed to play it again exactly:
+// the rom and where it was loaded, where the rng started and the settings that change how instructions run.
+// Starting from a fresh emulator with these, the same keys every frame give the same game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Movie {
+    pub rom_hash: u32,
+    pub rng_kind: RandomKind,
+    // the rng's state at the start
+    pub rng_state: Vec<u8>,
+    pub platform: Platform,
+    pub quirks: Quirks,
+    pub load_address: u16,
+    pub instructions_per_frame: u32,
+    // keypad state of every frame, bit n set while key n is held
+    pub frames: Vec<u16>,
+}
+
+impl Movie {
+    // rng is the source the emulator starts with, before the first frame
+    pub fn new(rom_hash: u32, rng: &dyn Random, platform: Platform, quirks: Quirks, load_address: u16, instructions_per_frame: u32) -> Self {
+        return Movie { rom_hash, rng_kind: rng.kind(), rng_state: rng.save(), platform, quirks, load_address, instructions_per_frame, frames: Vec::new() };
+    }
+
+    // the rng the emulator has to start with to play the movie
+    pub fn start_rng(&self) -> Box<dyn Random> {
+        return rng::restore(self.rng_kind, &self.rng_state).expect("the rng state is checked when the movie is loaded");
+    }
+
+    // adds a frame with the keys as they are now
+    pub fn record(&mut self, keys: &[bool; 0x10]) {
+        self.frames.push(pack_keys(keys));
+    }
+
+    // runs frame number frame of the movie, with its keys held. false once the movie has ended
+    pub fn play_frame(&self, emulator: &mut Emulator, frame: usize) -> Result<bool, CpuError> {
+        let Some(keys) = self.frames.get(frame) else {
+            return Ok(false);
+        };
+        emulator.keys = unpack_keys(*keys);
+        cpu::execute_frame(emulator, self.instructions_per_frame)?;
+        return Ok(true);
+    }
+}
+
+pub fn pack_keys(keys: &[bool; 0x10]) -> u16 {
+    return keys.iter().enumerate().fold(0, |bits, (key, held)| bits | ((*held as u16) << key));
+}
+
+pub fn unpack_keys(bits: u16) -> [bool; 0x10] {
+    return std::array::from_fn(|key| bits & (1 << key) != 0);
+}
+
+pub fn save(movie: &Movie) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(MAGIC);
+    data.extend(VERSION.to_be_bytes());
+    data.extend(movie.rom_hash.to_be_bytes());
+    data.push(movie.rng_kind as u8);
+    data.extend((movie.rng_state.len() as u32).to_be_bytes());
+    data.extend(&movie.rng_state);
+    data.push(movie.platform as u8);
+    let quirks = movie.quirks;
+    data.extend([
+        quirks.shift_uses_vy,
+        quirks.load_store_increments_i,
+        quirks.jump_uses_vx,
+        quirks.logic_resets_vf,
+        quirks.clip_sprites,
+    ].map(|quirk| quirk as u8));
+    data.extend(movie.load_address.to_be_bytes());
+    data.extend(movie.instructions_per_frame.to_be_bytes());
+    data.extend((movie.frames.len() as u32).to_be_bytes());
+    for keys in &movie.frames {
+        data.extend(keys.to_be_bytes());
+    }
+    let checksum = hex_util::crc32(&data);
+    data.extend(checksum.to_be_bytes());
+    return data;
+}
+
+// reads a movie written by save, checking it was recorded with the rom being run
+pub fn load(data: &[u8], rom_hash: u32) -> Result<Movie, MovieError> {
+    if data.len() < MAGIC.len() || data[..MAGIC.len()] != MAGIC {
+        return Err(MovieError::NotAMovie);
+    }
+    let mut position = MAGIC.len();
+    let mut take = |len: usize| -> Result<&[u8], MovieError> {
+        let bytes = data.get(position..position + len).ok_or(MovieError::ChecksumMismatch)?;
+        position += len;
+        return Ok(bytes);
+    };
+
+    let version = u16::from_be_bytes(take(2)?.try_into().unwrap());
+    if version != VERSION {
+        return Err(MovieError::WrongVersion { found: version });
+    }
+    let (body, checksum) = data.split_at(data.len().saturating_sub(4));
+    if checksum.len() != 4 || hex_util::crc32(body) != u32::from_be_bytes(checksum.try_into().unwrap()) {
+        return Err(MovieError::ChecksumMismatch);
+    }
+
+    let found = u32::from_be_bytes(take(4)?.try_into().unwrap());
+    if found != rom_hash {
+        return Err(MovieError::WrongRom { expected: rom_hash, found });
+    }
+    let rng_kind = RandomKind::from_id(take(1)?[0]).ok_or(MovieError::Corrupt)?;
+    let len = u32::from_be_bytes(take(4)?.try_into().unwrap()) as usize;
+    let rng_state = take(len)?.to_vec();
+    if rng::restore(rng_kind, &rng_state).is_none() {
+        return Err(MovieError::Corrupt);
+    }
+    let platform = match take(1)?[0] {
+        0 => Platform::Chip8,
+        1 => Platform::SuperChip,
+        2 => Platform::XoChip,
+        _ => return Err(MovieError::Corrupt),
+    };
+    let quirk_bytes = take(5)?;
+    if quirk_bytes.iter().any(|byte| *byte > 1) {
+        return Err(MovieError::Corrupt);
+    }
+    let quirks = Quirks {
+        shift_uses_vy: quirk_bytes[0] == 1,
+        load_store_increments_i: quirk_bytes[1] == 1,
+        jump_uses_vx: quirk_bytes[2] == 1,
+        logic_resets_vf: quirk_bytes[3] == 1,
+        clip_sprites: quirk_bytes[4] == 1,
+    };
+    let load_address = u16::from_be_bytes(take(2)?.try_into().unwrap());
+    let instructions_per_frame = u32::from_be_bytes(take(4)?.try_into().unwrap());
+    let count = u32::from_be_bytes(take(4)?.try_into().unwrap()) as usize;
+    // the frames and the checksum have to be all that's left
+    if body.len().checked_sub(position) != Some(count * 2) || instructions_per_frame == 0 {
+        return Err(MovieError::Corrupt);
+    }
+    let frames = body[position..].chunks(2).map(|keys| u16::from_be_bytes([keys[0], keys[1]])).collect();
+
+    return Ok(Movie { rom_hash, rng_kind, rng_state, platform, quirks, load_address, instructions_per_frame, frames });
+}
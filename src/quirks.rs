@@ -0,0 +1,104 @@
+// Behaviour of the opcodes that chip8 interpreters never agreed on.
+// Each flag picks one reading of an ambiguous instruction, the presets match the interpreters roms were written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    // 8xy6 / 8xyE: shift Vy and store the result in Vx (cosmac vip), instead of shifting Vx in place
+    pub shift_uses_vy: bool,
+    // Fx55 / Fx65: I is left pointing past the last register stored or loaded (cosmac vip)
+    pub load_store_increments_i: bool,
+    // Bnnn: jump to xnn + Vx (chip-48, super-chip), instead of nnn + V0
+    pub jump_uses_vx: bool,
+    // 8xy1 / 8xy2 / 8xy3: VF is reset to 0 after the operation (cosmac vip)
+    pub logic_resets_vf: bool,
+    // Dxyn: sprites are cut off at the edges of the screen instead of wrapping around to the other side
+    pub clip_sprites: bool,
+}
+
+pub const PRESET_NAMES: [&str; 5] = ["default", "vip", "chip48", "schip", "xochip"];
+// names of the single quirks for --quirk, in the order of the fields
+pub const QUIRK_NAMES: [&str; 5] = ["shifting", "memory", "jumping", "vf-reset", "clipping"];
+
+impl Default for Quirks {
+    // the behaviour this emulator has always had, which follows the technical reference
+    fn default() -> Self {
+        return Quirks {
+            shift_uses_vy: false,
+            load_store_increments_i: false,
+            jump_uses_vx: false,
+            logic_resets_vf: false,
+            clip_sprites: false,
+        };
+    }
+}
+
+impl Quirks {
+    // original cosmac vip interpreter
+    pub fn vip() -> Self {
+        return Quirks {
+            shift_uses_vy: true,
+            load_store_increments_i: true,
+            jump_uses_vx: false,
+            logic_resets_vf: true,
+            clip_sprites: true,
+        };
+    }
+
+    // chip-48 on the hp-48 calculators
+    pub fn chip48() -> Self {
+        return Quirks {
+            shift_uses_vy: false,
+            load_store_increments_i: true,
+            jump_uses_vx: true,
+            logic_resets_vf: false,
+            clip_sprites: true,
+        };
+    }
+
+    // super-chip 1.1
+    pub fn schip() -> Self {
+        return Quirks {
+            shift_uses_vy: false,
+            load_store_increments_i: false,
+            jump_uses_vx: true,
+            logic_resets_vf: false,
+            clip_sprites: true,
+        };
+    }
+
+    // xo-chip as implemented by octo
+    pub fn xochip() -> Self {
+        return Quirks {
+            shift_uses_vy: true,
+            load_store_increments_i: true,
+            jump_uses_vx: false,
+            logic_resets_vf: false,
+            clip_sprites: false,
+        };
+    }
+
+    // looks up a preset by the name used on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name.to_ascii_lowercase().as_str() {
+            "default" => Some(Quirks::default()),
+            "vip" | "cosmac" => Some(Quirks::vip()),
+            "chip48" | "chip-48" => Some(Quirks::chip48()),
+            "schip" | "superchip" | "super-chip" => Some(Quirks::schip()),
+            "xochip" | "xo-chip" | "octo" => Some(Quirks::xochip()),
+            _ => None,
+        };
+    }
+
+    // turns a single quirk on or off by its name from QUIRK_NAMES, false if there's no quirk by that name
+    pub fn set(&mut self, name: &str, on: bool) -> bool {
+        let quirk = match name.to_ascii_lowercase().as_str() {
+            "shifting" => &mut self.shift_uses_vy,
+            "memory" => &mut self.load_store_increments_i,
+            "jumping" => &mut self.jump_uses_vx,
+            "vf-reset" => &mut self.logic_resets_vf,
+            "clipping" => &mut self.clip_sprites,
+            _ => return false,
+        };
+        *quirk = on;
+        return true;
+    }
+}
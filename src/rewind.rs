@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+
+use crate::emulator::Emulator;
+use crate::savestate;
+
+// seconds of play kept unless --rewind says otherwise
+pub const DEFAULT_SECONDS: u32 = 10;
+
+// An older snapshot, stored as the difference from the one after it.
+// Snapshots are save states, which only change in a few bytes from one frame to the next,
+// so the xor of two neighbours is nearly all zeros and run length encodes to almost nothing.
+#[derive(Debug, Clone)]
+enum Delta {
+    // xor with the newer snapshot, run length encoded by encode_runs
+    Xor(Vec<u8>),
+    // the whole snapshot, when it isn't the same length as the newer one, after a platform change
+    Full(Vec<u8>),
+}
+
+impl Delta {
+    fn len(&self) -> usize {
+        return match self {
+            Delta::Xor(data) | Delta::Full(data) => data.len(),
+        };
+    }
+}
+
+// A ring buffer of the last few seconds of snapshots for walking back through play.
+// Only the newest snapshot is kept whole, each older one is a delta against the one after it.
+// When full the oldest snapshot is dropped, so memory is bounded by capacity.
+#[derive(Debug, Clone)]
+pub struct Rewind {
+    // most snapshots kept
+    capacity: usize,
+    // oldest first, deltas[n] turns snapshot n + 1 back into snapshot n
+    deltas: VecDeque<Delta>,
+    // the newest snapshot as a save state, empty until the first push
+    latest: Vec<u8>,
+    // bytes held by the deltas
+    delta_bytes: usize,
+}
+
+impl Rewind {
+    // keeps up to capacity snapshots, one is pushed per frame so 60 is a second
+    pub fn new(capacity: usize) -> Self {
+        return Rewind { capacity: capacity.max(1), deltas: VecDeque::new(), latest: Vec::new(), delta_bytes: 0 };
+    }
+
+    // snapshots that can be stepped back to
+    pub fn len(&self) -> usize {
+        return self.deltas.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.deltas.is_empty();
+    }
+
+    // bytes of snapshot data held
+    pub fn memory_used(&self) -> usize {
+        return self.latest.len() + self.delta_bytes;
+    }
+
+    // records the emulator as the newest snapshot
+    pub fn push(&mut self, emulator: &Emulator) {
+        let snapshot = savestate::save(emulator, 0);
+        if !self.latest.is_empty() {
+            let delta = if self.latest.len() == snapshot.len() {
+                let xor: Vec<u8> = self.latest.iter().zip(&snapshot).map(|(older, newer)| older ^ newer).collect();
+                Delta::Xor(encode_runs(&xor))
+            } else {
+                Delta::Full(std::mem::take(&mut self.latest))
+            };
+            self.delta_bytes += delta.len();
+            self.deltas.push_back(delta);
+        }
+        self.latest = snapshot;
+
+        while self.deltas.len() >= self.capacity {
+            if let Some(oldest) = self.deltas.pop_front() {
+                self.delta_bytes -= oldest.len();
+            }
+        }
+    }
+
+    // goes back one snapshot and returns it, None when there's nothing older left
+    pub fn step_back(&mut self) -> Option<Emulator> {
+        let delta = self.deltas.pop_back()?;
+        self.delta_bytes -= delta.len();
+        self.latest = match delta {
+            Delta::Xor(runs) => {
+                let xor = decode_runs(&runs, self.latest.len());
+                self.latest.iter().zip(&xor).map(|(newer, difference)| newer ^ difference).collect()
+            }
+            Delta::Full(snapshot) => snapshot,
+        };
+        return savestate::load(&self.latest, 0).ok();
+    }
+
+    // forgets every snapshot, after loading a state or resetting
+    pub fn clear(&mut self) {
+        self.deltas.clear();
+        self.latest.clear();
+        self.delta_bytes = 0;
+    }
+}
+
+// Run length encodes data that is mostly zeros as pairs of counts followed by the non zero bytes:
+//   varint            zeros to skip
+//   varint            bytes copied as they are
+//   ...               the copied bytes
+// trailing zeros are left out, decode_runs is told the full length
+pub fn encode_runs(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut position = 0;
+    while position < data.len() {
+        let zeros = data[position..].iter().take_while(|byte| **byte == 0).count();
+        position += zeros;
+        if position == data.len() {
+            break;
+        }
+        // a lone zero between changed bytes is cheaper to copy than to start a new run for
+        let mut end = position;
+        while end < data.len() && (data[end] != 0 || data.get(end + 1).is_some_and(|next| *next != 0)) {
+            end += 1;
+        }
+        write_varint(&mut encoded, zeros);
+        write_varint(&mut encoded, end - position);
+        encoded.extend_from_slice(&data[position..end]);
+        position = end;
+    }
+    return encoded;
+}
+
+// undoes encode_runs, giving len bytes. bad input decodes to whatever it can rather than failing
+pub fn decode_runs(encoded: &[u8], len: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(len);
+    let mut position = 0;
+    while position < encoded.len() {
+        let zeros = read_varint(encoded, &mut position);
+        let copied = read_varint(encoded, &mut position);
+        data.resize((data.len() + zeros).min(len), 0);
+        let end = (position + copied).min(encoded.len());
+        data.extend_from_slice(&encoded[position..end]);
+        position = end;
+    }
+    data.resize(len, 0);
+    return data;
+}
+
+// 7 bits at a time, low bits first, the top bit set on every byte but the last
+fn write_varint(data: &mut Vec<u8>, value: usize) {
+    let mut value = value;
+    while value >= 0x80 {
+        data.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn read_varint(data: &[u8], position: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    while let Some(byte) = data.get(*position) {
+        *position += 1;
+        value |= ((byte & 0x7F) as usize) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 || shift >= usize::BITS {
+            break;
+        }
+    }
+    return value;
+}
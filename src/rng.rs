@@ -0,0 +1,182 @@
+use std::fmt;
+
+// Where Cxkk gets its random numbers from. The emulator owns one, seeded, so the same seed and
+// the same key presses replay a game exactly. save and restore carry it through save states and movies.
+pub trait Random: fmt::Debug {
+    fn next_byte(&mut self) -> u8;
+
+    fn kind(&self) -> RandomKind;
+
+    // everything needed to carry on the same sequence later
+    fn save(&self) -> Vec<u8>;
+
+    // takes back what save gave, false if the data can't have come from save
+    fn restore(&mut self, data: &[u8]) -> bool;
+
+    // a second source that carries on with the same numbers
+    fn duplicate(&self) -> Box<dyn Random> {
+        return restore(self.kind(), &self.save()).expect("a source restores from its own save");
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomKind {
+    Xorshift,
+    Vip,
+}
+
+pub const RANDOM_NAMES: [&str; 2] = ["xorshift", "vip"];
+
+impl RandomKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name.to_ascii_lowercase().as_str() {
+            "xorshift" => Some(RandomKind::Xorshift),
+            "vip" => Some(RandomKind::Vip),
+            _ => None,
+        };
+    }
+
+    // the number save states and movies store for the kind
+    pub fn from_id(id: u8) -> Option<Self> {
+        return match id {
+            0 => Some(RandomKind::Xorshift),
+            1 => Some(RandomKind::Vip),
+            _ => None,
+        };
+    }
+
+    pub fn create(&self, seed: u64) -> Box<dyn Random> {
+        return match self {
+            RandomKind::Xorshift => Box::new(Xorshift::new(seed)),
+            RandomKind::Vip => Box::new(Vip::new(seed)),
+        };
+    }
+}
+
+// a source of the kind in the state save gave, None if the state is invalid
+pub fn restore(kind: RandomKind, data: &[u8]) -> Option<Box<dyn Random>> {
+    let mut random = kind.create(0);
+    if !random.restore(data) {
+        return None;
+    }
+    return Some(random);
+}
+
+// seed of Emulator::default, frontends pick a new one for every run unless given --seed
+pub const DEFAULT_SEED: u64 = 0x4752_4550_4348_4938;
+
+// xorshift64*, see Vigna, "An experimental exploration of Marsaglia's xorshift generators, scrambled".
+// the default source, fast with no pattern a game could notice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xorshift {
+    state: u64,
+}
+
+impl Default for Xorshift {
+    fn default() -> Self {
+        return Xorshift::new(DEFAULT_SEED);
+    }
+}
+
+impl Xorshift {
+    // any seed works, the state is mixed with splitmix64 since xorshift can't start from 0
+    pub fn new(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        return Xorshift { state: if z == 0 { 1 } else { z } };
+    }
+}
+
+impl Random for Xorshift {
+    fn next_byte(&mut self) -> u8 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        // the top bits are the most random
+        return (self.state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8;
+    }
+
+    fn kind(&self) -> RandomKind {
+        return RandomKind::Xorshift;
+    }
+
+    fn save(&self) -> Vec<u8> {
+        return self.state.to_be_bytes().to_vec();
+    }
+
+    fn restore(&mut self, data: &[u8]) -> bool {
+        let Ok(bytes) = <[u8; 8]>::try_from(data) else {
+            return false;
+        };
+        // xorshift never reaches 0
+        let state = u64::from_be_bytes(bytes);
+        if state == 0 {
+            return false;
+        }
+        self.state = state;
+        return true;
+    }
+}
+
+// The COSMAC VIP interpreter's Cxkk routine. It keeps a 16 bit counter in register R9, and for each number
+//   steps R9
+//   adds the high byte of R9 to the byte at 0x0100 + the low byte of R9, a byte of the interpreter's own code
+//   shifts that right through the carry and adds it to itself again, which becomes the new high byte and the number
+// so the numbers depend on the interpreter, pass it to with_interpreter to get the VIP's exact sequence.
+// without it a stand in page of bytes is used, the numbers then have the VIP's character but not its values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vip {
+    r9: u16,
+    // the 256 bytes of interpreter the routine reads
+    page: Vec<u8>,
+}
+
+// the seed the stand in page is made from
+const VIP_PAGE_SEED: u64 = 0x0100;
+
+impl Vip {
+    pub fn new(seed: u64) -> Self {
+        let mut source = Xorshift::new(VIP_PAGE_SEED);
+        let page = (0..0x100).map(|_| source.next_byte()).collect();
+        return Vip { r9: seed as u16, page };
+    }
+
+    // uses the real interpreter, a dump of the VIP's memory from 0x0000, at least 512 bytes
+    pub fn with_interpreter(seed: u64, interpreter: &[u8]) -> Option<Self> {
+        let page = interpreter.get(0x100..0x200)?.to_vec();
+        return Some(Vip { r9: seed as u16, page });
+    }
+}
+
+impl Random for Vip {
+    fn next_byte(&mut self) -> u8 {
+        self.r9 = self.r9.wrapping_add(1);
+        let [high, low] = self.r9.to_be_bytes();
+        let (sum, carry) = self.page[low as usize].overflowing_add(high);
+        let shifted = (sum >> 1) | ((carry as u8) << 7);
+        let number = shifted.wrapping_add(sum);
+        self.r9 = u16::from_be_bytes([number, low]);
+        return number;
+    }
+
+    fn kind(&self) -> RandomKind {
+        return RandomKind::Vip;
+    }
+
+    fn save(&self) -> Vec<u8> {
+        let mut data = self.r9.to_be_bytes().to_vec();
+        data.extend(&self.page);
+        return data;
+    }
+
+    fn restore(&mut self, data: &[u8]) -> bool {
+        if data.len() != 2 + 0x100 {
+            return false;
+        }
+        self.r9 = u16::from_be_bytes([data[0], data[1]]);
+        self.page = data[2..].to_vec();
+        return true;
+    }
+}
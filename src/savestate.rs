@@ -0,0 +1,253 @@
+use std::fmt;
+
+use crate::emulator::{Emulator, Platform};
+use crate::hex_util;
+use crate::quirks::Quirks;
+use crate::rng::{self, RandomKind};
+
+// Save state file layout, all numbers big endian:
+//   "GC8S"            magic
+//   u16               format version
+//   u32               crc-32 of the rom the state was made with
+//   ...               the emulator, in the order of write_emulator
+//   u32               crc-32 of everything before it
+pub const MAGIC: [u8; 4] = *b"GC8S";
+// bump whenever the layout changes, older states are rejected rather than read wrong
+pub const VERSION: u16 = 4;
+
+// why a save state couldn't be loaded. the running emulator is never touched when loading fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveStateError {
+    // the file doesn't start with the magic, so it isn't a save state
+    NotASaveState,
+    // made by a different version of the emulator
+    WrongVersion { found: u16 },
+    // the file was damaged or cut short
+    ChecksumMismatch,
+    // made while running a different rom
+    WrongRom { expected: u32, found: u32 },
+    // passed the checksum but holds values no emulator could have, the file was made by something else
+    Corrupt,
+}
+
+impl fmt::Display for SaveStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            SaveStateError::NotASaveState => write!(f, "not a save state file"),
+            SaveStateError::WrongVersion { found } => write!(f, "save state is format version {}, this emulator reads version {}", found, VERSION),
+            SaveStateError::ChecksumMismatch => write!(f, "save state is damaged, checksum doesn't match"),
+            SaveStateError::WrongRom { expected, found } => write!(f, "save state was made with a different rom (crc {:08X}, running {:08X})", found, expected),
+            SaveStateError::Corrupt => write!(f, "save state holds invalid values"),
+        };
+    }
+}
+
+impl std::error::Error for SaveStateError {}
+
+// number of save slots, F6 / F7 cycle through them in the window
+pub const SLOTS: u8 = 10;
+
+// states are kept next to the rom, rom.ch8.state0 to rom.ch8.state9
+pub fn slot_path(rom_path: &str, slot: u8) -> String {
+    return format!("{}.state{}", rom_path, slot);
+}
+
+// identifies the rom a state belongs to
+pub fn rom_hash(rom: &[u8]) -> u32 {
+    return hex_util::crc32(rom);
+}
+
+pub fn save(emulator: &Emulator, rom_hash: u32) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend(MAGIC);
+    data.extend(VERSION.to_be_bytes());
+    data.extend(rom_hash.to_be_bytes());
+    write_emulator(&mut data, emulator);
+    let checksum = hex_util::crc32(&data);
+    data.extend(checksum.to_be_bytes());
+    return data;
+}
+
+// reads a state written by save, checking that it was made by this version for the rom being run
+pub fn load(data: &[u8], rom_hash: u32) -> Result<Emulator, SaveStateError> {
+    if data.len() < MAGIC.len() || data[..MAGIC.len()] != MAGIC {
+        return Err(SaveStateError::NotASaveState);
+    }
+    let mut reader = Reader { data, position: MAGIC.len() };
+    let version = reader.u16()?;
+    if version != VERSION {
+        return Err(SaveStateError::WrongVersion { found: version });
+    }
+
+    let (body, checksum) = data.split_at(data.len() - 4);
+    if hex_util::crc32(body) != u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) {
+        return Err(SaveStateError::ChecksumMismatch);
+    }
+
+    let found = reader.u32()?;
+    if found != rom_hash {
+        return Err(SaveStateError::WrongRom { expected: rom_hash, found });
+    }
+
+    let mut reader = Reader { data: body, position: reader.position };
+    let emulator = read_emulator(&mut reader)?;
+    if reader.position != body.len() {
+        return Err(SaveStateError::Corrupt);
+    }
+    return Ok(emulator);
+}
+
+fn write_bools(data: &mut Vec<u8>, values: &[bool]) {
+    data.extend((values.len() as u32).to_be_bytes());
+    data.extend(values.iter().map(|value| *value as u8));
+}
+
+fn write_emulator(data: &mut Vec<u8>, emulator: &Emulator) {
+    data.push(emulator.platform as u8);
+    let quirks = emulator.quirks;
+    data.extend([
+        quirks.shift_uses_vy,
+        quirks.load_store_increments_i,
+        quirks.jump_uses_vx,
+        quirks.logic_resets_vf,
+        quirks.clip_sprites,
+    ].map(|quirk| quirk as u8));
+
+    data.extend(emulator.pc.to_be_bytes());
+    data.push(emulator.sp);
+    for address in emulator.stack {
+        data.extend(address.to_be_bytes());
+    }
+    data.extend(emulator.v);
+    data.extend(emulator.i.to_be_bytes());
+    data.push(emulator.delay_timer);
+    data.push(emulator.sound_timer);
+    write_bools(data, &emulator.keys);
+
+    data.extend((emulator.ram.len() as u32).to_be_bytes());
+    data.extend(&emulator.ram);
+
+    data.push(emulator.hires as u8);
+    data.push(emulator.plane_mask);
+    write_bools(data, &emulator.display_memory);
+    write_bools(data, &emulator.second_plane);
+
+    data.extend(emulator.rpl);
+    data.extend(emulator.audio_pattern);
+    data.push(emulator.pitch);
+    data.push(emulator.rng.kind() as u8);
+    let rng = emulator.rng.save();
+    data.extend((rng.len() as u32).to_be_bytes());
+    data.extend(rng);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn bytes(&mut self, len: usize) -> Result<&[u8], SaveStateError> {
+        if self.position + len > self.data.len() {
+            return Err(SaveStateError::Corrupt);
+        }
+        let bytes = &self.data[self.position..self.position + len];
+        self.position += len;
+        return Ok(bytes);
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SaveStateError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        return Ok(array);
+    }
+
+    fn u8(&mut self) -> Result<u8, SaveStateError> {
+        return Ok(self.bytes(1)?[0]);
+    }
+
+    fn bool(&mut self) -> Result<bool, SaveStateError> {
+        return match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SaveStateError::Corrupt),
+        };
+    }
+
+    fn u16(&mut self) -> Result<u16, SaveStateError> {
+        return Ok(u16::from_be_bytes(self.array()?));
+    }
+
+    fn u32(&mut self) -> Result<u32, SaveStateError> {
+        return Ok(u32::from_be_bytes(self.array()?));
+    }
+
+    // a length followed by that many bytes, the length has to be the one expected
+    fn sized(&mut self, expected: usize) -> Result<&[u8], SaveStateError> {
+        if self.u32()? as usize != expected {
+            return Err(SaveStateError::Corrupt);
+        }
+        return self.bytes(expected);
+    }
+
+    fn bools(&mut self, expected: usize) -> Result<Vec<bool>, SaveStateError> {
+        let bytes = self.sized(expected)?;
+        if bytes.iter().any(|byte| *byte > 1) {
+            return Err(SaveStateError::Corrupt);
+        }
+        return Ok(bytes.iter().map(|byte| *byte == 1).collect());
+    }
+}
+
+fn read_emulator(reader: &mut Reader) -> Result<Emulator, SaveStateError> {
+    let mut emulator = Emulator::default();
+
+    let platform = match reader.u8()? {
+        0 => Platform::Chip8,
+        1 => Platform::SuperChip,
+        2 => Platform::XoChip,
+        _ => return Err(SaveStateError::Corrupt),
+    };
+    emulator.set_platform(platform);
+    emulator.quirks = Quirks {
+        shift_uses_vy: reader.bool()?,
+        load_store_increments_i: reader.bool()?,
+        jump_uses_vx: reader.bool()?,
+        logic_resets_vf: reader.bool()?,
+        clip_sprites: reader.bool()?,
+    };
+
+    emulator.pc = reader.u16()?;
+    emulator.sp = reader.u8()?;
+    // stack[1..=sp] holds the entries, so sp can't reach the stack's length
+    if emulator.sp as usize >= emulator.stack.len() {
+        return Err(SaveStateError::Corrupt);
+    }
+    for address in emulator.stack.iter_mut() {
+        *address = reader.u16()?;
+    }
+    emulator.v = reader.array()?;
+    emulator.i = reader.u16()?;
+    emulator.delay_timer = reader.u8()?;
+    emulator.sound_timer = reader.u8()?;
+    let keys = reader.bools(emulator.keys.len())?;
+    emulator.keys.copy_from_slice(&keys);
+
+    let ram_size = platform.ram_size();
+    emulator.ram.copy_from_slice(reader.sized(ram_size)?);
+
+    emulator.set_hires(reader.bool()?);
+    emulator.plane_mask = reader.u8()?;
+    let pixels = emulator.display_width() * emulator.display_height();
+    emulator.display_memory = reader.bools(pixels)?;
+    emulator.second_plane = reader.bools(pixels)?;
+
+    emulator.rpl = reader.array()?;
+    emulator.audio_pattern = reader.array()?;
+    emulator.pitch = reader.u8()?;
+    let kind = RandomKind::from_id(reader.u8()?).ok_or(SaveStateError::Corrupt)?;
+    let len = reader.u32()? as usize;
+    emulator.rng = rng::restore(kind, reader.bytes(len)?).ok_or(SaveStateError::Corrupt)?;
+
+    return Ok(emulator);
+}
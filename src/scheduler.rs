@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use crate::timer::TIMER_FREQUENCY;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+// when more frames than this are owed, for example after the window was dragged or the machine stalled,
+// the rest are dropped instead of running the game at high speed to catch up
+pub const MAX_CATCH_UP_FRAMES: u64 = 5;
+
+// the instructions per frame speed hotkeys step through
+pub const SPEEDS: [u32; 16] = [1, 2, 5, 8, 11, 15, 20, 30, 50, 100, 200, 500, 1000, 2000, 5000, 10000];
+
+// Decides how many 60hz frames to run for the wall clock time that has passed.
+// Time is accumulated as nanoseconds * 60, so no fraction of a frame is lost to rounding and frames never drift.
+// Each frame ticks the timers once, so this is also what keeps them at 60hz.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    pub instructions_per_frame: u32,
+    accumulator: u64,
+}
+
+impl Scheduler {
+    pub fn new(instructions_per_frame: u32) -> Self {
+        return Scheduler { instructions_per_frame, accumulator: 0 };
+    }
+
+    // number of frames due after elapsed more time, at most MAX_CATCH_UP_FRAMES
+    pub fn frames_due(&mut self, elapsed: Duration) -> u64 {
+        self.accumulator += (elapsed.as_nanos() as u64) * TIMER_FREQUENCY;
+
+        let frames = self.accumulator / NANOS_PER_SECOND;
+        self.accumulator %= NANOS_PER_SECOND;
+        if frames > MAX_CATCH_UP_FRAMES {
+            self.accumulator = 0;
+            return MAX_CATCH_UP_FRAMES;
+        }
+        return frames;
+    }
+
+    // how long until the next frame is due, for sleeping between frames
+    pub fn until_next_frame(&self) -> Duration {
+        let remaining = NANOS_PER_SECOND - self.accumulator;
+        return Duration::from_nanos(remaining.div_ceil(TIMER_FREQUENCY));
+    }
+
+    // steps to the next speed in SPEEDS
+    pub fn faster(&mut self) {
+        self.instructions_per_frame = SPEEDS.iter().copied().find(|speed| *speed > self.instructions_per_frame)
+            .unwrap_or(self.instructions_per_frame);
+    }
+
+    // steps to the previous speed in SPEEDS
+    pub fn slower(&mut self) {
+        self.instructions_per_frame = SPEEDS.iter().rev().copied().find(|speed| *speed < self.instructions_per_frame)
+            .unwrap_or(self.instructions_per_frame);
+    }
+}
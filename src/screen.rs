@@ -0,0 +1,151 @@
+use crate::display;
+use crate::emulator::Emulator;
+
+// rgb colour of each pixel value, index 0 is the background, see display::PALETTE
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub colors: [[u8; 3]; 4],
+}
+
+pub const PALETTE_NAMES: [&str; 6] = ["classic", "dark", "green", "amber", "lcd", "octo"];
+
+impl Default for Palette {
+    fn default() -> Self {
+        return Palette { colors: display::PALETTE };
+    }
+}
+
+impl Palette {
+    // looks up a preset by name, or reads 2 or 4 comma separated RRGGBB colours, background first
+    pub fn from_name(name: &str) -> Option<Self> {
+        let colors = match name.to_ascii_lowercase().as_str() {
+            "classic" => display::PALETTE,
+            "dark" => [[0, 0, 0], [255, 255, 255], [85, 85, 85], [170, 170, 170]],
+            "green" => [[0, 20, 0], [51, 255, 102], [17, 119, 51], [34, 187, 68]],
+            "amber" => [[20, 10, 0], [255, 176, 0], [128, 80, 0], [191, 128, 0]],
+            "lcd" => [[155, 188, 15], [15, 56, 15], [48, 98, 48], [139, 172, 15]],
+            "octo" => [[153, 102, 0], [255, 204, 0], [255, 102, 0], [102, 34, 0]],
+            _ => return Palette::from_hex(name),
+        };
+        return Some(Palette { colors });
+    }
+
+    fn from_hex(text: &str) -> Option<Self> {
+        let parsed: Option<Vec<[u8; 3]>> = text.split(',').map(|color| {
+            let color = color.trim().trim_start_matches('#');
+            if color.len() != 6 {
+                return None;
+            }
+            let rgb = u32::from_str_radix(color, 16).ok()?;
+            return Some([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8]);
+        }).collect();
+        let parsed = parsed?;
+
+        let mut colors = display::PALETTE;
+        match parsed.len() {
+            // only background and foreground given, the xo-chip colours are mixed from them
+            2 => {
+                colors[0] = parsed[0];
+                colors[1] = parsed[1];
+                colors[2] = mix(parsed[0], parsed[1], 170);
+                colors[3] = mix(parsed[0], parsed[1], 85);
+            }
+            4 => colors.copy_from_slice(&parsed),
+            _ => return None,
+        }
+        return Some(Palette { colors });
+    }
+}
+
+// blends from one colour towards another, amount 0 is all from and 255 is all to
+fn mix(from: [u8; 3], to: [u8; 3], amount: u8) -> [u8; 3] {
+    let channel = |a: u8, b: u8| ((a as u32 * (255 - amount as u32) + b as u32 * amount as u32) / 255) as u8;
+    return [channel(from[0], to[0]), channel(from[1], to[1]), channel(from[2], to[2])];
+}
+
+// Turns the emulator's display into rgb pixels for a frontend to show.
+// With phosphor fade on, pixels that go dark fade out over a few frames like an old crt instead of vanishing,
+// which hides the flicker of games that erase and redraw their sprites every frame.
+#[derive(Debug, Clone)]
+pub struct Screen {
+    pub palette: Palette,
+    // darken the edge of each pixel so they read as separate dots
+    pub grid: bool,
+    // frames a pixel takes to fade out after it goes dark, 0 for no fading
+    pub phosphor_frames: u32,
+    width: usize,
+    height: usize,
+    // how lit each pixel still is, 255 while on, counting down after it goes dark
+    brightness: Vec<u8>,
+    // palette index each pixel last had while lit, what it fades out from
+    lit: Vec<u8>,
+}
+
+impl Screen {
+    pub fn new(palette: Palette, grid: bool, phosphor_frames: u32) -> Self {
+        return Screen { palette, grid, phosphor_frames, width: 0, height: 0, brightness: Vec::new(), lit: Vec::new() };
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn height(&self) -> usize {
+        return self.height;
+    }
+
+    // takes in the display after a frame. a resolution change starts again with nothing fading
+    pub fn update(&mut self, emulator: &Emulator) {
+        let (width, height) = (emulator.display_width(), emulator.display_height());
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.brightness = vec![0; width * height];
+            self.lit = vec![0; width * height];
+        }
+
+        let fade_step = match self.phosphor_frames {
+            0 => 255,
+            frames => (255 / frames).max(1) as u8,
+        };
+        for index in 0..width * height {
+            let pixel = emulator.pixel(index);
+            if pixel != 0 {
+                self.brightness[index] = 255;
+                self.lit[index] = pixel;
+            } else {
+                self.brightness[index] = self.brightness[index].saturating_sub(fade_step);
+            }
+        }
+    }
+
+    // colour of a pixel as of the last update
+    pub fn color(&self, index: usize) -> [u8; 3] {
+        let background = self.palette.colors[0];
+        return match self.brightness[index] {
+            0 => background,
+            brightness => mix(background, self.palette.colors[self.lit[index] as usize], brightness),
+        };
+    }
+
+    // draws the screen as rgb24 rows with every pixel a cell x cell square, returns the width and height in image pixels
+    pub fn render(&self, cell: usize, rgb: &mut Vec<u8>) -> (usize, usize) {
+        let cell = cell.max(1);
+        let (width, height) = (self.width * cell, self.height * cell);
+        rgb.clear();
+        rgb.reserve(width * height * 3);
+        // a grid needs room for the pixel to still show inside its border
+        let grid = self.grid && cell >= 3;
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut color = self.color((y / cell) * self.width + x / cell);
+                if grid && (x % cell == cell - 1 || y % cell == cell - 1) {
+                    color = mix(color, [0, 0, 0], 64);
+                }
+                rgb.extend(color);
+            }
+        }
+        return (width, height);
+    }
+}
@@ -0,0 +1,213 @@
+// The terminal frontend: the screen drawn as text, the keys read in raw mode and the bell for the beeper.
+// Part of the grep_chip8 binary rather than the library, and only built with the tty feature.
+
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+
+use grep_chip8::beeper::Pattern;
+use grep_chip8::frontend::{self, AudioSink, Input, InputSource, Session, VideoSink};
+use grep_chip8::keymap;
+use grep_chip8::screen::Screen;
+use grep_chip8::tty::{self, Glyphs};
+
+// Most terminals only send a key when it goes down and again as it repeats, never when it's let go,
+// so a key counts as held until it hasn't been sent for this long. Terminals that can report releases
+// (the kitty keyboard protocol) are asked to, and then keys are let go when they really are
+const HOLD_TIME: Duration = Duration::from_millis(150);
+
+// raw mode on the alternate screen while it lives, the terminal is put back as it was when dropped
+struct RawTerminal {
+    // the terminal sends key releases
+    releases: bool,
+}
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide, terminal::Clear(ClearType::All))?;
+        let releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+        if releases {
+            execute!(stdout, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+        }
+        return Ok(RawTerminal { releases });
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        if self.releases {
+            let _ = execute!(stdout, PopKeyboardEnhancementFlags);
+        }
+        let _ = execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// redraws only when the text changed, the status goes on the line under the screen
+struct TtyVideo {
+    stdout: Stdout,
+    glyphs: Glyphs,
+    lines: Vec<String>,
+    status: Option<String>,
+    status_changed: bool,
+}
+
+impl VideoSink for TtyVideo {
+    fn present(&mut self, screen: &Screen) {
+        let lines = tty::render(screen, self.glyphs);
+        if lines == self.lines && !self.status_changed {
+            return;
+        }
+        // a resolution change leaves the old picture's edges behind
+        if lines.len() != self.lines.len() {
+            queue!(self.stdout, terminal::Clear(ClearType::All)).expect("couldnt write to the terminal");
+        }
+        for (row, line) in lines.iter().enumerate() {
+            queue!(self.stdout, cursor::MoveTo(0, row as u16)).expect("couldnt write to the terminal");
+            self.stdout.write_all(line.as_bytes()).expect("couldnt write to the terminal");
+        }
+        queue!(self.stdout, cursor::MoveTo(0, lines.len() as u16), terminal::Clear(ClearType::CurrentLine)).expect("couldnt write to the terminal");
+        if let Some(status) = &self.status {
+            self.stdout.write_all(status.as_bytes()).expect("couldnt write to the terminal");
+        }
+        self.stdout.flush().expect("couldnt write to the terminal");
+        self.lines = lines;
+        self.status_changed = false;
+    }
+
+    fn set_status(&mut self, status: Option<&str>) {
+        self.status = status.map(str::to_string);
+        self.status_changed = true;
+    }
+}
+
+// rings the terminal bell as each beep starts, a terminal can't hold a tone
+struct TtyAudio {
+    bell: bool,
+    playing: bool,
+}
+
+impl AudioSink for TtyAudio {
+    fn set_playing(&mut self, playing: bool) {
+        if self.bell && playing && !self.playing {
+            let mut stdout = io::stdout();
+            stdout.write_all(b"\x07").expect("couldnt write to the terminal");
+            stdout.flush().expect("couldnt write to the terminal");
+        }
+        self.playing = playing;
+    }
+
+    // the bell sounds the same whatever the pattern
+    fn set_pattern(&mut self, _pattern: Option<Pattern>) {}
+}
+
+// the same keys as the window: escape or ctrl+c quits, F5 saves to the current slot, F9 loads from it,
+// F6 / F7 pick the slot, page up / page down change the speed, holding backspace rewinds and enter resets
+// after an error. every other key goes through the keymap
+struct TtyInput {
+    keymap: keymap::Keymap,
+    releases: bool,
+    // when each keypad key was last sent, None while it's up
+    pads: [Option<Instant>; 16],
+    rewind: Option<Instant>,
+}
+
+impl InputSource for TtyInput {
+    fn poll(&mut self) -> Vec<Input> {
+        let mut inputs = Vec::new();
+        let now = Instant::now();
+        while event::poll(Duration::ZERO).unwrap_or(false) {
+            let Ok(Event::Key(key)) = event::read() else {
+                continue;
+            };
+            let held = key.kind != KeyEventKind::Release;
+            let pressed = key.kind == KeyEventKind::Press;
+            let input = match key.code {
+                KeyCode::Esc if pressed => Input::Quit,
+                KeyCode::Char('c') if pressed && key.modifiers.contains(KeyModifiers::CONTROL) => Input::Quit,
+                KeyCode::Enter if pressed => Input::Reset,
+                KeyCode::F(5) if pressed => Input::SaveState,
+                KeyCode::F(9) if pressed => Input::LoadState,
+                KeyCode::F(6) if pressed => Input::PreviousSlot,
+                KeyCode::F(7) if pressed => Input::NextSlot,
+                KeyCode::PageUp if pressed => Input::Faster,
+                KeyCode::PageDown if pressed => Input::Slower,
+                KeyCode::Backspace => {
+                    let was_held = self.rewind.is_some();
+                    self.rewind = held.then_some(now);
+                    if was_held == held {
+                        continue;
+                    }
+                    Input::Rewind { held }
+                }
+                code => {
+                    let Some(pad) = key_name(code).and_then(|name| self.keymap.pad_key(&name)) else {
+                        continue;
+                    };
+                    let was_held = self.pads[pad].is_some();
+                    self.pads[pad] = held.then_some(now);
+                    if was_held == held {
+                        continue;
+                    }
+                    Input::Key { pad, held }
+                }
+            };
+            inputs.push(input);
+        }
+
+        if !self.releases {
+            for (pad, sent) in self.pads.iter_mut().enumerate() {
+                if sent.is_some_and(|sent| now - sent >= HOLD_TIME) {
+                    *sent = None;
+                    inputs.push(Input::Key { pad, held: false });
+                }
+            }
+            if self.rewind.is_some_and(|sent| now - sent >= HOLD_TIME) {
+                self.rewind = None;
+                inputs.push(Input::Rewind { held: false });
+            }
+        }
+        return inputs;
+    }
+}
+
+// the name sdl gives a key, so keymap files work the same in the terminal
+fn key_name(code: KeyCode) -> Option<String> {
+    let name = match code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_ascii_uppercase().to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::Insert => "Insert".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
+        _ => return None,
+    };
+    return Some(name);
+}
+
+// takes over the terminal and runs the session in it until escape is pressed
+pub fn run_terminal(mut session: Session, keymap: keymap::Keymap, glyphs: Glyphs, bell: bool) {
+    let raw = RawTerminal::enter().unwrap_or_else(|error| {
+        eprintln!("Couldn't put the terminal in raw mode: {}", error);
+        std::process::exit(1);
+    });
+    let mut video = TtyVideo { stdout: io::stdout(), glyphs, lines: Vec::new(), status: None, status_changed: false };
+    let mut audio = TtyAudio { bell, playing: false };
+    let mut input = TtyInput { keymap, releases: raw.releases, pads: [None; 16], rewind: None };
+
+    frontend::run(&mut session, &mut video, &mut audio, &mut input);
+    // back to the normal screen first so the movie's message stays up
+    drop(raw);
+    crate::finish(session);
+}
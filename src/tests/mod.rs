@@ -1,3 +1,24 @@
+mod test_asm;
+mod test_beeper;
+mod test_conformance;
 mod test_cpu;
+mod test_debugger;
+mod test_disasm;
 mod test_display;
-mod test_hex_util;
\ No newline at end of file
+mod test_dump;
+mod test_emulator;
+mod test_frontend;
+mod test_gdbstub;
+mod test_hex_util;
+mod test_instruction;
+mod test_keymap;
+mod test_movie;
+mod test_quirks;
+mod test_rewind;
+mod test_rng;
+mod test_savestate;
+mod test_scheduler;
+mod test_screen;
+mod test_timer;
+mod test_trace;
+mod test_tty;
\ No newline at end of file
@@ -0,0 +1,92 @@
+; flags.s - VF after each arithmetic opcode. each check draws the result then VF as two hex digits,
+; five a row, in this order:
+;   01 01  ADD 0xFF + 0x02, carries
+;   30 00  ADD 0x10 + 0x20
+;   20 01  SUB 0x30 - 0x10, no borrow
+;   E0 00  SUB 0x10 - 0x30, borrows
+;   20 01  SUBN 0x30 - 0x10
+;   02 01  SHR 0x05, the bit shifted out
+;   02 01  SHL 0x81, the bit shifted out
+;   01     ADD VF, V1 with VF as the target, the flag is written last
+;   00     SUB VF, V1 with VF as the target
+; none of them depend on a quirk
+; assemble with: cargo run -- asm src/tests/roms/flags.s
+
+        CLS
+        LD V8, 0
+        LD V9, 1
+
+        LD V0, 0xFF
+        LD V1, 0x02
+        ADD V0, V1
+        CALL result
+
+        LD V0, 0x10
+        LD V1, 0x20
+        ADD V0, V1
+        CALL result
+
+        LD V0, 0x30
+        LD V1, 0x10
+        SUB V0, V1
+        CALL result
+
+        LD V0, 0x10
+        LD V1, 0x30
+        SUB V0, V1
+        CALL result
+
+        LD V0, 0x10
+        LD V1, 0x30
+        SUBN V0, V1
+        CALL result
+
+        LD V0, 0x05
+        SHR V0, V0
+        CALL result
+
+        LD V0, 0x81
+        SHL V0, V0
+        CALL result
+
+        LD VF, 0xFF
+        LD V1, 0x02
+        ADD VF, V1
+        LD V0, VF
+        CALL report
+
+        LD VF, 0x10
+        LD V1, 0x20
+        SUB VF, V1
+        LD V0, VF
+        CALL report
+
+done:   JP done
+
+; reports V0 and then VF
+result: LD V3, VF
+        CALL report
+        LD V0, V3
+        CALL report
+        RET
+
+; draws V0 as two hex digits at V8, V9 and moves to the next place, five a row. uses V1, V2, VF and I
+report: LD V1, V0
+        SHR V1, V1
+        SHR V1, V1
+        SHR V1, V1
+        SHR V1, V1
+        LD F, V1
+        DRW V8, V9, 5
+        ADD V8, 5
+        LD V1, V0
+        LD V2, 0x0F
+        AND V1, V2
+        LD F, V1
+        DRW V8, V9, 5
+        ADD V8, 7
+        SE V8, 60
+        RET
+        LD V8, 0
+        ADD V9, 7
+        RET
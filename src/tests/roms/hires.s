@@ -0,0 +1,48 @@
+; hires.s - super-chip's 128x64 screen: a 16x16 sprite, two big font digits and a small one,
+; then the whole screen scrolled down 4 and right 4. the box ends up at 12, 12
+; assemble with: cargo run -- asm --platform schip src/tests/roms/hires.s
+
+        HIGH
+        CLS
+        LD V0, 8
+        LD V1, 8
+        LD I, box
+        DRW V0, V1, 0
+
+        LD V2, 9
+        LD HF, V2
+        LD V0, 32
+        DRW V0, V1, 10
+        LD V2, 5
+        LD HF, V2
+        LD V0, 44
+        DRW V0, V1, 10
+
+        SCD 4
+        SCR
+
+        ; drawn after the scroll, so it stays where it was put
+        LD V2, 0xC
+        LD F, V2
+        LD V0, 100
+        LD V1, 40
+        DRW V0, V1, 5
+
+done:   JP done
+
+box:    db ################
+        db #..............#
+        db #.############.#
+        db #.#..........#.#
+        db #.#..........#.#
+        db #.#..######..#.#
+        db #.#..#....#..#.#
+        db #.#..#.##.#..#.#
+        db #.#..#.##.#..#.#
+        db #.#..#....#..#.#
+        db #.#..######..#.#
+        db #.#..........#.#
+        db #.#..........#.#
+        db #.############.#
+        db #..............#
+        db ################
@@ -0,0 +1,148 @@
+; opcodes.s - one check per chip-8 opcode, the result of each drawn as two hex digits.
+; five results a row, in this order:
+;   42 LD Vx, kk     35 ADD Vx, kk    7A LD Vx, Vy     CA OR            30 AND
+;   F0 XOR           46 ADD Vx, Vy    2F SUB           89 SUBN          42 SHR
+;   62 SHL           AA SE Vx, kk     BB SNE Vx, kk    CC SE Vx, Vy     DD SNE Vx, Vy
+;   5A CALL / RET    11 JP            77 ADD I / LD Vx, [I]   9C LD [I]   54 LD B (254)
+; none of them depend on a quirk
+; assemble with: cargo run -- asm src/tests/roms/opcodes.s
+
+        CLS
+        LD V8, 0
+        LD V9, 1
+
+        LD V0, 0x42
+        CALL report
+
+        LD V0, 0x10
+        ADD V0, 0x25
+        CALL report
+
+        LD V3, 0x7A
+        LD V0, V3
+        CALL report
+
+        LD V0, 0xC0
+        LD V1, 0x0A
+        OR V0, V1
+        CALL report
+
+        LD V0, 0xF3
+        LD V1, 0x3C
+        AND V0, V1
+        CALL report
+
+        LD V0, 0xFF
+        LD V1, 0x0F
+        XOR V0, V1
+        CALL report
+
+        LD V0, 0x12
+        LD V1, 0x34
+        ADD V0, V1
+        CALL report
+
+        LD V0, 0x50
+        LD V1, 0x21
+        SUB V0, V1
+        CALL report
+
+        LD V0, 0x10
+        LD V1, 0x99
+        SUBN V0, V1
+        CALL report
+
+        LD V0, 0x84
+        SHR V0, V0
+        CALL report
+
+        LD V0, 0x31
+        SHL V0, V0
+        CALL report
+
+        LD V0, 0xAA
+        SE V0, 0xAA
+        LD V0, 0xEE
+        CALL report
+
+        LD V0, 0xBB
+        SNE V0, 0x00
+        LD V0, 0xEE
+        CALL report
+
+        LD V0, 0xCC
+        LD V1, 0xCC
+        SE V0, V1
+        LD V0, 0xEE
+        CALL report
+
+        LD V0, 0xDD
+        LD V1, 0x00
+        SNE V0, V1
+        LD V0, 0xEE
+        CALL report
+
+        LD V0, 0
+        CALL set_5a
+        CALL report
+
+        LD V0, 0x11
+        JP jumped
+        LD V0, 0xEE
+jumped: CALL report
+
+        LD I, data
+        LD V1, 2
+        ADD I, V1
+        LD V0, [I]
+        CALL report
+
+        LD I, scratch
+        LD V0, 0x9C
+        LD [I], V0
+        LD V0, 0
+        LD I, scratch
+        LD V0, [I]
+        CALL report
+
+        LD V3, 254
+        LD I, scratch
+        LD B, V3
+        LD I, scratch
+        LD V2, [I]
+        SHL V1, V1
+        SHL V1, V1
+        SHL V1, V1
+        SHL V1, V1
+        OR V1, V2
+        LD V0, V1
+        CALL report
+
+done:   JP done
+
+set_5a: LD V0, 0x5A
+        RET
+
+; draws V0 as two hex digits at V8, V9 and moves to the next place, five a row. uses V1, V2, VF and I
+report: LD V1, V0
+        SHR V1, V1
+        SHR V1, V1
+        SHR V1, V1
+        SHR V1, V1
+        LD F, V1
+        DRW V8, V9, 5
+        ADD V8, 5
+        LD V1, V0
+        LD V2, 0x0F
+        AND V1, V2
+        LD F, V1
+        DRW V8, V9, 5
+        ADD V8, 7
+        SE V8, 60
+        RET
+        LD V8, 0
+        ADD V9, 7
+        RET
+
+data:    db 0x01, 0x02, 0x77
+scratch: db 0, 0, 0
@@ -0,0 +1,56 @@
+; planes.s - xo-chip's second plane. a square on plane 1 (#), one on plane 2 (+) overlapping it (*),
+; one drawn on both planes at once, which reads a sprite for each plane one after the other,
+; and one left on plane 1 after plane 2 was cleared
+; assemble with: cargo run -- asm --platform xochip src/tests/roms/planes.s
+
+        CLS
+        LD V1, 4
+        LD I, square
+
+        ; a square on both planes, then clearing with only plane 2 selected leaves it on plane 1
+        PLANE 1
+        LD V0, 40
+        DRW V0, V1, 8
+        PLANE 2
+        DRW V0, V1, 8
+        CLS
+
+        PLANE 1
+        LD V0, 4
+        DRW V0, V1, 8
+        PLANE 2
+        LD V0, 8
+        DRW V0, V1, 8
+
+        ; plane 1 gets the square and plane 2 the hollow one, so the middle is plane 1 only
+        PLANE 3
+        LD V0, 24
+        LD I, both
+        DRW V0, V1, 8
+
+done:   JP done
+
+square: db ########
+        db ########
+        db ########
+        db ########
+        db ########
+        db ########
+        db ########
+        db ########
+both:   db ########
+        db ########
+        db ########
+        db ########
+        db ########
+        db ########
+        db ########
+        db ########
+        db ########
+        db #......#
+        db #......#
+        db #......#
+        db #......#
+        db #......#
+        db #......#
+        db ########
@@ -0,0 +1,80 @@
+; quirks.s - one check for each quirk, drawn as two hex digits in this order:
+;   shifting   SHR V0, V1 with V0 = 0x01, V1 = 0x08   00 shifts V0 in place, 04 shifts V1
+;   memory     LD [I], V0 then LD V0, [I]             11 I stays, 22 I moved past V0
+;   jumping    JP V0, table with V0 = 0, V2 = 2        B0 jumps to nnn + V0, B2 to xnn + Vx
+;   vf-reset   OR V0, V0 with VF = 0x55               55 VF kept, 00 VF reset
+;   clipping   a sprite drawn over the right edge     01 wraps onto the left, 00 cut off
+; the sprite of the last check stays on the bottom row
+; assemble with: cargo run -- asm src/tests/roms/quirks.s
+
+        CLS
+        LD V8, 0
+        LD V9, 1
+
+        LD V0, 0x01
+        LD V1, 0x08
+        SHR V0, V1
+        CALL report
+
+        LD I, scratch
+        LD V0, 0x11
+        LD [I], V0
+        LD V0, [I]
+        CALL report
+
+        LD V0, 0
+        LD V2, 2
+        JP V0, table
+jumped: CALL report
+
+        LD VF, 0x55
+        LD V0, 1
+        OR V0, V0
+        LD V0, VF
+        CALL report
+
+        ; the sprite ends at x 3 if it wraps, then a dot at x 1 collides with it
+        LD V3, 60
+        LD V4, 31
+        LD I, line
+        DRW V3, V4, 1
+        LD V3, 1
+        LD I, dot
+        DRW V3, V4, 1
+        LD V0, VF
+        CALL report
+
+done:   JP done
+
+; draws V0 as two hex digits at V8, V9 and moves to the next place, five a row. uses V1, V2, VF and I
+report: LD V1, V0
+        SHR V1, V1
+        SHR V1, V1
+        SHR V1, V1
+        SHR V1, V1
+        LD F, V1
+        DRW V8, V9, 5
+        ADD V8, 5
+        LD V1, V0
+        LD V2, 0x0F
+        AND V1, V2
+        LD F, V1
+        DRW V8, V9, 5
+        ADD V8, 7
+        SE V8, 60
+        RET
+        LD V8, 0
+        ADD V9, 7
+        RET
+
+; Bnnn lands on the first entry with V0 and on the second with V2, x is 2 as the table is at 0x2xx
+table:  JP jump_v0
+        JP jump_vx
+jump_v0: LD V0, 0xB0
+        JP jumped
+jump_vx: LD V0, 0xB2
+        JP jumped
+
+line:    db ########
+dot:     db #.......
+scratch: db 0x00, 0x22
@@ -0,0 +1,116 @@
+#[allow(unused_imports)]
+use crate::emulator::Platform;
+
+#[allow(dead_code)]
+fn error_at(source: &str, platform: Platform) -> (usize, usize, String) {
+    let error = crate::asm::assemble(source, 0x200, platform).unwrap_err();
+    return (error.line, error.column, error.message);
+}
+
+#[test]
+fn assemble() {
+    // arrange
+    let source = "
+        ; draws a sprite forever
+        start:  CLS
+                LD I, sprite
+                LD V0, 5
+                DRW V0, V0, 1
+        loop:   JP loop
+        sprite: db 0xF0
+    ";
+
+    // act
+    let rom = crate::asm::assemble(source, 0x200, Platform::Chip8).unwrap();
+
+    // assert
+    assert_eq!(rom, vec![0x00, 0xE0, 0xA2, 0x0A, 0x60, 0x05, 0xD0, 0x01, 0x12, 0x08, 0xF0]);
+}
+
+#[test]
+fn assemble_constants_and_expressions() {
+    // arrange, sprite_end is used before it is defined
+    let source = "
+        ROWS equ sprite_end - sprite
+        X    EQU ROWS + 0b10
+        LD V1, X
+        DRW V1, V1, ROWS
+        LD I, sprite + 1
+        sprite: db 1, 2, 3
+        sprite_end:
+    ";
+
+    // act
+    let rom = crate::asm::assemble(source, 0x200, Platform::Chip8).unwrap();
+
+    // assert
+    assert_eq!(rom, vec![0x61, 0x05, 0xD1, 0x13, 0xA2, 0x07, 1, 2, 3]);
+}
+
+#[test]
+fn assemble_data() {
+    // arrange
+    let source = "db ####...., ...##...\ndb ################\ndw 0x1234, -0 + 2";
+
+    // act
+    let rom = crate::asm::assemble(source, 0x200, Platform::Chip8).unwrap();
+
+    // assert
+    assert_eq!(rom, vec![0xF0, 0x18, 0xFF, 0xFF, 0x12, 0x34, 0x00, 0x02]);
+}
+
+#[test]
+fn assemble_xochip() {
+    // arrange
+    let source = "LD I, LONG data\nLD [I], V1 - V3\nPLANE 3\ndata: db 0";
+
+    // act
+    let rom = crate::asm::assemble(source, 0x200, Platform::XoChip).unwrap();
+
+    // assert
+    assert_eq!(rom, vec![0xF0, 0x00, 0x02, 0x08, 0x51, 0x32, 0xF3, 0x01, 0x00]);
+}
+
+#[test]
+fn assemble_errors() {
+    assert_eq!(error_at("CLS\nLD V0, 256", Platform::Chip8), (2, 8, "value 256 out of range 0 - 0xFF".to_string()));
+    assert_eq!(error_at("  JP nowhere", Platform::Chip8), (1, 6, "unknown symbol 'nowhere'".to_string()));
+    assert_eq!(error_at("MOV V0, V1", Platform::Chip8), (1, 1, "unknown mnemonic 'MOV'".to_string()));
+    assert_eq!(error_at("ld V0, I", Platform::Chip8), (1, 1, "invalid operands for LD".to_string()));
+    assert_eq!(error_at("a: CLS\na: CLS", Platform::Chip8), (2, 1, "'a' is already defined".to_string()));
+    assert_eq!(error_at("\n\tHIGH", Platform::Chip8), (2, 2, "HIGH needs the schip platform".to_string()));
+    assert_eq!(error_at("V1: CLS", Platform::Chip8), (1, 1, "invalid label name 'V1'".to_string()));
+    assert_eq!(error_at("LD V0,", Platform::Chip8), (1, 7, "expected a value".to_string()));
+    assert_eq!(error_at("LD V0, 0x7FFFFFFFFFFFFFFF + 1", Platform::Chip8), (1, 29, "'1' makes the value too big".to_string()));
+    assert_eq!(error_at("DW -0x7FFFFFFFFFFFFFFF - 2", Platform::Chip8), (1, 26, "'2' makes the value too big".to_string()));
+}
+
+#[test]
+fn assemble_every_instruction() {
+    // every instruction the decoder knows assembles back to its opcode from the text the disassembler prints.
+    // opcodes that don't decode print as DW and come back the same way
+    for opcode in 0..=0xFFFF {
+        let instruction = crate::instruction::decode(opcode);
+        if instruction == crate::instruction::Instruction::Ldil {
+            continue;
+        }
+        let source = instruction.to_string();
+
+        let rom = crate::asm::assemble(&source, 0x200, Platform::XoChip);
+
+        assert_eq!(rom, Ok(opcode.to_be_bytes().to_vec()), "{}", source);
+    }
+}
+
+#[test]
+fn assemble_disassembly() {
+    // arrange
+    let rom = vec![0x00, 0xE0, 0xA2, 0x0C, 0x60, 0x05, 0x22, 0x0A, 0x12, 0x08, 0x00, 0xEE, 0xF0, 0x90, 0x90];
+    let listing = crate::disasm::disassemble(&rom, 0x200, Platform::Chip8);
+
+    // act
+    let assembled = crate::asm::assemble(&listing, 0x200, Platform::Chip8);
+
+    // assert
+    assert_eq!(assembled, Ok(rom));
+}
@@ -0,0 +1,93 @@
+#[allow(unused_imports)]
+use crate::beeper::{Beeper, Pattern, Tone, Waveform};
+
+#[test]
+fn waveform_sample() {
+    assert_eq!(Waveform::Square.sample(0.25), 1.0);
+    assert_eq!(Waveform::Square.sample(0.75), -1.0);
+    assert!((Waveform::Sine.sample(0.25) - 1.0).abs() < 1e-6);
+    assert!(Waveform::Sine.sample(0.0).abs() < 1e-6);
+    assert_eq!(Waveform::Triangle.sample(0.0), -1.0);
+    assert_eq!(Waveform::Triangle.sample(0.5), 1.0);
+    assert_eq!(Waveform::Triangle.sample(0.25), 0.0);
+}
+
+#[test]
+fn waveform_from_name() {
+    assert_eq!(Waveform::from_name("Sine"), Some(Waveform::Sine));
+    assert_eq!(Waveform::from_name("saw"), None);
+}
+
+#[test]
+fn fill_silent_when_stopped() {
+    // arrange
+    let mut beeper = Beeper::new(Tone::default(), 44_100);
+    let mut samples = [1.0; 64];
+
+    // act
+    beeper.fill(&mut samples);
+
+    // assert
+    assert!(samples.iter().all(|sample| *sample == 0.0));
+}
+
+#[test]
+fn fill_ramps_in_and_out() {
+    // arrange, a ramp lasts 5ms which is 50 samples at 10khz
+    let tone = Tone { frequency: 50.0, volume: 0.5, waveform: Waveform::Square };
+    let mut beeper = Beeper::new(tone, 10_000);
+    let mut samples = [0.0; 100];
+
+    // act
+    beeper.set_playing(true);
+    beeper.fill(&mut samples);
+
+    // assert, a cycle is 200 samples so the square wave stays in its high half
+    assert!(samples[0] > 0.0 && samples[0] < 0.05);
+    assert!(samples.windows(2).take(49).all(|pair| pair[1] > pair[0]));
+    assert!(samples[50..].iter().all(|sample| *sample == 0.5));
+
+    // act
+    beeper.set_playing(false);
+    beeper.fill(&mut samples);
+
+    // assert, fades to silence instead of cutting off
+    assert!(samples[0].abs() > 0.4 && samples[0].abs() < 0.5);
+    assert!(samples[50..].iter().all(|sample| *sample == 0.0));
+}
+
+#[test]
+fn pattern_rate() {
+    // act
+    let rates: Vec<f32> = [64, 112, 16].iter().map(|pitch| Pattern { bits: [0; 0x10], pitch: *pitch }.rate()).collect();
+
+    // assert, an octave for every 48
+    assert_eq!(rates, vec![4000.0, 8000.0, 2000.0]);
+}
+
+#[test]
+fn fill_plays_the_pattern() {
+    // arrange, at 4000hz the default pitch plays one bit a sample and a ramp lasts 20 samples
+    let mut bits = [0; 0x10];
+    bits[0] = 0b1010_0000;
+    bits[0xF] = 0x01;
+    let mut beeper = Beeper::new(Tone { frequency: 440.0, volume: 0.5, waveform: Waveform::Square }, 4000);
+    let mut samples = [0.0; 384];
+
+    // act
+    beeper.set_pattern(Some(Pattern { bits, pitch: 64 }));
+    beeper.set_playing(true);
+    beeper.fill(&mut samples);
+
+    // assert, the second loop plays the bits high bit first at full volume
+    let expected: Vec<f32> = (0..128).map(|bit| if [0, 2, 127].contains(&bit) { 0.5 } else { -0.5 }).collect();
+    assert_eq!(samples[128..256].to_vec(), expected);
+
+    // act, a pitch 48 higher plays twice as fast
+    beeper.set_pattern(Some(Pattern { bits, pitch: 112 }));
+    beeper.fill(&mut samples);
+
+    // assert, every other bit
+    let expected: Vec<f32> = (0..64).map(|sample| if [0, 1].contains(&sample) { 0.5 } else { -0.5 }).collect();
+    assert_eq!(samples[..64].to_vec(), expected);
+}
@@ -0,0 +1,209 @@
+#[allow(unused_imports)]
+use crate::cli::{Command, FrontendKind, RunOptions};
+#[allow(unused_imports)]
+use grep_chip8::emulator::Platform;
+#[allow(unused_imports)]
+use grep_chip8::quirks::Quirks;
+#[allow(unused_imports)]
+use grep_chip8::trace;
+#[allow(unused_imports)]
+use grep_chip8::tty::Glyphs;
+
+#[allow(dead_code)]
+fn parse(args: &[&str]) -> Result<Command, String> {
+    return crate::cli::parse(args.iter().map(|arg| arg.to_string()));
+}
+
+#[allow(dead_code)]
+fn parse_run(args: &[&str]) -> RunOptions {
+    return match parse(args) {
+        Ok(Command::Run(options)) => *options,
+        other => panic!("expected run options, got {:?}", other),
+    };
+}
+
+#[test]
+fn rom_only() {
+    // act
+    let options = parse_run(&["game.ch8"]);
+
+    // assert
+    assert_eq!(options, RunOptions { rom_path: "game.ch8".to_string(), ..RunOptions::default() });
+    assert_eq!(options.quirks(), Quirks::default());
+    assert_eq!(options.instructions_per_frame(), 11);
+}
+
+#[test]
+fn run_command() {
+    // act
+    let options = parse_run(&["run", "--platform", "schip", "--scale", "4", "--headless", "--frames", "300", "game.ch8"]);
+
+    // assert, schip's own quirks and speed
+    assert_eq!(options.rom_path, "game.ch8");
+    assert_eq!(options.platform, Platform::SuperChip);
+    assert_eq!(options.scale, 4);
+    assert!(options.headless);
+    assert_eq!(options.frames, Some(300));
+    assert_eq!(options.quirks(), Quirks::schip());
+    assert_eq!(options.instructions_per_frame(), 30);
+}
+
+#[test]
+fn quirk_overrides() {
+    // act
+    let options = parse_run(&["--quirks", "vip", "--quirk", "clipping=off", "--quirk", "Jumping=on", "game.ch8"]);
+
+    // assert
+    let mut expected = Quirks::vip();
+    expected.clip_sprites = false;
+    expected.jump_uses_vx = true;
+    assert_eq!(options.quirks(), expected);
+}
+
+#[test]
+fn speed() {
+    // act
+    let per_second = parse_run(&["--speed", "700", "game.ch8"]);
+    let slow = parse_run(&["--speed", "1", "game.ch8"]);
+    let per_frame = parse_run(&["--speed", "700", "--ipf", "15", "game.ch8"]);
+    let fastest = parse_run(&["--speed", "4294967295", "game.ch8"]);
+
+    // assert, rounded to whole instructions per frame, at least one
+    assert_eq!(per_second.instructions_per_frame(), 12);
+    assert_eq!(slow.instructions_per_frame(), 1);
+    assert_eq!(per_frame.instructions_per_frame(), 15);
+    assert_eq!(fastest.instructions_per_frame(), u32::MAX / 60);
+}
+
+#[test]
+fn load_address() {
+    // act
+    let hex = parse_run(&["--load-address", "0x600", "game.ch8"]);
+    let decimal = parse_run(&["--load-address", "1536", "game.ch8"]);
+    let invalid = parse(&["--load-address", "0x10000", "game.ch8"]);
+
+    // assert
+    assert_eq!(hex.load_address, 0x600);
+    assert_eq!(decimal.load_address, 0x600);
+    assert_eq!(invalid, Err("Expected an address like 0x200, found '0x10000'".to_string()));
+}
+
+#[test]
+fn frontend() {
+    // act
+    let tty = parse_run(&["--frontend", "tty", "--glyphs", "braille", "game.ch8"]);
+    let sdl = parse_run(&["--frontend", "SDL", "game.ch8"]);
+
+    // assert
+    assert_eq!(tty.frontend, Some(FrontendKind::Tty));
+    assert_eq!(tty.glyphs, Glyphs::Braille);
+    assert_eq!(sdl.frontend, Some(FrontendKind::Sdl));
+    assert_eq!(sdl.glyphs, Glyphs::HalfBlock);
+}
+
+#[test]
+fn debug_with_the_default_frontend() {
+    // act
+    let result = parse(&["--debug", "game.ch8"]);
+    let headless = parse(&["--debug", "--headless", "game.ch8"]);
+
+    // assert, a build without the window runs in the terminal, which has nowhere to type commands
+    assert_eq!(result.is_ok(), cfg!(feature = "sdl"), "{:?}", result);
+    assert!(headless.is_ok());
+}
+
+#[test]
+fn trace() {
+    // act
+    let options = parse_run(&["--trace", "t.bin", "--trace-format", "binary", "--trace-range", "0x200-768", "--trace-class", "display, flow", "game.ch8"]);
+
+    // assert
+    assert_eq!(options.trace_path.as_deref(), Some("t.bin"));
+    assert_eq!(options.trace_format, trace::Format::Binary);
+    assert_eq!(options.trace_filter, trace::Filter { start: 0x200, end: 0x300, classes: vec![trace::Class::Display, trace::Class::Flow] });
+}
+
+#[test]
+fn disasm_and_asm() {
+    // act
+    let disasm = parse(&["disasm", "--platform", "xochip", "game.ch8"]);
+    let asm = parse(&["asm", "game.s", "-o", "out.ch8", "--load-address", "0x300"]);
+
+    // assert
+    assert_eq!(disasm, Ok(Command::Disasm(crate::cli::DisasmOptions { rom_path: "game.ch8".to_string(), platform: Platform::XoChip, load_address: 0x200 })));
+    assert_eq!(asm, Ok(Command::Asm(crate::cli::AsmOptions {
+        source_path: "game.s".to_string(),
+        output_path: Some("out.ch8".to_string()),
+        platform: Platform::Chip8,
+        load_address: 0x300,
+    })));
+}
+
+#[test]
+fn help() {
+    // act
+    let top = parse(&["--help"]);
+    let command = parse(&["help"]);
+    let run = parse(&["--platform", "schip", "-h"]);
+    let asm = parse(&["help", "asm"]);
+    let unknown = parse(&["help", "play"]);
+
+    // assert
+    assert_eq!(top, Ok(Command::Help(crate::cli::USAGE)));
+    assert_eq!(command, Ok(Command::Help(crate::cli::USAGE)));
+    assert_eq!(run, Ok(Command::Help(crate::cli::RUN_HELP)));
+    assert_eq!(asm, Ok(Command::Help(crate::cli::ASM_HELP)));
+    assert!(unknown.is_err());
+}
+
+#[test]
+fn help_lists_every_option() {
+    // arrange, every option the parser knows, found by trying each word of the help on its own
+    let words: Vec<&str> = crate::cli::RUN_HELP.split_whitespace().filter(|word| word.starts_with("--")).collect();
+
+    // assert, none of them is unknown
+    for word in words {
+        let result = parse(&[word, "game.ch8"]);
+        assert!(!result.as_ref().is_err_and(|error| error.starts_with("Unknown option")), "{} {:?}", word, result);
+    }
+}
+
+#[test]
+fn errors() {
+    // act
+    let cases = [
+        (parse(&[]), "No rom given"),
+        (parse(&["--frobnicate", "game.ch8"]), "Unknown option '--frobnicate'"),
+        (parse(&["a.ch8", "b.ch8"]), "Only one rom can be given, found 'a.ch8' and 'b.ch8'"),
+        (parse(&["game.ch8", "--ipf"]), "--ipf needs a value"),
+        (parse(&["--ipf", "0", "game.ch8"]), "Expected a number of instructions per frame, found '0'"),
+        (parse(&["--scale", "101", "game.ch8"]), "Expected a scale from 1 to 100, found '101'"),
+        (parse(&["--platform", "nes", "game.ch8"]), "Unknown platform 'nes', expected one of: chip8, schip, xochip"),
+        (parse(&["--quirk", "wrapping=on", "game.ch8"]), "Unknown quirk 'wrapping', expected one of: shifting, memory, jumping, vf-reset, clipping"),
+        (parse(&["--quirk", "clipping", "game.ch8"]), "Expected a quirk as NAME=on or NAME=off, found 'clipping'"),
+        (parse(&["--debug", "--gdb", "1234", "game.ch8"]), "--debug and --gdb can't be used together"),
+        (parse(&["--vip-interpreter", "vip.bin", "game.ch8"]), "--vip-interpreter only works with --rng vip"),
+        (parse(&["--frontend", "gtk", "game.ch8"]), "Unknown frontend 'gtk', expected one of: sdl, tty"),
+        (parse(&["--frontend", "tty", "--headless", "game.ch8"]), "--frontend can't be used with --headless"),
+        (parse(&["--frontend", "tty", "--debug", "game.ch8"]), "--debug can't be used with the tty frontend, run it in the window or --headless"),
+        (parse(&["--trace-class", "alu", "game.ch8"]), "--trace-format, --trace-range and --trace-class need --trace"),
+        (parse(&["--trace", "t.txt", "--trace-range", "0x300-0x200", "game.ch8"]), "The range 0x300-0x200 starts after it ends"),
+        (parse(&["--trace", "t.txt", "--trace-range", "0x300", "game.ch8"]), "Expected a range of addresses like 0x200-0x2FF, found '0x300'"),
+        (parse(&["--trace", "t.txt", "--trace-class", "alu,draw", "game.ch8"]), "Unknown instruction class 'draw', expected one of: flow, skip, alu, memory, display, timer, input, sound"),
+        (parse(&["asm", "-o", "out.ch8"]), "No source file given"),
+    ];
+
+    // assert
+    for (result, message) in cases {
+        assert_eq!(result, Err(message.to_string()));
+    }
+}
+
+#[test]
+fn dash_is_a_path() {
+    // act
+    let options = parse_run(&["-"]);
+
+    // assert
+    assert_eq!(options.rom_path, "-");
+}
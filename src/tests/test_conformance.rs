@@ -0,0 +1,141 @@
+// Whole programs rather than single instructions: each rom in src/tests/roms is run for a number of frames
+// and its display compared with a golden image, the text `--dump out.txt` writes. the roms draw what they
+// found as hex digits, their sources say what each one should be. after a deliberate change to what a rom
+// draws, GOLDEN_UPDATE=1 cargo test writes the images again
+use std::fs;
+use std::path::PathBuf;
+
+#[allow(unused_imports)]
+use crate::dump;
+#[allow(unused_imports)]
+use crate::emulator::Platform;
+#[allow(unused_imports)]
+use crate::frontend;
+#[allow(unused_imports)]
+use crate::quirks::Quirks;
+#[allow(unused_imports)]
+use crate::rng::Xorshift;
+
+// enough for every rom to finish drawing at its platform's speed
+#[allow(dead_code)]
+const FRAMES: u32 = 60;
+
+#[allow(dead_code)]
+fn roms_path(file: &str) -> PathBuf {
+    return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/roms").join(file);
+}
+
+// runs rom.ch8 and compares the display with golden.txt, panicking with a diff of the rows that differ
+#[allow(dead_code)]
+fn check(rom: &str, platform: Platform, quirks: Quirks, golden: &str) {
+    let rom_path = roms_path(&format!("{}.ch8", rom));
+    let golden_path = roms_path(&format!("{}.txt", golden));
+    let data = fs::read(&rom_path).unwrap_or_else(|error| panic!("couldn't read {}: {}", rom_path.display(), error));
+    let mut emulator = frontend::create_emulator(platform, quirks, &data, crate::emulator::PROGRAM_START, &Xorshift::new(0)).unwrap();
+    for frame in 0..FRAMES {
+        emulator.run_frame(platform.default_instructions_per_frame()).unwrap_or_else(|error| panic!("{} stopped in frame {}: {}", rom, frame, error));
+    }
+    let actual = dump::ascii(&emulator);
+
+    if std::env::var_os("GOLDEN_UPDATE").is_some() {
+        fs::write(&golden_path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&golden_path).unwrap_or_else(|error| panic!("couldn't read {}: {}, GOLDEN_UPDATE=1 writes it", golden_path.display(), error));
+    if let Some(diff) = diff(&expected, &actual) {
+        panic!("{}.ch8 doesn't draw {}.txt after {} frames, {}\nGOLDEN_UPDATE=1 writes the new display if the change is meant", rom, golden, FRAMES, diff);
+    }
+}
+
+// None when the images match, otherwise each row that differs as expected over actual with ^ under the changed pixels
+#[allow(dead_code)]
+fn diff(expected: &str, actual: &str) -> Option<String> {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let size = |lines: &[&str]| (lines.first().map_or(0, |line| line.len()), lines.len());
+    if size(&expected) != size(&actual) {
+        let ((expected_width, expected_height), (actual_width, actual_height)) = (size(&expected), size(&actual));
+        return Some(format!(
+            "the display is {}x{} instead of {}x{}\nexpected:\n{}\nactual:\n{}",
+            actual_width, actual_height, expected_width, expected_height, expected.join("\n"), actual.join("\n")
+        ));
+    }
+
+    let mut rows = String::new();
+    let mut pixels = 0;
+    for (y, (expected_row, actual_row)) in expected.iter().zip(&actual).enumerate() {
+        if expected_row == actual_row {
+            continue;
+        }
+        let markers: String = expected_row.chars().zip(actual_row.chars()).map(|(e, a)| if e == a { ' ' } else { '^' }).collect();
+        pixels += markers.matches('^').count();
+        rows.push_str(&format!("row {:2} expected {}\n       actual   {}\n                {}\n", y, expected_row, actual_row, markers.trim_end()));
+    }
+    if pixels == 0 {
+        return None;
+    }
+    return Some(format!("{} pixels differ:\n{}", pixels, rows));
+}
+
+#[test]
+fn diff_marks_changed_pixels() {
+    // act
+    let same = diff("..#\n#..\n", "..#\n#..\n");
+    let changed = diff("..#\n#..\n", "..#\n##.\n");
+    let resized = diff("..\n", "...\n");
+
+    // assert
+    assert_eq!(same, None);
+    assert_eq!(changed.unwrap(), "1 pixels differ:\nrow  1 expected #..\n       actual   ##.\n                 ^\n");
+    assert!(resized.unwrap().starts_with("the display is 3x1 instead of 2x1"));
+}
+
+#[test]
+fn roms_match_their_sources() {
+    // arrange, the roms are checked in assembled so they still test the cpu if the assembler breaks
+    let roms = [("opcodes", Platform::Chip8), ("flags", Platform::Chip8), ("quirks", Platform::Chip8), ("hires", Platform::SuperChip), ("planes", Platform::XoChip)];
+
+    for (rom, platform) in roms {
+        // act
+        let source = fs::read_to_string(roms_path(&format!("{}.s", rom))).unwrap();
+        let assembled = crate::asm::assemble(&source, crate::emulator::PROGRAM_START, platform).unwrap();
+
+        // assert
+        assert_eq!(assembled, fs::read(roms_path(&format!("{}.ch8", rom))).unwrap(), "{}.ch8 is out of date, assemble {}.s again", rom, rom);
+    }
+}
+
+#[test]
+fn opcodes() {
+    check("opcodes", Platform::Chip8, Quirks::default(), "opcodes");
+}
+
+#[test]
+fn flags() {
+    check("flags", Platform::Chip8, Quirks::default(), "flags");
+}
+
+#[test]
+fn quirks_default() {
+    check("quirks", Platform::Chip8, Quirks::default(), "quirks_default");
+}
+
+#[test]
+fn quirks_vip() {
+    check("quirks", Platform::Chip8, Quirks::vip(), "quirks_vip");
+}
+
+#[test]
+fn quirks_schip() {
+    check("quirks", Platform::Chip8, Quirks::schip(), "quirks_schip");
+}
+
+#[test]
+fn hires() {
+    check("hires", Platform::SuperChip, Quirks::schip(), "hires");
+}
+
+#[test]
+fn planes() {
+    check("planes", Platform::XoChip, Quirks::xochip(), "planes");
+}
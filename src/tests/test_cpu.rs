@@ -6,7 +6,7 @@ fn get_next_instruction() {
     // arrange
     let mut emulator = Emulator::default();
     emulator.pc = 2;
-    emulator.ram = [0; 0x1000];
+    emulator.ram = vec![0; 0x1000];
 
     emulator.ram[2] = 0xAB;
     emulator.ram[3] = 0xCD;
@@ -15,16 +15,48 @@ fn get_next_instruction() {
     let expected_instruction: u16 = 0xABCD;
 
     // act
-    let next_instruction = crate::cpu::get_next_instruction(&mut emulator);
+    let next_instruction = crate::cpu::get_next_instruction(&mut emulator).unwrap();
 
     // assert
     assert_eq!(expected_pc, emulator.pc);
     assert_eq!(expected_instruction, next_instruction);
 }
 
+#[test]
+fn execute_frame() {
+    // arrange, ADD V0, 1 forever
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200..0x204].copy_from_slice(&[0x70, 0x01, 0x12, 0x00]);
+    emulator.delay_timer = 10;
+
+    // act
+    crate::cpu::execute_frame(&mut emulator, 10).unwrap();
+    crate::cpu::execute_frame(&mut emulator, 10).unwrap();
+
+    // assert, 20 instructions but only two timer ticks
+    assert_eq!(emulator.v[0], 10);
+    assert_eq!(emulator.delay_timer, 8);
+}
+
+#[test]
+fn execute_frame_error() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200..0x202].copy_from_slice(&[0x80, 0x0A]);
+    emulator.delay_timer = 10;
+
+    // act
+    let result = crate::cpu::execute_frame(&mut emulator, 10);
+
+    // assert, the timers don't tick for a frame that didn't finish
+    assert_eq!(result.unwrap_err().kind, crate::cpu::CpuErrorKind::UnknownOpcode);
+    assert_eq!(emulator.delay_timer, 10);
+}
+
 ////////////////////////////// INSTRUCTIONS ////////////////////////////////////////
 
 #[test]
+#[allow(clippy::assertions_on_constants)]
 fn sys() {
     // act
     crate::cpu::sys();
@@ -37,7 +69,7 @@ fn sys() {
 fn cls() {
     // arrange
     let mut emulator = Emulator::default();
-    emulator.display_memory = [true; 0x800];
+    emulator.display_memory = vec![true; 0x800];
 
     let expected_display_mem: [bool; 0x800] = [false; 0x800];
 
@@ -59,7 +91,7 @@ fn ret() {
     let expected_sp: u8 = 4;
 
     // act
-    crate::cpu::ret(&mut emulator);
+    crate::cpu::ret(&mut emulator).unwrap();
 
     // assert
     assert_eq!(expected_pc, emulator.pc);
@@ -72,10 +104,9 @@ fn jp() {
     let mut emulator = Emulator::default();
 
     let expected_pc: u16 = 0x0123;
-    let instruction: u16 = 0x1123;
 
     // act
-    crate::cpu::jp(&mut emulator, instruction);
+    crate::cpu::jp(&mut emulator, 0x123);
 
     // assert
     assert_eq!(emulator.pc, expected_pc);
@@ -89,11 +120,10 @@ fn call() {
 
     let expected_sp: u8 = 1;
     let expected_stack_value = 0x0456; // current pc pushed onto stack
-    let instruction: u16 = 0x2123;
     let expected_pc = 0x0123;
 
     // act
-    crate::cpu::call(&mut emulator, instruction);
+    crate::cpu::call(&mut emulator, 0x123).unwrap();
 
     // assert
     assert_eq!(emulator.pc, expected_pc);
@@ -105,16 +135,14 @@ fn call() {
 fn se() {
     // arrange
     let mut emulator = Emulator::default();
+    emulator.pc = 0;
     emulator.v[4] = 0x56;
 
-    let eq_instruction = 0x3456;
-    let neq_instruction = 0x3457;
-
-    crate::cpu::se(&mut emulator, eq_instruction);
+    crate::cpu::se(&mut emulator, 4, 0x56).unwrap();
     assert_eq!(emulator.pc, 2);
 
     // not equal, dont increment
-    crate::cpu::se(&mut emulator, neq_instruction);
+    crate::cpu::se(&mut emulator, 4, 0x57).unwrap();
     assert_eq!(emulator.pc, 2);
 }
 
@@ -122,16 +150,14 @@ fn se() {
 fn sne() {
     // arrange
     let mut emulator = Emulator::default();
+    emulator.pc = 0;
     emulator.v[4] = 0x56;
 
-    let eq_instruction = 0x4456;
-    let neq_instruction = 0x4457;
-
-    crate::cpu::sne(&mut emulator, eq_instruction);
+    crate::cpu::sne(&mut emulator, 4, 0x56).unwrap();
     assert_eq!(emulator.pc, 0);
 
     // equal, dont increment
-    crate::cpu::sne(&mut emulator, neq_instruction);
+    crate::cpu::sne(&mut emulator, 4, 0x57).unwrap();
     assert_eq!(emulator.pc, 2);
 }
 
@@ -139,17 +165,15 @@ fn sne() {
 fn sev() {
     // arrange
     let mut emulator = Emulator::default();
+    emulator.pc = 0;
     emulator.v[4] = 0x56;
     emulator.v[5] = 0x56;
 
-    let eq_instruction = 0x5450;
-    let neq_instruction = 0x5460;
-
-    crate::cpu::sev(&mut emulator, eq_instruction);
+    crate::cpu::sev(&mut emulator, 4, 5).unwrap();
     assert_eq!(emulator.pc, 2);
 
     // not equal, dont increment
-    crate::cpu::sev(&mut emulator, neq_instruction);
+    crate::cpu::sev(&mut emulator, 4, 6).unwrap();
     assert_eq!(emulator.pc, 2);
 }
 
@@ -159,10 +183,9 @@ fn ldx() {
     let mut emulator = Emulator::default();
 
     // act
-    let instruction = 0x7450;
+    crate::cpu::ldx(&mut emulator, 4, 0x50);
 
     // assert
-    crate::cpu::ldx(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0x50);
 }
 
@@ -173,10 +196,9 @@ fn addx() {
     emulator.v[4] = 0x1;
 
     // act
-    let instruction = 0x7450;
+    crate::cpu::addx(&mut emulator, 4, 0x50);
 
     // assert
-    crate::cpu::addx(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0x51);
 }
 
@@ -187,10 +209,9 @@ fn ldxy() {
     emulator.v[5] = 0x2;
 
     // act
-    let instruction = 0x8450;
+    crate::cpu::ldxy(&mut emulator, 4, 5);
 
     // assert
-    crate::cpu::ldxy(&mut emulator, instruction);
     assert_eq!(emulator.v[4], emulator.v[5]);
 }
 
@@ -202,10 +223,9 @@ fn orxy() {
     emulator.v[5] = 0x4;
 
     // act
-    let instruction = 0x8451;
+    crate::cpu::orxy(&mut emulator, 4, 5);
 
     // assert
-    crate::cpu::orxy(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0xC);
 }
 
@@ -217,10 +237,9 @@ fn andxy() {
     emulator.v[5] = 0x4;
 
     // act
-    let instruction = 0x8452;
+    crate::cpu::andxy(&mut emulator, 4, 5);
 
     // assert
-    crate::cpu::andxy(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0x4);
 }
 
@@ -232,10 +251,9 @@ fn xorxy() {
     emulator.v[5] = 0x4;
 
     // act
-    let instruction = 0x8453;
+    crate::cpu::xorxy(&mut emulator, 4, 5);
 
     // assert
-    crate::cpu::xorxy(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0x8);
 }
 
@@ -247,10 +265,9 @@ fn addxy() {
     emulator.v[5] = 0x3;
 
     // act
-    let instruction = 0x8454;
+    crate::cpu::addxy(&mut emulator, 4, 5);
 
     // assert
-    crate::cpu::addxy(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0x2);
     assert_eq!(emulator.v[0xF], 0x1);
 }
@@ -263,10 +280,9 @@ fn subxy() {
     emulator.v[5] = 0x3;
 
     // act
-    let instruction = 0x8455;
+    crate::cpu::subxy(&mut emulator, 4, 5);
 
     // assert
-    crate::cpu::subxy(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0xFC);
     assert_eq!(emulator.v[0xF], 0x1);
 }
@@ -279,10 +295,9 @@ fn shrxy() {
     emulator.v[4] = 0xFF;
 
     // act
-    let instruction = 0x8456;
+    crate::cpu::shrxy(&mut emulator, 4, 5);
 
     // assert
-    crate::cpu::shrxy(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0x7F);
     assert_eq!(emulator.v[0xF], 0x1);
 }
@@ -295,10 +310,9 @@ fn subnxy() {
     emulator.v[5] = 0xFF;
 
     // act
-    let instruction = 0x8457;
+    crate::cpu::subnxy(&mut emulator, 4, 5);
 
     // assert
-    crate::cpu::subnxy(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0xFC);
     assert_eq!(emulator.v[0xF], 0x1);
 }
@@ -311,10 +325,9 @@ fn shlxy() {
     emulator.v[4] = 0xF0;
 
     // act
-    let instruction = 0x845E;
+    crate::cpu::shlxy(&mut emulator, 4, 5);
 
     // assert
-    crate::cpu::shlxy(&mut emulator, instruction);
     assert_eq!(emulator.v[4], 0xE0);
     assert_eq!(emulator.v[0xF], 0x1);
 }
@@ -323,12 +336,11 @@ fn shlxy() {
 fn snexy() {
     // arrange
     let mut emulator = Emulator::default();
+    emulator.pc = 0;
     emulator.v[4] = 0x56;
     emulator.v[5] = 0x57;
 
-    let instruction = 0x9450;
-
-    crate::cpu::snexy(&mut emulator, instruction);
+    crate::cpu::snexy(&mut emulator, 4, 5).unwrap();
     assert_eq!(emulator.pc, 2);
 }
 
@@ -336,11 +348,9 @@ fn snexy() {
 fn ldi() {
     // arrange
     let mut emulator = Emulator::default();
-    
-    let instruction = 0xA450;
 
     // act
-    crate::cpu::ldi(&mut emulator, instruction);
+    crate::cpu::ldi(&mut emulator, 0x450);
 
     // assert
     assert_eq!(emulator.i, 0x450);
@@ -351,10 +361,9 @@ fn jpv() {
     // arrange
     let mut emulator = Emulator::default();
     emulator.v[0] = 0x1;
-    let instruction = 0xB450;
 
     // act
-    crate::cpu::jpv(&mut emulator, instruction);
+    crate::cpu::jpv(&mut emulator, 0x450);
 
     // assert
     assert_eq!(emulator.pc, 0x451);
@@ -364,19 +373,50 @@ fn jpv() {
 fn rnd() {
     // arrange
     let mut emulator = Emulator::default();
-    
-    let instruction = 0xC410;
 
     // act
-    crate::cpu::rnd(&mut emulator, instruction);
+    crate::cpu::rnd(&mut emulator, 4, 0x10);
 
     // assert
     assert!(emulator.v[4] <= 0x10);
 }
 
+#[test]
+fn rnd_seeded() {
+    // arrange, the same seed on a second source gives the byte the emulator will get
+    let mut emulator = Emulator::default();
+    emulator.rng = Box::new(crate::rng::Xorshift::new(3));
+    let mut expected = crate::rng::Xorshift::new(3);
+
+    // act
+    crate::cpu::rnd(&mut emulator, 4, 0xF0);
+
+    // assert
+    assert_eq!(emulator.v[4], crate::rng::Random::next_byte(&mut expected) & 0xF0);
+}
+
 #[test]
 fn drw() {
-    // todo
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.i = 0x300;
+    emulator.ram[0x300] = 0x81;
+    emulator.v[0] = 2;
+    emulator.v[1] = 3;
+
+    // act
+    crate::cpu::drw(&mut emulator, 0, 1, 1).unwrap();
+
+    // assert
+    assert!(emulator.display_memory[3 * 64 + 2]);
+    assert!(emulator.display_memory[3 * 64 + 9]);
+    assert_eq!(emulator.display_memory.iter().filter(|p| **p).count(), 2);
+    assert_eq!(emulator.v[0xF], 0);
+
+    // drawing again erases the sprite and reports the collision
+    crate::cpu::drw(&mut emulator, 0, 1, 1).unwrap();
+    assert!(emulator.display_memory.iter().all(|p| !*p));
+    assert_eq!(emulator.v[0xF], 1);
 }
 
 #[test]
@@ -395,10 +435,8 @@ fn ldxdt() {
     let mut emulator = Emulator::default();
     emulator.delay_timer = 0x10;
 
-    let instruction = 0xF407;
-
     // act
-    crate::cpu::ldxdt(&mut emulator, instruction);
+    crate::cpu::ldxdt(&mut emulator, 4);
 
     // assert
     assert_eq!(emulator.v[4], emulator.delay_timer);
@@ -415,10 +453,8 @@ fn lddt() {
     let mut emulator = Emulator::default();
     emulator.v[4] = 0x10;
 
-    let instruction = 0xF415;
-
     // act
-    crate::cpu::lddt(&mut emulator, instruction);
+    crate::cpu::lddt(&mut emulator, 4);
 
     // assert
     assert_eq!(emulator.delay_timer, emulator.v[4]);
@@ -430,10 +466,8 @@ fn ldst() {
     let mut emulator = Emulator::default();
     emulator.v[4] = 0x10;
 
-    let instruction = 0xF418;
-
     // act
-    crate::cpu::ldst(&mut emulator, instruction);
+    crate::cpu::ldst(&mut emulator, 4);
 
     // assert
     assert_eq!(emulator.sound_timer, emulator.v[4]);
@@ -446,10 +480,8 @@ fn addi() {
     emulator.v[4] = 0x10;
     emulator.i = 0x2;
 
-    let instruction = 0xF41E;
-
     // act
-    crate::cpu::addi(&mut emulator, instruction);
+    crate::cpu::addi(&mut emulator, 4);
 
     // assert
     assert_eq!(emulator.i, 0x12);
@@ -466,10 +498,8 @@ fn ldb() {
     let mut emulator = Emulator::default();
     emulator.v[4] = 123;
 
-    let instruction = 0xF433;
-
     // act
-    crate::cpu::ldb(&mut emulator, instruction);
+    crate::cpu::ldb(&mut emulator, 4).unwrap();
 
     // assert
     assert_eq!(emulator.ram[emulator.i as usize], 1);
@@ -485,10 +515,8 @@ fn ldii() {
     emulator.v[1] = 0x2;
     emulator.v[2] = 0x3;
 
-    let instruction = 0xF255;
-
     // act
-    crate::cpu::ldii(&mut emulator, instruction);
+    crate::cpu::ldii(&mut emulator, 2).unwrap();
 
     // assert
     assert_eq!(emulator.ram[emulator.i as usize], 1);
@@ -504,13 +532,562 @@ fn ldvi() {
     emulator.ram[1] = 0x2;
     emulator.ram[2] = 0x3;
 
-    let instruction = 0xF265;
-
     // act
-    crate::cpu::ldvi(&mut emulator, instruction);
+    crate::cpu::ldvi(&mut emulator, 2).unwrap();
 
     // assert
     assert_eq!(emulator.v[0], 1);
     assert_eq!(emulator.v[1], 2);
     assert_eq!(emulator.v[2], 3);
-}
\ No newline at end of file
+}
+
+////////////////////////////// QUIRKS ////////////////////////////////////////
+
+#[test]
+fn logic_resets_vf_quirk() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.quirks.logic_resets_vf = true;
+    emulator.v[0xF] = 0x1;
+    emulator.v[4] = 0xC;
+    emulator.v[5] = 0x4;
+
+    // act
+    crate::cpu::orxy(&mut emulator, 4, 5);
+
+    // assert
+    assert_eq!(emulator.v[4], 0xC);
+    assert_eq!(emulator.v[0xF], 0x0);
+}
+
+#[test]
+fn shift_uses_vy_quirk() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.quirks.shift_uses_vy = true;
+    emulator.v[4] = 0x0;
+    emulator.v[5] = 0x81;
+
+    // act
+    crate::cpu::shrxy(&mut emulator, 4, 5);
+
+    // assert
+    assert_eq!(emulator.v[4], 0x40);
+    assert_eq!(emulator.v[0xF], 0x1);
+
+    // act
+    crate::cpu::shlxy(&mut emulator, 4, 5);
+
+    // assert
+    assert_eq!(emulator.v[4], 0x02);
+    assert_eq!(emulator.v[5], 0x81);
+    assert_eq!(emulator.v[0xF], 0x1);
+}
+
+#[test]
+fn shift_flag_wins_over_vf_result() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.v[0xF] = 0x3;
+
+    // act
+    crate::cpu::shrxy(&mut emulator, 15, 0);
+
+    // assert
+    assert_eq!(emulator.v[0xF], 0x1);
+}
+
+#[test]
+fn jump_uses_vx_quirk() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.quirks.jump_uses_vx = true;
+    emulator.v[0] = 0x1;
+    emulator.v[4] = 0x2;
+
+    // act
+    crate::cpu::jpv(&mut emulator, 0x450);
+
+    // assert
+    assert_eq!(emulator.pc, 0x452);
+}
+
+#[test]
+fn load_store_increments_i_quirk() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.quirks.load_store_increments_i = true;
+    emulator.i = 0x300;
+
+    // act
+    crate::cpu::ldii(&mut emulator, 2).unwrap();
+
+    // assert
+    assert_eq!(emulator.i, 0x303);
+
+    // act
+    crate::cpu::ldvi(&mut emulator, 0).unwrap();
+
+    // assert
+    assert_eq!(emulator.i, 0x304);
+}
+
+#[test]
+fn drw_wraps_by_default() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.i = 0x300;
+    emulator.ram[0x300] = 0xFF;
+    emulator.v[0] = 60;
+    emulator.v[1] = 31;
+
+    // act
+    crate::cpu::drw(&mut emulator, 0, 1, 1).unwrap();
+
+    // assert, the 4 rightmost pixels wrap to the start of the row
+    assert!(emulator.display_memory[31 * 64 + 63]);
+    assert!(emulator.display_memory[31 * 64]);
+    assert!(emulator.display_memory[31 * 64 + 3]);
+    assert!(!emulator.display_memory[31 * 64 + 4]);
+}
+
+#[test]
+fn clip_sprites_quirk() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.quirks.clip_sprites = true;
+    emulator.i = 0x300;
+    emulator.ram[0x300] = 0xFF;
+    emulator.ram[0x301] = 0xFF;
+    emulator.v[0] = 60;
+    emulator.v[1] = 31;
+
+    // act
+    crate::cpu::drw(&mut emulator, 0, 1, 2).unwrap();
+
+    // assert, nothing past the edges is drawn
+    assert!(emulator.display_memory[31 * 64 + 63]);
+    assert!(!emulator.display_memory[31 * 64]);
+    assert!(!emulator.display_memory[63]);
+    assert_eq!(emulator.display_memory.iter().filter(|p| **p).count(), 4);
+}
+
+
+////////////////////////////// SUPER-CHIP ////////////////////////////////////////
+
+#[test]
+fn superchip_opcodes_need_superchip_platform() {
+    // arrange
+    let mut emulator = Emulator::default();
+
+    // act, 00FF is just SYS on the original chip8
+    crate::cpu::call_instruction(&mut emulator, 0x00FF).unwrap();
+
+    // assert
+    assert!(!emulator.hires);
+
+    // act
+    emulator.platform = crate::emulator::Platform::SuperChip;
+    crate::cpu::call_instruction(&mut emulator, 0x00FF).unwrap();
+
+    // assert
+    assert!(emulator.hires);
+}
+
+#[test]
+fn high_low() {
+    // arrange
+    let mut emulator = Emulator::default();
+
+    // act
+    crate::cpu::high(&mut emulator);
+
+    // assert
+    assert_eq!(emulator.display_width(), 128);
+    assert_eq!(emulator.display_height(), 64);
+    assert_eq!(emulator.display_memory.len(), 128 * 64);
+
+    // act
+    crate::cpu::low(&mut emulator);
+
+    // assert
+    assert_eq!(emulator.display_memory.len(), 64 * 32);
+}
+
+#[test]
+fn scd() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.display_memory[5] = true;
+
+    // act
+    crate::cpu::scd(&mut emulator, 2);
+
+    // assert
+    assert!(!emulator.display_memory[5]);
+    assert!(emulator.display_memory[2 * 64 + 5]);
+}
+
+#[test]
+fn scr_scl() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.display_memory[10] = true;
+
+    // act
+    crate::cpu::scr(&mut emulator);
+
+    // assert
+    assert!(emulator.display_memory[14]);
+
+    // act
+    crate::cpu::scl(&mut emulator);
+    crate::cpu::scl(&mut emulator);
+
+    // assert
+    assert!(emulator.display_memory[6]);
+    assert_eq!(emulator.display_memory.iter().filter(|p| **p).count(), 1);
+}
+
+#[test]
+fn exit() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.platform = crate::emulator::Platform::SuperChip;
+    emulator.ram[0x200] = 0x00;
+    emulator.ram[0x201] = 0xFD;
+
+    // act
+    crate::cpu::execute_next_instruction(&mut emulator).unwrap();
+
+    // assert, stays on the exit instruction
+    assert_eq!(emulator.pc, 0x200);
+}
+
+#[test]
+fn drw_16x16_hires() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.platform = crate::emulator::Platform::SuperChip;
+    crate::cpu::high(&mut emulator);
+    emulator.i = 0x300;
+    for i in 0..32 {
+        emulator.ram[0x300 + i] = 0xFF;
+    }
+    emulator.v[0] = 100;
+    emulator.v[1] = 40;
+
+    // act
+    crate::cpu::drw(&mut emulator, 0, 1, 0).unwrap();
+
+    // assert
+    assert_eq!(emulator.display_memory.iter().filter(|p| **p).count(), 256);
+    assert!(emulator.display_memory[40 * 128 + 100]);
+    assert!(emulator.display_memory[55 * 128 + 115]);
+    assert!(!emulator.display_memory[56 * 128 + 115]);
+}
+
+#[test]
+fn ldhf() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.v[3] = 2;
+
+    // act
+    crate::cpu::ldhf(&mut emulator, 3);
+
+    // assert
+    assert_eq!(emulator.i as usize, crate::emulator::BIG_FONTSET_ADDRESS + 20);
+    assert_eq!(emulator.ram[emulator.i as usize], 0x3E);
+}
+
+#[test]
+fn ldrv_ldvr() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.v[0] = 0x1;
+    emulator.v[1] = 0x2;
+    emulator.v[2] = 0x3;
+
+    // act
+    crate::cpu::ldrv(&mut emulator, 1);
+    emulator.v = [0; 0x10];
+    crate::cpu::ldvr(&mut emulator, 2);
+
+    // assert, only V0 and V1 were saved
+    assert_eq!(emulator.v[0], 0x1);
+    assert_eq!(emulator.v[1], 0x2);
+    assert_eq!(emulator.v[2], 0x0);
+}
+
+
+////////////////////////////// XO-CHIP ////////////////////////////////////////
+
+#[allow(dead_code)]
+fn xochip_emulator() -> Emulator {
+    let mut emulator = Emulator::default();
+    emulator.set_platform(crate::emulator::Platform::XoChip);
+    return emulator;
+}
+
+#[test]
+fn xochip_ram() {
+    // act
+    let emulator = xochip_emulator();
+
+    // assert
+    assert_eq!(emulator.ram.len(), 0x10000);
+}
+
+#[test]
+fn ldil() {
+    // arrange
+    let mut emulator = xochip_emulator();
+    emulator.ram[0x200..0x204].copy_from_slice(&[0xF0, 0x00, 0xBE, 0xEF]);
+
+    // act
+    crate::cpu::execute_next_instruction(&mut emulator).unwrap();
+
+    // assert
+    assert_eq!(emulator.i, 0xBEEF);
+    assert_eq!(emulator.pc, 0x204);
+}
+
+#[test]
+fn skip_over_long_instruction() {
+    // arrange
+    let mut emulator = xochip_emulator();
+    emulator.ram[0x200..0x206].copy_from_slice(&[0x30, 0x00, 0xF0, 0x00, 0xBE, 0xEF]);
+
+    // act
+    crate::cpu::execute_next_instruction(&mut emulator).unwrap();
+
+    // assert
+    assert_eq!(emulator.pc, 0x206);
+}
+
+#[test]
+fn plane_drw() {
+    // arrange
+    let mut emulator = xochip_emulator();
+    emulator.i = 0x300;
+    emulator.ram[0x300] = 0x80; // first plane
+    emulator.ram[0x301] = 0xC0; // second plane
+
+    // act
+    crate::cpu::plane(&mut emulator, 3);
+    crate::cpu::drw(&mut emulator, 0, 1, 1).unwrap();
+
+    // assert
+    assert_eq!(emulator.plane_mask, 0x3);
+    assert_eq!(emulator.pixel(0), 3);
+    assert_eq!(emulator.pixel(1), 2);
+    assert_eq!(emulator.pixel(2), 0);
+
+    // act, only clears the second plane
+    crate::cpu::plane(&mut emulator, 2);
+    crate::cpu::cls(&mut emulator);
+
+    // assert
+    assert_eq!(emulator.pixel(0), 1);
+    assert_eq!(emulator.pixel(1), 0);
+}
+
+#[test]
+fn scu() {
+    // arrange
+    let mut emulator = xochip_emulator();
+    emulator.display_memory[3 * 64 + 5] = true;
+
+    // act
+    crate::cpu::scu(&mut emulator, 2);
+
+    // assert
+    assert!(emulator.display_memory[64 + 5]);
+    assert_eq!(emulator.display_memory.iter().filter(|p| **p).count(), 1);
+}
+
+#[test]
+fn ldrange_ldvrange() {
+    // arrange
+    let mut emulator = xochip_emulator();
+    emulator.i = 0x300;
+    emulator.v[2] = 0x1;
+    emulator.v[3] = 0x2;
+    emulator.v[4] = 0x3;
+
+    // act
+    crate::cpu::ldrange(&mut emulator, 2, 4).unwrap();
+
+    // assert
+    assert_eq!(emulator.ram[0x300..0x303], [0x1, 0x2, 0x3]);
+    assert_eq!(emulator.i, 0x300);
+
+    // act, reversed order
+    crate::cpu::ldvrange(&mut emulator, 8, 7).unwrap();
+
+    // assert
+    assert_eq!(emulator.v[8], 0x1);
+    assert_eq!(emulator.v[7], 0x2);
+}
+
+#[test]
+fn ldaudio_ldpitch() {
+    // arrange
+    let mut emulator = xochip_emulator();
+    emulator.i = 0x300;
+    emulator.ram[0x300] = 0xAA;
+    emulator.ram[0x30F] = 0x55;
+    emulator.v[1] = 100;
+
+    // act
+    crate::cpu::ldaudio(&mut emulator).unwrap();
+    crate::cpu::ldpitch(&mut emulator, 1);
+
+    // assert
+    assert_eq!(emulator.audio_pattern[0], 0xAA);
+    assert_eq!(emulator.audio_pattern[15], 0x55);
+    assert_eq!(emulator.pitch, 100);
+}
+
+
+#[test]
+fn ldk_at_end_of_memory() {
+    // arrange, LD V0, K in the last two bytes, fetching it wraps the pc to 0
+    let mut emulator = xochip_emulator();
+    emulator.pc = 0xFFFE;
+    emulator.ram[0xFFFE..].copy_from_slice(&[0xF0, 0x0A]);
+
+    // act
+    crate::cpu::execute_next_instruction(&mut emulator).unwrap();
+
+    // assert, waits on the same instruction
+    assert_eq!(emulator.pc, 0xFFFE);
+}
+
+#[test]
+fn exit_at_end_of_memory() {
+    // arrange
+    let mut emulator = xochip_emulator();
+    emulator.pc = 0xFFFE;
+    emulator.ram[0xFFFE..].copy_from_slice(&[0x00, 0xFD]);
+
+    // act
+    crate::cpu::execute_next_instruction(&mut emulator).unwrap();
+
+    // assert
+    assert_eq!(emulator.pc, 0xFFFE);
+}
+
+////////////////////////////// ERRORS ////////////////////////////////////////
+
+#[test]
+fn unknown_opcode() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200] = 0x80;
+    emulator.ram[0x201] = 0x0A;
+
+    // act
+    let result = crate::cpu::execute_next_instruction(&mut emulator);
+
+    // assert
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, crate::cpu::CpuErrorKind::UnknownOpcode);
+    assert_eq!(error.pc, 0x200);
+    assert_eq!(error.opcode, 0x800A);
+}
+
+#[test]
+fn stack_underflow() {
+    // arrange
+    let mut emulator = Emulator::default();
+
+    // act
+    let result = crate::cpu::ret(&mut emulator);
+
+    // assert
+    assert_eq!(result, Err(crate::cpu::CpuErrorKind::StackUnderflow));
+    assert_eq!(emulator.pc, 0x200);
+}
+
+#[test]
+fn stack_overflow() {
+    // arrange
+    let mut emulator = Emulator::default();
+    for _ in 0..15 {
+        crate::cpu::call(&mut emulator, 0x200).unwrap();
+    }
+
+    // act
+    let result = crate::cpu::call(&mut emulator, 0x200);
+
+    // assert
+    assert_eq!(result, Err(crate::cpu::CpuErrorKind::StackOverflow));
+    assert_eq!(emulator.sp, 15);
+}
+
+#[test]
+fn memory_out_of_bounds() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.i = 0xFFE;
+    emulator.ram[0x200] = 0xF2;
+    emulator.ram[0x201] = 0x55;
+
+    // act
+    let result = crate::cpu::execute_next_instruction(&mut emulator);
+
+    // assert
+    let error = result.unwrap_err();
+    assert_eq!(error.kind, crate::cpu::CpuErrorKind::MemoryOutOfBounds { address: 0x1000 });
+    assert_eq!(error.opcode, 0xF255);
+    assert_eq!(error.to_string(), "memory access out of bounds at 0x1000, opcode F255 at 0x200");
+}
+
+#[test]
+fn pc_out_of_bounds() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.pc = 0xFFF;
+
+    // act
+    let result = crate::cpu::execute_next_instruction(&mut emulator);
+
+    // assert
+    assert_eq!(result.unwrap_err().kind, crate::cpu::CpuErrorKind::PcOutOfBounds);
+    assert_eq!(emulator.pc, 0xFFF);
+}
+
+#[test]
+fn logic_opcodes_decode_exactly() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.v[4] = 0x3;
+    emulator.v[5] = 0x1;
+
+    // act, 8xy6 used to be decoded as 8xy2
+    crate::cpu::call_instruction(&mut emulator, 0x8456).unwrap();
+
+    // assert
+    assert_eq!(emulator.v[4], 0x1);
+    assert_eq!(emulator.v[0xF], 0x1);
+}
+
+#[test]
+fn execute() {
+    // arrange
+    let mut emulator = Emulator::default();
+
+    // act
+    crate::cpu::execute(&mut emulator, crate::instruction::Instruction::Ldx { x: 3, kk: 0x42 }).unwrap();
+
+    // assert
+    assert_eq!(emulator.v[3], 0x42);
+
+    // act, unsupported on chip8
+    let result = crate::cpu::execute(&mut emulator, crate::instruction::Instruction::Ldhf { x: 3 });
+
+    // assert
+    assert_eq!(result.unwrap_err().opcode, 0xF330);
+}
@@ -0,0 +1,252 @@
+#[allow(unused_imports)]
+use crate::debugger::Debugger;
+#[allow(unused_imports)]
+use crate::emulator::Emulator;
+
+// LD V0, 5 / ADD V0, 1 / JP 0x202, counts up forever
+#[allow(dead_code)]
+fn counting_emulator() -> Emulator {
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200..0x206].copy_from_slice(&[0x60, 0x05, 0x70, 0x01, 0x12, 0x02]);
+    return emulator;
+}
+
+#[test]
+fn step() {
+    // arrange
+    let mut emulator = counting_emulator();
+    let mut debugger = Debugger::new(10);
+
+    // act
+    let output = debugger.command(&mut emulator, "step 2");
+
+    // assert, the listing shows where the pc ended up
+    assert_eq!(emulator.v[0], 6);
+    assert_eq!(emulator.pc, 0x204);
+    assert!(output.starts_with("> 0204: 1202"));
+}
+
+#[test]
+fn step_repeats_on_empty_line() {
+    // arrange
+    let mut emulator = counting_emulator();
+    let mut debugger = Debugger::new(10);
+
+    // act
+    debugger.command(&mut emulator, "s");
+    debugger.command(&mut emulator, "");
+
+    // assert
+    assert_eq!(emulator.pc, 0x204);
+}
+
+#[test]
+fn step_ticks_timers() {
+    // arrange
+    let mut emulator = counting_emulator();
+    emulator.delay_timer = 10;
+    let mut debugger = Debugger::new(4);
+
+    // act
+    debugger.command(&mut emulator, "step 9");
+
+    // assert, one tick per 4 instructions
+    assert_eq!(emulator.delay_timer, 8);
+}
+
+#[test]
+fn continue_stops_at_breakpoint() {
+    // arrange
+    let mut emulator = counting_emulator();
+    let mut debugger = Debugger::new(10);
+    debugger.command(&mut emulator, "break 0x204");
+
+    // act
+    debugger.command(&mut emulator, "continue");
+    let message = debugger.run_frame(&mut emulator);
+
+    // assert
+    assert!(!debugger.running);
+    assert_eq!(emulator.pc, 0x204);
+    assert!(message.unwrap().starts_with("breakpoint at 0x204"));
+}
+
+#[test]
+fn continue_from_breakpoint() {
+    // arrange, already stopped at the breakpoint
+    let mut emulator = counting_emulator();
+    emulator.pc = 0x204;
+    emulator.v[0] = 6;
+    let mut debugger = Debugger::new(10);
+    debugger.command(&mut emulator, "b 0x204");
+
+    // act
+    debugger.command(&mut emulator, "c");
+    let message = debugger.run_frame(&mut emulator);
+
+    // assert, went round the loop once and stopped there again
+    assert!(message.is_some());
+    assert_eq!(emulator.pc, 0x204);
+    assert_eq!(emulator.v[0], 7);
+}
+
+#[test]
+fn continue_runs_a_frame_at_a_time() {
+    // arrange
+    let mut emulator = counting_emulator();
+    let mut debugger = Debugger::new(10);
+
+    // act
+    debugger.command(&mut emulator, "continue");
+    let message = debugger.run_frame(&mut emulator);
+
+    // assert, LD and then 3 rounds of ADD and JP, still running
+    assert_eq!(message, None);
+    assert!(debugger.running);
+    assert_eq!(emulator.v[0], 5 + 5);
+}
+
+#[test]
+fn continue_stops_on_error() {
+    // arrange, 800A isn't an instruction
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200..0x202].copy_from_slice(&[0x80, 0x0A]);
+    let mut debugger = Debugger::new(10);
+
+    // act
+    debugger.command(&mut emulator, "continue");
+    let message = debugger.run_frame(&mut emulator);
+
+    // assert
+    assert!(!debugger.running);
+    assert!(message.unwrap().starts_with("stopped: "));
+}
+
+#[test]
+fn breakpoints_list_and_delete() {
+    // arrange
+    let mut emulator = counting_emulator();
+    let mut debugger = Debugger::new(10);
+    debugger.command(&mut emulator, "break 0x300");
+    debugger.command(&mut emulator, "break 512");
+
+    // act
+    let listed = debugger.command(&mut emulator, "break");
+    debugger.command(&mut emulator, "delete 0x300");
+    let missing = debugger.command(&mut emulator, "delete 0x300");
+    debugger.command(&mut emulator, "delete all");
+
+    // assert
+    assert_eq!(listed, "0x200\n0x300");
+    assert_eq!(missing, "error: no breakpoint at 0x300");
+    assert!(debugger.breakpoints.is_empty());
+}
+
+#[test]
+fn set_registers() {
+    // arrange
+    let mut emulator = counting_emulator();
+    let mut debugger = Debugger::new(10);
+
+    // act
+    debugger.command(&mut emulator, "set vA 0x12");
+    debugger.command(&mut emulator, "set i 0x345");
+    debugger.command(&mut emulator, "set PC 0x300");
+    debugger.command(&mut emulator, "set dt 60");
+    let too_big = debugger.command(&mut emulator, "set v0 256");
+    let unknown = debugger.command(&mut emulator, "set vg 1");
+    debugger.command(&mut emulator, "set sp 15");
+    let past_the_stack = debugger.command(&mut emulator, "set sp 16");
+
+    // assert
+    assert_eq!(emulator.v[0xA], 0x12);
+    assert_eq!(emulator.i, 0x345);
+    assert_eq!(emulator.pc, 0x300);
+    assert_eq!(emulator.delay_timer, 60);
+    assert_eq!(too_big, "error: 0x100 doesn't fit in a byte");
+    assert_eq!(unknown, "error: unknown register 'vg'");
+    assert_eq!(past_the_stack, "error: sp goes up to 15");
+    assert_eq!(emulator.sp, 15);
+}
+
+#[test]
+fn poke_and_memory() {
+    // arrange
+    let mut emulator = counting_emulator();
+    let mut debugger = Debugger::new(10);
+
+    // act
+    debugger.command(&mut emulator, "poke 0x300 0xAB 0xCD 1");
+    let output = debugger.command(&mut emulator, "x 0x300 20");
+
+    // assert
+    assert_eq!(&emulator.ram[0x300..0x303], &[0xAB, 0xCD, 0x01]);
+    assert_eq!(output, "0300: AB CD 01 00 00 00 00 00 00 00 00 00 00 00 00 00\n0310: 00 00 00 00");
+}
+
+#[test]
+fn poke_past_the_end() {
+    // arrange
+    let mut emulator = counting_emulator();
+    let mut debugger = Debugger::new(10);
+
+    // act
+    let output = debugger.command(&mut emulator, "poke 0xFFF 1 2");
+
+    // assert, nothing written
+    assert_eq!(output, "error: 0x1000 is past the end of memory");
+    assert_eq!(emulator.ram[0xFFF], 0);
+}
+
+#[test]
+fn list_marks_pc() {
+    // arrange
+    let mut emulator = counting_emulator();
+    emulator.pc = 0x202;
+
+    // act
+    let output = crate::debugger::list(&emulator, 0x202, 3);
+
+    // assert
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("  0200: 6005"));
+    assert!(lines[1].starts_with("> 0202: 7001"));
+    assert!(lines[2].starts_with("  0204: 1202"));
+}
+
+#[test]
+fn registers() {
+    // arrange, CALL 0x210
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200..0x202].copy_from_slice(&[0x22, 0x10]);
+    emulator.v[0xF] = 1;
+    let mut debugger = Debugger::new(10);
+    let empty = crate::debugger::registers(&emulator);
+    debugger.command(&mut emulator, "step");
+
+    // act
+    let output = crate::debugger::registers(&emulator);
+
+    // assert, the return address the call pushed
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "pc 0x210  i 0x000  sp 1  dt 0  st 0");
+    assert!(lines[2].ends_with("vF 01"));
+    assert_eq!(lines[3], "stack [0x202]");
+    assert!(empty.ends_with("stack []"));
+}
+
+#[test]
+fn unknown_command() {
+    // arrange
+    let mut emulator = counting_emulator();
+    let mut debugger = Debugger::new(10);
+
+    // act
+    let output = debugger.command(&mut emulator, "jump 0x200");
+    debugger.command(&mut emulator, "quit");
+
+    // assert
+    assert_eq!(output, "error: unknown command 'jump', try help");
+    assert!(debugger.quit);
+}
@@ -0,0 +1,81 @@
+#[allow(unused_imports)]
+use crate::disasm::LabelKind;
+#[allow(unused_imports)]
+use crate::emulator::Platform;
+
+#[test]
+fn analyze_separates_code_from_data() {
+    // arrange, CLS / LD I, 0x20A / CALL 0x208 / JP 0x206 / RET / sprite
+    let rom = [0x00, 0xE0, 0xA2, 0x0A, 0x22, 0x08, 0x12, 0x06, 0x00, 0xEE, 0xF0, 0x90];
+
+    // act
+    let analysis = crate::disasm::analyze(&rom, 0x200, Platform::Chip8);
+
+    // assert
+    assert_eq!(analysis.code.iter().copied().collect::<Vec<u16>>(), vec![0x200, 0x202, 0x204, 0x206, 0x208]);
+    assert_eq!(analysis.labels.get(&0x206), Some(&LabelKind::Jump));
+    assert_eq!(analysis.labels.get(&0x208), Some(&LabelKind::Subroutine));
+    assert_eq!(analysis.labels.get(&0x20A), Some(&LabelKind::Data));
+}
+
+#[test]
+fn analyze_follows_both_sides_of_a_skip() {
+    // arrange, SE V0, 0 / JP 0x208 / RET / RET
+    let rom = [0x30, 0x00, 0x12, 0x08, 0x00, 0xEE, 0x00, 0xEE, 0x00, 0xEE];
+
+    // act
+    let analysis = crate::disasm::analyze(&rom, 0x200, Platform::Chip8);
+
+    // assert, 0x206 is never reached
+    assert_eq!(analysis.code.iter().copied().collect::<Vec<u16>>(), vec![0x200, 0x202, 0x204, 0x208]);
+}
+
+#[test]
+fn analyze_stops_at_unsupported_opcodes() {
+    // arrange, LD HF, V0 only exists on super-chip
+    let rom = [0xF0, 0x30, 0x00, 0xEE];
+
+    // act
+    let chip8 = crate::disasm::analyze(&rom, 0x200, Platform::Chip8);
+    let schip = crate::disasm::analyze(&rom, 0x200, Platform::SuperChip);
+
+    // assert
+    assert!(chip8.code.is_empty());
+    assert_eq!(schip.code.len(), 2);
+}
+
+#[test]
+fn analyze_runs_newer_0nnn_opcodes_as_sys() {
+    // arrange, SCD 3 and EXIT are SYS calls on chip8 and the code carries on after them
+    let rom = [0x00, 0xC3, 0x00, 0xFD, 0x60, 0x01, 0x00, 0xEE];
+
+    // act
+    let chip8 = crate::disasm::analyze(&rom, 0x200, Platform::Chip8);
+    let listing = crate::disasm::disassemble(&rom, 0x200, Platform::Chip8);
+
+    // assert
+    assert_eq!(chip8.code.iter().copied().collect::<Vec<u16>>(), vec![0x200, 0x202, 0x204, 0x206]);
+    assert!(listing.contains("SYS 0x0FD"), "{}", listing);
+}
+
+#[test]
+fn disassemble() {
+    // arrange, xo-chip long load followed by the data it points at
+    let rom = [0xF0, 0x00, 0x02, 0x06, 0x00, 0xFD, 0x81];
+
+    // act
+    let listing = crate::disasm::disassemble(&rom, 0x200, Platform::XoChip);
+
+    // assert
+    assert!(listing.contains("LD I, LONG data_206"));
+    assert!(listing.contains("; 200: F000 0206"));
+    assert!(listing.contains("EXIT"));
+    assert!(listing.contains("data_206:\n    db 0x81"));
+    assert!(listing.contains("; 206: #......#"));
+}
+
+#[test]
+fn sprite_art() {
+    assert_eq!(crate::disasm::sprite_art(0xF0), "####....");
+    assert_eq!(crate::disasm::sprite_art(0x01), ".......#");
+}
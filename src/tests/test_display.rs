@@ -9,4 +9,46 @@ fn sys_addr() {
 
     // assert
     assert!(test_display_mem.iter().eq(expected_display_mem.iter()));
-}
\ No newline at end of file
+}
+
+#[test]
+fn scroll_down() {
+    // arrange, 4x3 display with the top row lit
+    let mut display = [true, true, true, true, false, false, false, false, false, false, false, false];
+
+    // act
+    crate::display::scroll_down(&mut display, 4, 1);
+
+    // assert
+    assert_eq!(display, [false, false, false, false, true, true, true, true, false, false, false, false]);
+}
+
+#[test]
+fn scroll_left_and_right() {
+    // arrange, 4x2 display
+    let mut display = [true, false, false, true, false, true, false, false];
+
+    // act
+    crate::display::scroll_right(&mut display, 4, 1);
+
+    // assert
+    assert_eq!(display, [false, true, false, false, false, false, true, false]);
+
+    // act
+    crate::display::scroll_left(&mut display, 4, 2);
+
+    // assert
+    assert_eq!(display, [false, false, false, false, true, false, false, false]);
+}
+
+#[test]
+fn scroll_up() {
+    // arrange, 2x3 display with the bottom row lit
+    let mut display = [false, false, false, false, true, true];
+
+    // act
+    crate::display::scroll_up(&mut display, 2, 2);
+
+    // assert
+    assert_eq!(display, [true, true, false, false, false, false]);
+}
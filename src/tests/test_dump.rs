@@ -0,0 +1,66 @@
+#[allow(unused_imports)]
+use crate::dump::Format;
+#[allow(unused_imports)]
+use crate::emulator::Emulator;
+
+#[test]
+fn format_from_path() {
+    assert_eq!(Format::from_path("out/frame.PBM"), Some(Format::Pbm));
+    assert_eq!(Format::from_path("frame.png"), Some(Format::Png));
+    assert_eq!(Format::from_path("frame.txt"), Some(Format::Ascii));
+    assert_eq!(Format::from_path("frame.bmp"), None);
+    assert_eq!(Format::from_path("frame"), None);
+}
+
+#[test]
+fn ascii() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.display_memory[1] = true;
+    emulator.second_plane[2] = true;
+    emulator.display_memory[64] = true;
+    emulator.second_plane[64] = true;
+
+    // act
+    let text = crate::dump::ascii(&emulator);
+
+    // assert
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 32);
+    assert_eq!(&lines[0][..4], ".#+.");
+    assert_eq!(&lines[1][..4], "*...");
+    assert_eq!(lines[31], ".".repeat(64));
+}
+
+#[test]
+fn pbm() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.set_hires(true);
+    emulator.display_memory[0] = true;
+
+    // act
+    let text = String::from_utf8(crate::dump::pbm(&emulator)).unwrap();
+
+    // assert
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "P1");
+    assert_eq!(lines[1], "128 64");
+    assert!(lines[2].starts_with("1 0 0"));
+    assert_eq!(lines.len(), 2 + 64);
+}
+
+#[test]
+fn png() {
+    // arrange
+    let emulator = Emulator::default();
+
+    // act
+    let png = crate::dump::png(&emulator);
+
+    // assert, signature, IHDR with 64x32 8 bit indexed colour, then IEND as the last chunk
+    assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']);
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(&png[16..29], &[0, 0, 0, 64, 0, 0, 0, 32, 8, 3, 0, 0, 0]);
+    assert_eq!(&png[png.len() - 12..], &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]);
+}
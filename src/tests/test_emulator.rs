@@ -0,0 +1,139 @@
+#[allow(unused_imports)]
+use crate::emulator::{Emulator, LoadError, Platform};
+
+#[test]
+fn load_rom() {
+    // arrange
+    let mut emulator = Emulator::default();
+
+    // act
+    let result = emulator.load_rom(&[0x60, 0x05, 0x12, 0x02], 0x200);
+
+    // assert
+    assert_eq!(result, Ok(()));
+    assert_eq!(&emulator.ram[0x200..0x204], &[0x60, 0x05, 0x12, 0x02]);
+    assert_eq!(emulator.pc, 0x200);
+}
+
+#[test]
+fn load_address() {
+    // arrange
+    let mut emulator = Emulator::default();
+
+    // act
+    let result = emulator.load_rom(&[0xAB], 0x600);
+
+    // assert, runs from where it was loaded
+    assert_eq!(result, Ok(()));
+    assert_eq!(emulator.ram[0x600], 0xAB);
+    assert_eq!(emulator.pc, 0x600);
+}
+
+#[test]
+fn fills_memory() {
+    // arrange
+    let mut emulator = Emulator::default();
+    let rom = vec![0xFF; 0x1000 - 0x200];
+
+    // act
+    let result = emulator.load_rom(&rom, 0x200);
+
+    // assert
+    assert_eq!(result, Ok(()));
+    assert_eq!(emulator.ram[0xFFF], 0xFF);
+}
+
+#[test]
+fn too_big() {
+    // arrange
+    let mut emulator = Emulator::default();
+    let rom = vec![0xFF; 0x1000 - 0x200 + 1];
+
+    // act
+    let result = emulator.load_rom(&rom, 0x200);
+
+    // assert, nothing is loaded
+    assert_eq!(result, Err(LoadError::TooBig { size: 0xE01, address: 0x200, platform: Platform::Chip8 }));
+    assert!(emulator.ram[0x200..].iter().all(|byte| *byte == 0));
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "rom is 3585 bytes but only 3584 fit between 0x200 and the end of chip8's 4096 bytes of memory, xo-chip roms can be up to 65024 bytes"
+    );
+}
+
+#[test]
+fn xochip_memory() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.set_platform(Platform::XoChip);
+    let rom = vec![0xFF; 0x8000];
+
+    // act
+    let result = emulator.load_rom(&rom, 0x200);
+
+    // assert
+    assert_eq!(result, Ok(()));
+    assert_eq!(emulator.ram[0x81FF], 0xFF);
+}
+
+#[test]
+fn bad_address() {
+    // arrange
+    let mut emulator = Emulator::default();
+
+    // act
+    let over_the_font = emulator.load_rom(&[1], 0x50);
+    let past_the_end = emulator.load_rom(&[1], 0x1000);
+
+    // assert
+    assert_eq!(over_the_font, Err(LoadError::BadAddress { address: 0x50, ram_size: 0x1000 }));
+    assert_eq!(past_the_end, Err(LoadError::BadAddress { address: 0x1000, ram_size: 0x1000 }));
+    assert_eq!(emulator.ram[0x50], crate::emulator::Emulator::default().ram[0x50]);
+}
+
+#[test]
+fn empty() {
+    // arrange
+    let mut emulator = Emulator::default();
+
+    // act
+    let result = emulator.load_rom(&[], 0x200);
+
+    // assert
+    assert_eq!(result, Err(LoadError::Empty));
+}
+
+#[test]
+fn step_and_run_frame() {
+    // arrange, LD V0, 5 / ADD V0, 1 / JP 0x202
+    let mut emulator = Emulator::default();
+    emulator.load_rom(&[0x60, 0x05, 0x70, 0x01, 0x12, 0x02], 0x200).unwrap();
+    emulator.delay_timer = 2;
+
+    // act
+    emulator.step().unwrap();
+    let after_step = emulator.v[0];
+    emulator.run_frame(4).unwrap();
+
+    // assert, two rounds of the loop and one timer tick
+    assert_eq!(after_step, 5);
+    assert_eq!(emulator.v[0], 7);
+    assert_eq!(emulator.delay_timer, 1);
+}
+
+#[test]
+fn framebuffer() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.display_memory[0] = true;
+    emulator.second_plane[0] = true;
+    emulator.second_plane[65] = true;
+
+    // act
+    let framebuffer = emulator.framebuffer();
+
+    // assert
+    assert_eq!(framebuffer.len(), emulator.display_width() * emulator.display_height());
+    assert_eq!(&framebuffer[..2], &[3, 0]);
+    assert_eq!(framebuffer[65], 2);
+}
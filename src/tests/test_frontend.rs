@@ -0,0 +1,431 @@
+#[allow(unused_imports)]
+use crate::beeper::Pattern;
+#[allow(unused_imports)]
+use crate::frontend::{AudioSink, Console, Input, InputSource, NoAudio, NoInput, Session, SessionOptions, VideoSink};
+#[allow(unused_imports)]
+use crate::emulator::{LoadError, Platform};
+#[allow(unused_imports)]
+use crate::screen::Screen;
+#[allow(unused_imports)]
+use std::time::Duration;
+
+// remembers what it was shown
+#[allow(dead_code)]
+#[derive(Default)]
+struct TestVideo {
+    presented: usize,
+    lit_pixels: usize,
+    statuses: Vec<Option<String>>,
+}
+
+impl VideoSink for TestVideo {
+    fn present(&mut self, screen: &Screen) {
+        self.presented += 1;
+        self.lit_pixels = (0..screen.width() * screen.height()).filter(|index| screen.color(*index) != screen.palette.colors[0]).count();
+    }
+
+    fn set_status(&mut self, status: Option<&str>) {
+        self.statuses.push(status.map(str::to_string));
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Default)]
+struct TestAudio {
+    playing: bool,
+    pattern: Option<Pattern>,
+}
+
+impl AudioSink for TestAudio {
+    fn set_playing(&mut self, playing: bool) {
+        self.playing = playing;
+    }
+
+    fn set_pattern(&mut self, pattern: Option<Pattern>) {
+        self.pattern = pattern;
+    }
+}
+
+// scripted input, one batch per pass
+#[allow(dead_code)]
+#[derive(Default)]
+struct TestInput {
+    batches: Vec<Vec<Input>>,
+}
+
+impl InputSource for TestInput {
+    fn poll(&mut self) -> Vec<Input> {
+        if self.batches.is_empty() {
+            return Vec::new();
+        }
+        return self.batches.remove(0);
+    }
+}
+
+// scripted debugger commands, one batch per pass. what it printed stays readable after the session took it
+#[allow(dead_code)]
+#[derive(Default)]
+struct TestConsole {
+    batches: Vec<Vec<String>>,
+    printed: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+}
+
+impl Console for TestConsole {
+    fn poll(&mut self) -> Vec<String> {
+        if self.batches.is_empty() {
+            return Vec::new();
+        }
+        return self.batches.remove(0);
+    }
+
+    fn print(&mut self, output: &str) {
+        self.printed.borrow_mut().push(output.to_string());
+    }
+
+    fn prompt(&mut self) {
+        self.printed.borrow_mut().push(crate::debugger::PROMPT.to_string());
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Default)]
+struct TestFrontend {
+    video: TestVideo,
+    audio: TestAudio,
+    input: TestInput,
+}
+
+impl TestFrontend {
+    #[allow(dead_code)]
+    fn with_input(batches: Vec<Vec<Input>>) -> Self {
+        return TestFrontend { input: TestInput { batches }, ..TestFrontend::default() };
+    }
+
+    // one pass given exactly the time for the next frame
+    #[allow(dead_code)]
+    fn pass(&mut self, session: &mut Session) -> bool {
+        let elapsed = session.scheduler.until_next_frame();
+        return session.update(elapsed, &mut self.video, &mut self.audio, &mut self.input);
+    }
+}
+
+// ADD V0, 1 / JP 0x200, so V0 counts frames at 2 instructions a frame
+#[allow(dead_code)]
+const COUNTER: [u8; 4] = [0x70, 0x01, 0x12, 0x00];
+
+#[allow(dead_code)]
+fn options() -> SessionOptions {
+    return SessionOptions {
+        platform: Platform::Chip8,
+        quirks: crate::quirks::Quirks::default(),
+        load_address: 0x200,
+        rng: Box::new(crate::rng::Xorshift::new(1)),
+        scheduler: crate::scheduler::Scheduler::new(2),
+        screen: Screen::new(crate::screen::Palette::default(), false, 0),
+        debugger: None,
+        console: Box::new(crate::frontend::NoConsole),
+        gdb: None,
+        rewind: None,
+        record_path: None,
+        playback: None,
+        #[cfg(feature = "trace")]
+        tracer: None,
+    };
+}
+
+#[allow(dead_code)]
+fn session(rom: &[u8]) -> Session {
+    return Session::new(rom.to_vec(), "test.ch8", options()).unwrap();
+}
+
+#[test]
+fn runs_frames_as_they_come_due() {
+    // arrange
+    let mut session = session(&COUNTER);
+    let mut video = TestVideo::default();
+
+    // act, three frames' time in one pass, then a pass too soon for another frame
+    session.update(Duration::from_millis(50), &mut video, &mut NoAudio, &mut NoInput);
+    session.update(Duration::from_millis(1), &mut video, &mut NoAudio, &mut NoInput);
+
+    // assert, drawn every pass all the same
+    assert_eq!(session.emulator.v[0], 3);
+    assert_eq!(video.presented, 2);
+}
+
+#[test]
+fn keys() {
+    // arrange
+    let mut session = session(&COUNTER);
+    let mut frontend = TestFrontend::with_input(vec![vec![Input::Key { pad: 5, held: true }, Input::Key { pad: 0x10, held: true }]]);
+
+    // act
+    frontend.pass(&mut session);
+
+    // assert, a key the keypad doesn't have is ignored
+    assert!(session.emulator.keys[5]);
+    assert_eq!(session.emulator.keys.iter().filter(|held| **held).count(), 1);
+}
+
+#[test]
+fn quit() {
+    // arrange
+    let mut session = session(&COUNTER);
+    let mut frontend = TestFrontend::with_input(vec![vec![], vec![Input::Quit]]);
+
+    // act
+    let first = frontend.pass(&mut session);
+    let second = frontend.pass(&mut session);
+
+    // assert, nothing more runs once asked to quit
+    assert!(first);
+    assert!(!second);
+    assert_eq!(session.emulator.v[0], 1);
+}
+
+#[test]
+fn halts_and_resets() {
+    // arrange, LD V0, 7 then 800A, which isn't an instruction
+    let mut session = session(&[0x60, 0x07, 0x80, 0x0A]);
+    let mut frontend = TestFrontend::with_input(vec![vec![], vec![], vec![Input::Reset]]);
+
+    // act
+    frontend.pass(&mut session);
+    let halted = session.halted();
+    frontend.pass(&mut session);
+    frontend.pass(&mut session);
+
+    // assert, halted until reset, then runs up to the error again
+    assert_eq!(halted.unwrap().pc, 0x202);
+    let error = Some("unknown opcode 800A at 0x202 - press enter to reset".to_string());
+    assert_eq!(frontend.video.statuses, vec![error.clone(), None, error]);
+}
+
+#[test]
+fn debugger_commands() {
+    // arrange
+    let console = TestConsole { batches: vec![vec!["step".to_string()], vec!["quit".to_string()]], ..TestConsole::default() };
+    let printed = console.printed.clone();
+    let options = SessionOptions { debugger: Some(crate::debugger::Debugger::new(2)), console: Box::new(console), ..options() };
+    let mut session = Session::new(COUNTER.to_vec(), "test.ch8", options).unwrap();
+    let mut frontend = TestFrontend::default();
+
+    // act
+    let stepped = frontend.pass(&mut session);
+    let quit = frontend.pass(&mut session);
+
+    // assert, paused at start, then the step's listing and the prompt again
+    assert!(stepped);
+    assert!(!quit);
+    assert_eq!(session.emulator.v[0], 1);
+    let printed = printed.borrow();
+    assert_eq!(printed[0], crate::debugger::PROMPT);
+    assert!(printed[1].starts_with("> 0202: 1200"), "{}", printed[1]);
+    assert_eq!(printed[2], crate::debugger::PROMPT);
+}
+
+#[test]
+fn finish_reports_the_movie() {
+    // arrange
+    let options = SessionOptions { record_path: Some("missing/dir/test.c8m".to_string()), ..options() };
+    let mut session = Session::new(COUNTER.to_vec(), "test.ch8", options).unwrap();
+    TestFrontend::default().pass(&mut session);
+
+    // act
+    let result = session.finish();
+
+    // assert, the frontend gets the error to show
+    assert!(result.unwrap_err().starts_with("Couldn't write movie 'missing/dir/test.c8m': "));
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn trace_survives_reset() {
+    // arrange
+    let tracer = crate::trace::Tracer::new(Box::new(std::io::sink()), crate::trace::Format::Text, crate::trace::Filter::default());
+    let options = SessionOptions { tracer: Some(Box::new(tracer)), ..options() };
+    let mut session = Session::new(vec![0x60, 0x07, 0x80, 0x0A], "test.ch8", options).unwrap();
+    let mut frontend = TestFrontend::with_input(vec![vec![], vec![Input::Reset]]);
+
+    // act
+    frontend.pass(&mut session);
+    frontend.pass(&mut session);
+
+    // assert, the new emulator took the trace over
+    assert!(session.emulator.tracer.is_some());
+}
+
+#[test]
+fn reset_while_running() {
+    // arrange
+    let mut session = session(&COUNTER);
+    let mut frontend = TestFrontend::with_input(vec![vec![], vec![Input::Reset]]);
+
+    // act
+    frontend.pass(&mut session);
+    frontend.pass(&mut session);
+
+    // assert, only resets after an error
+    assert_eq!(session.emulator.v[0], 2);
+}
+
+#[test]
+fn rewind() {
+    // arrange
+    let mut session = Session::new(COUNTER.to_vec(), "test.ch8", SessionOptions { rewind: Some(crate::rewind::Rewind::new(60)), ..options() }).unwrap();
+    let mut frontend = TestFrontend::default();
+    for _ in 0..5 {
+        frontend.pass(&mut session);
+    }
+
+    // act, two frames back while held
+    frontend.input.batches = vec![vec![Input::Rewind { held: true }], vec![], vec![Input::Rewind { held: false }]];
+    frontend.pass(&mut session);
+    frontend.pass(&mut session);
+    let rewound = session.emulator.v[0];
+    frontend.pass(&mut session);
+
+    // assert, then carries on forwards
+    assert_eq!(rewound, 3);
+    assert_eq!(session.emulator.v[0], 4);
+}
+
+#[test]
+fn rewind_off() {
+    // arrange
+    let mut session = session(&COUNTER);
+    let mut frontend = TestFrontend::with_input(vec![vec![Input::Rewind { held: true }]]);
+
+    // act
+    frontend.pass(&mut session);
+
+    // assert, runs forwards as usual
+    assert_eq!(session.emulator.v[0], 1);
+}
+
+#[test]
+fn speed() {
+    // arrange
+    let mut session = session(&COUNTER);
+    let mut frontend = TestFrontend::with_input(vec![vec![Input::Faster]]);
+
+    // act
+    frontend.pass(&mut session);
+
+    // assert, the next of the speed steps, which the frame then ran at
+    assert_eq!(session.scheduler.instructions_per_frame, 5);
+    assert_eq!(frontend.video.statuses, vec![Some("5 instructions per frame".to_string())]);
+    assert_eq!(session.emulator.v[0], 3);
+}
+
+#[test]
+fn slots() {
+    // arrange
+    let mut session = session(&COUNTER);
+    let mut frontend = TestFrontend::with_input(vec![vec![Input::PreviousSlot, Input::NextSlot, Input::NextSlot]]);
+
+    // act
+    frontend.pass(&mut session);
+
+    // assert, wraps around below slot 0
+    assert_eq!(frontend.video.statuses, vec![Some("slot 9".to_string()), Some("slot 0".to_string()), Some("slot 1".to_string())]);
+}
+
+#[test]
+fn save_and_load_state() {
+    // arrange, states are written next to the rom
+    let rom_path = std::env::temp_dir().join(format!("grep_chip8_frontend_{}.ch8", std::process::id())).to_string_lossy().into_owned();
+    let mut session = Session::new(COUNTER.to_vec(), &rom_path, options()).unwrap();
+    let mut frontend = TestFrontend::with_input(vec![vec![], vec![Input::SaveState], vec![], vec![], vec![Input::LoadState]]);
+
+    // act
+    for _ in 0..5 {
+        frontend.pass(&mut session);
+    }
+    let _ = std::fs::remove_file(crate::savestate::slot_path(&rom_path, 0));
+
+    // assert, saved after the first frame, loaded back to it before the fifth
+    assert_eq!(frontend.video.statuses, vec![Some("saved slot 0".to_string()), Some("loaded slot 0".to_string())]);
+    assert_eq!(session.emulator.v[0], 2);
+}
+
+#[test]
+fn movie_blocks_loading_and_speed() {
+    // arrange
+    let mut session = Session::new(COUNTER.to_vec(), "test.ch8", SessionOptions { record_path: Some("unused.c8m".to_string()), ..options() }).unwrap();
+    let mut frontend = TestFrontend::with_input(vec![vec![Input::LoadState, Input::Slower]]);
+
+    // act
+    frontend.pass(&mut session);
+
+    // assert
+    let blocked = Some("loading states and changing speed would put the movie out of step".to_string());
+    assert_eq!(session.scheduler.instructions_per_frame, 2);
+    assert_eq!(frontend.video.statuses, vec![blocked.clone(), blocked]);
+}
+
+#[test]
+fn playback() {
+    // arrange, a movie holding key 0 for 2 frames at 3 instructions a frame. SKNP V1 / ADD V0, 1 / JP 0x200, V1 is 0
+    let rom = [0xE1, 0xA1, 0x70, 0x01, 0x12, 0x00];
+    let mut movie = crate::movie::Movie::new(crate::savestate::rom_hash(&rom), &crate::rng::Xorshift::new(1), Platform::Chip8, crate::quirks::Quirks::default(), 0x200, 3);
+    movie.frames = vec![0x0001, 0x0001];
+    let mut session = Session::new(rom.to_vec(), "test.ch8", SessionOptions { playback: Some(movie), ..options() }).unwrap();
+    let mut frontend = TestFrontend::default();
+
+    // act
+    for _ in 0..3 {
+        frontend.pass(&mut session);
+    }
+
+    // assert, counted while the movie held the key, then the input takes over with nothing held
+    assert_eq!(session.emulator.v[0], 2);
+    assert!(!session.emulator.keys[0]);
+    assert_eq!(frontend.video.statuses, vec![Some("movie finished after 2 frames".to_string())]);
+}
+
+#[test]
+fn present_and_beep() {
+    // arrange, LD V0, 10 / LD ST, V0 / LD F, V0 / DRW V0, V0, 5 / JP 0x208
+    let mut session = session(&[0x60, 0x0A, 0xF0, 0x18, 0xF0, 0x29, 0xD0, 0x05, 0x12, 0x08]);
+    session.scheduler.instructions_per_frame = 5;
+    let mut frontend = TestFrontend::default();
+
+    // act
+    frontend.pass(&mut session);
+
+    // assert, the A drawn is 14 pixels
+    assert!(frontend.audio.playing);
+    assert_eq!(frontend.audio.pattern, None);
+    assert_eq!(frontend.video.presented, 1);
+    assert_eq!(frontend.video.lit_pixels, 14);
+}
+
+#[test]
+fn xochip_audio_pattern() {
+    // arrange, LD I, 0x20A / AUDIO / LD V0, 112 / PITCH V0 / JP 0x208, then the pattern
+    let mut rom = vec![0xA2, 0x0A, 0xF0, 0x02, 0x60, 0x70, 0xF0, 0x3A, 0x12, 0x08];
+    rom.extend((0..0x10).map(|byte| byte as u8));
+    let mut options = options();
+    options.platform = Platform::XoChip;
+    let mut session = Session::new(rom, "test.8o", options).unwrap();
+    session.scheduler.instructions_per_frame = 5;
+    let mut frontend = TestFrontend::default();
+
+    // act
+    frontend.pass(&mut session);
+
+    // assert
+    let bits: Vec<u8> = (0..0x10).collect();
+    assert_eq!(frontend.audio.pattern.map(|pattern| pattern.bits.to_vec()), Some(bits));
+    assert_eq!(frontend.audio.pattern.map(|pattern| pattern.pitch), Some(112));
+}
+
+#[test]
+fn load_error() {
+    // act
+    let session = Session::new(Vec::new(), "test.ch8", options());
+
+    // assert
+    assert_eq!(session.err(), Some(LoadError::Empty));
+}
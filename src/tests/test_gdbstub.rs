@@ -0,0 +1,316 @@
+#[allow(unused_imports)]
+use crate::gdbstub::GdbStub;
+#[allow(unused_imports)]
+use crate::emulator::Emulator;
+#[allow(unused_imports)]
+use std::io::{Read, Write};
+#[allow(unused_imports)]
+use std::net::{TcpListener, TcpStream};
+
+// a stub and the client end of its connection, over localhost
+#[allow(dead_code)]
+fn connect() -> (GdbStub, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    client.set_read_timeout(Some(std::time::Duration::from_millis(10))).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    return (GdbStub::new(stream, 10).unwrap(), client);
+}
+
+// reads what the stub sent until there's a whole packet or nothing more comes, polling the stub in between
+#[allow(dead_code)]
+fn receive(stub: &mut GdbStub, emulator: &mut Emulator, client: &mut TcpStream) -> String {
+    let mut received = Vec::new();
+    for _ in 0..100 {
+        stub.poll(emulator).unwrap();
+        let mut buffer = [0; 4096];
+        match client.read(&mut buffer) {
+            Ok(count) => received.extend_from_slice(&buffer[..count]),
+            Err(_) if !received.is_empty() => break,
+            Err(_) => {}
+        }
+        let text = String::from_utf8_lossy(&received);
+        if text.find('$').is_some_and(|start| text[start..].find('#').is_some_and(|end| start + end + 3 <= text.len())) {
+            break;
+        }
+    }
+    return String::from_utf8_lossy(&received).into_owned();
+}
+
+// sends a packet the way gdb does and returns the raw reply, ack included
+#[allow(dead_code)]
+fn exchange(stub: &mut GdbStub, emulator: &mut Emulator, client: &mut TcpStream, data: &str) -> String {
+    client.write_all(crate::gdbstub::packet(data).as_bytes()).unwrap();
+    return receive(stub, emulator, client);
+}
+
+#[test]
+fn packet() {
+    // act
+    let framed = crate::gdbstub::packet("OK");
+
+    // assert
+    assert_eq!(framed, "$OK#9a");
+}
+
+#[test]
+fn read_registers() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+    emulator.v[0] = 0x12;
+    emulator.v[0xF] = 0x34;
+    emulator.i = 0x345;
+    emulator.sp = 1;
+    emulator.delay_timer = 7;
+
+    // act
+    let reply = exchange(&mut stub, &mut emulator, &mut client, "g");
+
+    // assert, v0-vf, then i and pc little endian, then sp, dt and st
+    let registers = format!("12{}34{}{}{}{}", "00".repeat(14), "4503", "0002", "01", "0700");
+    assert_eq!(reply, format!("+{}", crate::gdbstub::packet(&registers)));
+}
+
+#[test]
+fn write_register() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+
+    // act
+    let pc = exchange(&mut stub, &mut emulator, &mut client, "P11=0403");
+    let v3 = exchange(&mut stub, &mut emulator, &mut client, "P3=ff");
+    let read = exchange(&mut stub, &mut emulator, &mut client, "p11");
+    let wrong_size = exchange(&mut stub, &mut emulator, &mut client, "P3=ffff");
+
+    // assert
+    assert_eq!(pc, "+$OK#9a");
+    assert_eq!(v3, "+$OK#9a");
+    assert_eq!(read, format!("+{}", crate::gdbstub::packet("0403")));
+    assert_eq!(wrong_size, format!("+{}", crate::gdbstub::packet("E01")));
+    assert_eq!(emulator.pc, 0x304);
+    assert_eq!(emulator.v[3], 0xFF);
+}
+
+#[test]
+fn memory() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+
+    // act
+    let write = exchange(&mut stub, &mut emulator, &mut client, "M300,3:abcdef");
+    let read = exchange(&mut stub, &mut emulator, &mut client, "m2ff,5");
+    let past_the_end = exchange(&mut stub, &mut emulator, &mut client, "m1000,1");
+
+    // assert
+    assert_eq!(write, "+$OK#9a");
+    assert_eq!(read, format!("+{}", crate::gdbstub::packet("00abcdef00")));
+    assert_eq!(past_the_end, format!("+{}", crate::gdbstub::packet("E01")));
+    assert_eq!(&emulator.ram[0x300..0x303], &[0xAB, 0xCD, 0xEF]);
+}
+
+#[test]
+fn huge_lengths() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+    let xml = crate::gdbstub::target_xml();
+
+    // act, lengths that would overflow the end address
+    let start = emulator.ram.len() - 4;
+    let read = exchange(&mut stub, &mut emulator, &mut client, &format!("m{:x},ffffffffffffffff", start));
+    let write = exchange(&mut stub, &mut emulator, &mut client, "M1,ffffffffffffffff:ab");
+    let features = exchange(&mut stub, &mut emulator, &mut client, "qXfer:features:read:target.xml:10,ffffffffffffffff");
+
+    // assert, the read stops at the end of ram and the description at its end
+    assert_eq!(read, format!("+{}", crate::gdbstub::packet("00000000")));
+    assert_eq!(write, format!("+{}", crate::gdbstub::packet("E01")));
+    assert_eq!(features, format!("+{}", crate::gdbstub::packet(&format!("l{}", &xml[0x10..]))));
+}
+
+#[test]
+fn stack_pointer_bound() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+
+    // act, stack[1..=sp] holds the entries so 15 is the top
+    let top = exchange(&mut stub, &mut emulator, &mut client, "P12=0f");
+    let past = exchange(&mut stub, &mut emulator, &mut client, "P12=10");
+
+    // assert
+    assert_eq!(top, "+$OK#9a");
+    assert_eq!(past, format!("+{}", crate::gdbstub::packet("E01")));
+    assert_eq!(emulator.sp, 15);
+}
+
+#[test]
+fn unknown_commands() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+
+    // act, a command starting with a character that isn't ascii
+    let unicode = exchange(&mut stub, &mut emulator, &mut client, "\u{e9}1,2");
+    let unknown = exchange(&mut stub, &mut emulator, &mut client, "vMustReplyEmpty");
+
+    // assert, an empty reply tells gdb they aren't supported
+    assert_eq!(unicode, format!("+{}", crate::gdbstub::packet("")));
+    assert_eq!(unknown, format!("+{}", crate::gdbstub::packet("")));
+}
+
+#[test]
+fn step() {
+    // arrange, LD V0, 5
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200..0x202].copy_from_slice(&[0x60, 0x05]);
+
+    // act
+    let reply = exchange(&mut stub, &mut emulator, &mut client, "s");
+
+    // assert
+    assert_eq!(reply, format!("+{}", crate::gdbstub::packet("S05")));
+    assert_eq!(emulator.pc, 0x202);
+    assert_eq!(emulator.v[0], 5);
+}
+
+#[test]
+fn continue_to_breakpoint() {
+    // arrange, ADD V0, 1 / JP 0x200
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200..0x204].copy_from_slice(&[0x70, 0x01, 0x12, 0x00]);
+    let breakpoint = exchange(&mut stub, &mut emulator, &mut client, "Z0,200,2");
+
+    // act, continuing from the breakpoint goes round the loop once
+    let ack = exchange(&mut stub, &mut emulator, &mut client, "c");
+    stub.run_frame(&mut emulator).unwrap();
+    let stop = receive(&mut stub, &mut emulator, &mut client);
+
+    // assert
+    assert_eq!(breakpoint, "+$OK#9a");
+    assert_eq!(ack, "+");
+    assert_eq!(stop, crate::gdbstub::packet("S05"));
+    assert!(!stub.debugger.running);
+    assert_eq!(emulator.pc, 0x200);
+    assert_eq!(emulator.v[0], 1);
+}
+
+#[test]
+fn continue_until_error() {
+    // arrange, 800A isn't an instruction
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200..0x202].copy_from_slice(&[0x80, 0x0A]);
+    exchange(&mut stub, &mut emulator, &mut client, "c");
+
+    // act
+    stub.run_frame(&mut emulator).unwrap();
+    let stop = receive(&mut stub, &mut emulator, &mut client);
+
+    // assert
+    assert_eq!(stop, crate::gdbstub::packet("S04"));
+}
+
+#[test]
+fn interrupt() {
+    // arrange, JP 0x200 forever
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+    emulator.ram[0x200..0x202].copy_from_slice(&[0x12, 0x00]);
+    exchange(&mut stub, &mut emulator, &mut client, "c");
+    stub.run_frame(&mut emulator).unwrap();
+
+    // act, what ctrl-c in gdb sends
+    client.write_all(&[0x03]).unwrap();
+    let stop = receive(&mut stub, &mut emulator, &mut client);
+
+    // assert
+    assert_eq!(stop, crate::gdbstub::packet("S02"));
+    assert!(!stub.debugger.running);
+}
+
+#[test]
+fn bad_checksum() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+
+    // act
+    client.write_all(b"$g#00").unwrap();
+    let reply = receive(&mut stub, &mut emulator, &mut client);
+
+    // assert, asks for the packet again
+    assert_eq!(reply, "-");
+}
+
+#[test]
+fn target_description() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+    let xml = crate::gdbstub::target_xml();
+
+    // act
+    let supported = exchange(&mut stub, &mut emulator, &mut client, "qSupported:multiprocess+;xmlRegisters=i386");
+    let first = exchange(&mut stub, &mut emulator, &mut client, "qXfer:features:read:target.xml:0,10");
+    let rest = exchange(&mut stub, &mut emulator, &mut client, &format!("qXfer:features:read:target.xml:10,{:x}", xml.len()));
+
+    // assert
+    assert!(supported.contains("qXfer:features:read+"));
+    assert_eq!(first, format!("+{}", crate::gdbstub::packet(&format!("m{}", &xml[..0x10]))));
+    assert_eq!(rest, format!("+{}", crate::gdbstub::packet(&format!("l{}", &xml[0x10..]))));
+    assert!(xml.contains("<reg name=\"pc\" bitsize=\"16\" type=\"code_ptr\" regnum=\"17\"/>"));
+}
+
+#[test]
+fn no_ack_mode() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+
+    // act
+    let start = exchange(&mut stub, &mut emulator, &mut client, "QStartNoAckMode");
+    let reply = exchange(&mut stub, &mut emulator, &mut client, "?");
+
+    // assert, the reply to QStartNoAckMode is still acked
+    assert_eq!(start, "+$OK#9a");
+    assert_eq!(reply, crate::gdbstub::packet("S05"));
+}
+
+#[test]
+fn detach() {
+    // arrange
+    let (mut stub, mut client) = connect();
+    let mut emulator = Emulator::default();
+
+    // act
+    let reply = exchange(&mut stub, &mut emulator, &mut client, "D");
+
+    // assert
+    assert_eq!(reply, "+$OK#9a");
+    assert!(stub.closed);
+}
+
+#[test]
+fn hang_up() {
+    // arrange
+    let (mut stub, client) = connect();
+    let mut emulator = Emulator::default();
+
+    // act
+    drop(client);
+    for _ in 0..100 {
+        stub.poll(&mut emulator).unwrap();
+        if stub.closed {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+
+    // assert
+    assert!(stub.closed);
+}
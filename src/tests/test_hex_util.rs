@@ -16,4 +16,10 @@ fn get_nth_nibble() {
     assert_eq!(second, 4);
     assert_eq!(third, 5);
     assert_eq!(fourth, 6);
-}
\ No newline at end of file
+}
+
+#[test]
+fn crc32() {
+    assert_eq!(crate::hex_util::crc32(b"123456789"), 0xCBF43926);
+    assert_eq!(crate::hex_util::crc32(b""), 0);
+}
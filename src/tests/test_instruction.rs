@@ -0,0 +1,69 @@
+#[allow(unused_imports)]
+use crate::instruction::Instruction;
+
+#[test]
+fn decode() {
+    // act / assert
+    assert_eq!(crate::instruction::decode(0x00E0), Instruction::Cls);
+    assert_eq!(crate::instruction::decode(0x1234), Instruction::Jp { nnn: 0x234 });
+    assert_eq!(crate::instruction::decode(0x3A42), Instruction::Se { x: 0xA, kk: 0x42 });
+    assert_eq!(crate::instruction::decode(0x8AB6), Instruction::Shrxy { x: 0xA, y: 0xB });
+    assert_eq!(crate::instruction::decode(0xD125), Instruction::Drw { x: 1, y: 2, n: 5 });
+    assert_eq!(crate::instruction::decode(0xF265), Instruction::Ldvi { x: 2 });
+    assert_eq!(crate::instruction::decode(0x00C4), Instruction::Scd { n: 4 });
+    assert_eq!(crate::instruction::decode(0xF201), Instruction::Plane { n: 2 });
+    assert_eq!(crate::instruction::decode(0x800A), Instruction::Unknown { opcode: 0x800A });
+    assert_eq!(crate::instruction::decode(0xE1FF), Instruction::Unknown { opcode: 0xE1FF });
+}
+
+#[test]
+fn encode_decode_round_trip() {
+    for opcode in 0..=0xFFFF {
+        assert_eq!(crate::instruction::encode(crate::instruction::decode(opcode)), opcode, "opcode {:04X}", opcode);
+    }
+}
+
+#[test]
+fn is_supported() {
+    // arrange
+    use crate::emulator::Platform;
+
+    // act / assert
+    assert!(Instruction::Cls.is_supported(Platform::Chip8));
+    assert!(!Instruction::High.is_supported(Platform::Chip8));
+    assert!(Instruction::High.is_supported(Platform::SuperChip));
+    assert!(Instruction::High.is_supported(Platform::XoChip));
+    assert!(!Instruction::Ldil.is_supported(Platform::SuperChip));
+    assert!(!Instruction::Unknown { opcode: 0x800A }.is_supported(Platform::XoChip));
+}
+
+#[test]
+fn size() {
+    assert_eq!(Instruction::Ldil.size(), 4);
+    assert_eq!(Instruction::Cls.size(), 2);
+}
+
+#[test]
+fn display() {
+    assert_eq!(Instruction::Ldx { x: 0xA, kk: 0x05 }.to_string(), "LD VA, 0x05");
+    assert_eq!(Instruction::Jp { nnn: 0x208 }.to_string(), "JP 0x208");
+    assert_eq!(Instruction::Ldvi { x: 3 }.to_string(), "LD V3, [I]");
+    assert_eq!(Instruction::Drw { x: 1, y: 2, n: 5 }.to_string(), "DRW V1, V2, 5");
+    assert_eq!(Instruction::Ldrange { x: 1, y: 4 }.to_string(), "LD [I], V1 - V4");
+}
+
+#[test]
+fn parse() {
+    // arrange
+    use crate::instruction::{Operand, ParseError};
+
+    // act / assert
+    assert_eq!(crate::instruction::parse("ld", &[Operand::V(3), Operand::Value(0x42)]), Ok(Instruction::Ldx { x: 3, kk: 0x42 }));
+    assert_eq!(crate::instruction::parse("JP", &[Operand::V(0), Operand::Value(0x300)]), Ok(Instruction::Jpv { nnn: 0x300 }));
+    assert_eq!(crate::instruction::parse("SHR", &[Operand::V(4)]), Ok(Instruction::Shrxy { x: 4, y: 4 }));
+    assert_eq!(crate::instruction::parse("LD", &[Operand::Range(2, 5), Operand::IndirectI]), Ok(Instruction::Ldvrange { x: 2, y: 5 }));
+    assert_eq!(crate::instruction::parse("LD", &[Operand::I, Operand::Long(0xFFFF)]), Ok(Instruction::Ldil));
+    assert_eq!(crate::instruction::parse("DRW", &[Operand::V(0), Operand::V(1), Operand::Value(16)]), Err(ParseError::OutOfRange { operand: 2, max: 0xF }));
+    assert_eq!(crate::instruction::parse("CLS", &[Operand::V(0)]), Err(ParseError::BadOperands));
+    assert_eq!(crate::instruction::parse("NOP", &[]), Err(ParseError::UnknownMnemonic));
+}
@@ -0,0 +1,85 @@
+#[allow(unused_imports)]
+use crate::keymap::{Keymap, KeymapError};
+
+#[test]
+fn default_layout() {
+    // arrange
+    let keymap = Keymap::default();
+
+    // act / assert
+    assert_eq!(keymap.pad_key("1"), Some(0x1));
+    assert_eq!(keymap.pad_key("4"), Some(0xC));
+    assert_eq!(keymap.pad_key("q"), Some(0x4));
+    assert_eq!(keymap.pad_key("X"), Some(0x0));
+    assert_eq!(keymap.pad_key("V"), Some(0xF));
+    assert_eq!(keymap.pad_key("P"), None);
+    assert_eq!((0..0x10).map(|pad| keymap.keys_for(pad).len()).sum::<usize>(), 16);
+}
+
+#[test]
+fn apply() {
+    // arrange
+    let mut keymap = Keymap::default();
+
+    // act
+    keymap.apply("5 = Up, Keypad 8").unwrap();
+    keymap.apply("b=w").unwrap();
+
+    // assert, W moved from 5 to B and C no longer presses anything
+    assert_eq!(keymap.keys_for(0x5), vec!["keypad 8", "up"]);
+    assert_eq!(keymap.pad_key("UP"), Some(0x5));
+    assert_eq!(keymap.keys_for(0xB), vec!["w"]);
+    assert_eq!(keymap.pad_key("C"), None);
+}
+
+#[test]
+fn apply_errors() {
+    // arrange
+    let mut keymap = Keymap::default();
+
+    // act / assert
+    assert!(keymap.apply("5").is_err());
+    assert!(keymap.apply("G=Q").is_err());
+    assert!(keymap.apply("10=Q").is_err());
+    assert!(keymap.apply("5=").is_err());
+    assert_eq!(keymap, Keymap::default());
+}
+
+#[test]
+fn apply_config() {
+    // arrange
+    let config = "
+        ; every rom
+        5 = Up
+        [pong.ch8]
+        1 = Left
+        [DEADBEEF]
+        4 = Right
+        [tetris.ch8]
+        6 = Down
+    ";
+    let mut keymap = Keymap::default();
+
+    // act
+    keymap.apply_config(config, "PONG.ch8", 0xDEADBEEF).unwrap();
+
+    // assert
+    assert_eq!(keymap.pad_key("Up"), Some(0x5));
+    assert_eq!(keymap.pad_key("Left"), Some(0x1));
+    assert_eq!(keymap.pad_key("Right"), Some(0x4));
+    assert_eq!(keymap.pad_key("Down"), None);
+}
+
+#[test]
+fn apply_config_error() {
+    // arrange
+    let mut keymap = Keymap::default();
+
+    // act
+    let unclosed = keymap.apply_config("1 = Q\n[pong.ch8\n", "pong.ch8", 0);
+    let invalid = keymap.apply_config("\n\n5 Up\n", "pong.ch8", 0);
+
+    // assert
+    assert_eq!(unclosed.unwrap_err().line, 2);
+    assert_eq!(invalid.unwrap_err(), KeymapError { line: 3, message: "expected PAD=KEY, found '5 Up'".to_string() });
+}
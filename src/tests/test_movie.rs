@@ -0,0 +1,138 @@
+#[allow(unused_imports)]
+use crate::movie::{Movie, MovieError};
+#[allow(unused_imports)]
+use crate::emulator::{Emulator, Platform};
+#[allow(unused_imports)]
+use crate::quirks::Quirks;
+#[allow(unused_imports)]
+use crate::rng::Random;
+
+// adds a random byte to V3 every loop, and counts in V2 the loops key 0 was held for
+//   RND V0, 0xFF / ADD V3, V0 / SKNP V1 / ADD V2, 1 / JP 0x200
+#[allow(dead_code)]
+const ROM: [u8; 10] = [0xC0, 0xFF, 0x83, 0x04, 0xE1, 0xA1, 0x72, 0x01, 0x12, 0x00];
+
+#[allow(dead_code)]
+fn movie(seed: u64) -> Movie {
+    let rng = crate::rng::Xorshift::new(seed);
+    let mut movie = Movie::new(crate::savestate::rom_hash(&ROM), &rng, Platform::Chip8, Quirks::default(), 0x200, 10);
+    for frame in 0..120 {
+        let mut keys = [false; 0x10];
+        keys[0] = frame % 7 < 3;
+        keys[0xA] = frame % 2 == 0;
+        movie.record(&keys);
+    }
+    return movie;
+}
+
+// plays the whole movie from a fresh emulator, returns it as a save state to compare everything at once
+#[allow(dead_code)]
+fn play(movie: &Movie) -> Vec<u8> {
+    let mut emulator = Emulator::default();
+    emulator.set_platform(movie.platform);
+    emulator.quirks = movie.quirks;
+    emulator.rng = movie.start_rng();
+    emulator.ram[0x200..0x200 + ROM.len()].copy_from_slice(&ROM);
+    let mut frame = 0;
+    while movie.play_frame(&mut emulator, frame).unwrap() {
+        frame += 1;
+    }
+    assert_eq!(frame, movie.frames.len());
+    return crate::savestate::save(&emulator, movie.rom_hash);
+}
+
+#[test]
+fn pack_keys() {
+    // arrange
+    let mut keys = [false; 0x10];
+    keys[0] = true;
+    keys[0xF] = true;
+
+    // act
+    let bits = crate::movie::pack_keys(&keys);
+
+    // assert
+    assert_eq!(bits, 0x8001);
+    assert_eq!(crate::movie::unpack_keys(bits), keys);
+}
+
+#[test]
+fn save_load() {
+    // arrange
+    let mut movie = movie(1234);
+    movie.platform = Platform::SuperChip;
+    movie.quirks = Quirks::schip();
+    movie.load_address = 0x600;
+
+    // act
+    let loaded = crate::movie::load(&crate::movie::save(&movie), movie.rom_hash);
+
+    // assert
+    assert_eq!(loaded.unwrap(), movie);
+}
+
+#[test]
+fn save_load_vip() {
+    // arrange
+    let rng = crate::rng::Vip::new(77);
+    let movie = Movie::new(1, &rng, Platform::Chip8, Quirks::default(), 0x200, 10);
+
+    // act
+    let loaded = crate::movie::load(&crate::movie::save(&movie), 1).unwrap();
+
+    // assert, starts from the same point
+    let mut started = loaded.start_rng();
+    let mut expected = rng.clone();
+    assert_eq!(loaded.rng_kind, crate::rng::RandomKind::Vip);
+    assert_eq!((0..10).map(|_| started.next_byte()).collect::<Vec<u8>>(), (0..10).map(|_| expected.next_byte()).collect::<Vec<u8>>());
+}
+
+#[test]
+fn load_errors() {
+    // arrange
+    let movie = movie(1234);
+    let data = crate::movie::save(&movie);
+    let mut damaged = data.clone();
+    damaged[30] ^= 0xFF;
+
+    // act
+    let wrong_rom = crate::movie::load(&data, movie.rom_hash ^ 1);
+    let damaged = crate::movie::load(&damaged, movie.rom_hash);
+    let truncated = crate::movie::load(&data[..20], movie.rom_hash);
+    let not_a_movie = crate::movie::load(&crate::savestate::save(&Emulator::default(), movie.rom_hash), movie.rom_hash);
+
+    // assert
+    assert_eq!(wrong_rom.unwrap_err(), MovieError::WrongRom { expected: movie.rom_hash ^ 1, found: movie.rom_hash });
+    assert_eq!(damaged.unwrap_err(), MovieError::ChecksumMismatch);
+    assert_eq!(truncated.unwrap_err(), MovieError::ChecksumMismatch);
+    assert_eq!(not_a_movie.unwrap_err(), MovieError::NotAMovie);
+}
+
+#[test]
+fn playback_is_deterministic() {
+    // arrange
+    let movie = movie(1234);
+
+    // act
+    let first = play(&movie);
+    let second = play(&crate::movie::load(&crate::movie::save(&movie), movie.rom_hash).unwrap());
+
+    // assert, bit for bit, rng included
+    assert_eq!(first, second);
+}
+
+#[test]
+fn playback_depends_on_seed_and_keys() {
+    // arrange
+    let original = movie(1234);
+    let other_seed = movie(4321);
+    let mut other_keys = movie(1234);
+    other_keys.frames[50] ^= 1;
+
+    // act
+    let original = play(&original);
+
+    // assert
+    assert_ne!(original, play(&other_seed));
+    assert_ne!(original, play(&other_keys));
+}
@@ -0,0 +1,46 @@
+#[allow(unused_imports)]
+use crate::quirks::Quirks;
+
+#[test]
+fn from_name() {
+    // act
+    let vip = Quirks::from_name("VIP");
+    let schip = Quirks::from_name("schip");
+    let unknown = Quirks::from_name("nope");
+
+    // assert
+    assert_eq!(vip, Some(Quirks::vip()));
+    assert_eq!(schip, Some(Quirks::schip()));
+    assert_eq!(unknown, None);
+}
+
+#[test]
+fn preset_names_resolve() {
+    for name in crate::quirks::PRESET_NAMES {
+        assert!(Quirks::from_name(name).is_some());
+    }
+}
+
+#[test]
+fn set() {
+    // arrange
+    let mut quirks = Quirks::default();
+
+    // act
+    let clipping = quirks.set("clipping", true);
+    let shifting = quirks.set("Shifting", true);
+    let unknown = quirks.set("wrapping", true);
+
+    // assert
+    assert!(clipping && shifting && !unknown);
+    assert!(quirks.clip_sprites);
+    assert!(quirks.shift_uses_vy);
+    assert!(!quirks.jump_uses_vx);
+}
+
+#[test]
+fn quirk_names_resolve() {
+    for name in crate::quirks::QUIRK_NAMES {
+        assert!(Quirks::default().set(name, true));
+    }
+}
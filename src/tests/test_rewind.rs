@@ -0,0 +1,145 @@
+#[allow(unused_imports)]
+use crate::rewind::Rewind;
+#[allow(unused_imports)]
+use crate::emulator::{Emulator, Platform};
+
+#[test]
+fn encode_runs_round_trip() {
+    // arrange
+    let mut data = vec![0; 300];
+    data[5] = 1;
+    data[6] = 2;
+    data[8] = 3;
+    data[200] = 0xFF;
+
+    // act
+    let encoded = crate::rewind::encode_runs(&data);
+    let decoded = crate::rewind::decode_runs(&encoded, data.len());
+
+    // assert, the lone zero at 7 is copied rather than starting a new run
+    assert_eq!(decoded, data);
+    assert_eq!(encoded, vec![5, 4, 1, 2, 0, 3, 191, 1, 1, 0xFF]);
+}
+
+#[test]
+fn encode_runs_all_zeros() {
+    // act
+    let encoded = crate::rewind::encode_runs(&[0; 4096]);
+
+    // assert
+    assert!(encoded.is_empty());
+    assert_eq!(crate::rewind::decode_runs(&encoded, 4096), vec![0; 4096]);
+}
+
+#[test]
+fn step_back() {
+    // arrange, a snapshot per frame with v0 counting the frames
+    let mut rewind = Rewind::new(10);
+    let mut emulator = Emulator::default();
+    for frame in 0..5 {
+        emulator.v[0] = frame;
+        emulator.ram[0x300] = frame * 2;
+        rewind.push(&emulator);
+    }
+
+    // act
+    let back = rewind.step_back().unwrap();
+    let further = rewind.step_back().unwrap();
+
+    // assert
+    assert_eq!(back.v[0], 3);
+    assert_eq!(further.v[0], 2);
+    assert_eq!(further.ram[0x300], 4);
+    assert_eq!(rewind.len(), 2);
+}
+
+#[test]
+fn step_back_to_the_start() {
+    // arrange
+    let mut rewind = Rewind::new(10);
+    let mut emulator = Emulator::default();
+    rewind.push(&emulator);
+    emulator.pc = 0x202;
+    rewind.push(&emulator);
+
+    // act
+    let first = rewind.step_back();
+    let past_the_start = rewind.step_back();
+
+    // assert
+    assert_eq!(first.unwrap().pc, 0x200);
+    assert!(past_the_start.is_none());
+}
+
+#[test]
+fn capacity() {
+    // arrange
+    let mut rewind = Rewind::new(3);
+    let mut emulator = Emulator::default();
+
+    // act
+    for frame in 0..10 {
+        emulator.v[0] = frame;
+        rewind.push(&emulator);
+    }
+
+    // assert, 3 snapshots in all, the newest and 2 to step back to
+    assert_eq!(rewind.len(), 2);
+    assert_eq!(rewind.step_back().unwrap().v[0], 8);
+    assert_eq!(rewind.step_back().unwrap().v[0], 7);
+    assert!(rewind.step_back().is_none());
+}
+
+#[test]
+fn deltas_are_small() {
+    // arrange
+    let mut rewind = Rewind::new(100);
+    let mut emulator = Emulator::default();
+    rewind.push(&emulator);
+    let full = rewind.memory_used();
+
+    // act, a few bytes change a frame
+    for frame in 0..99 {
+        emulator.v[1] = frame;
+        emulator.display_memory[frame as usize] = true;
+        rewind.push(&emulator);
+    }
+
+    // assert, 99 frames take less room than a single full snapshot
+    assert!(rewind.memory_used() < full * 2, "{} bytes for 100 snapshots of {} bytes", rewind.memory_used(), full);
+}
+
+#[test]
+fn platform_change() {
+    // arrange, xo-chip has more memory so its snapshots don't line up with chip-8's
+    let mut rewind = Rewind::new(10);
+    let mut emulator = Emulator::default();
+    emulator.v[0] = 1;
+    rewind.push(&emulator);
+    emulator.set_platform(Platform::XoChip);
+    rewind.push(&emulator);
+
+    // act
+    let back = rewind.step_back().unwrap();
+
+    // assert
+    assert_eq!(back.platform, Platform::Chip8);
+    assert_eq!(back.v[0], 1);
+}
+
+#[test]
+fn clear() {
+    // arrange
+    let mut rewind = Rewind::new(10);
+    let emulator = Emulator::default();
+    rewind.push(&emulator);
+    rewind.push(&emulator);
+
+    // act
+    rewind.clear();
+
+    // assert
+    assert!(rewind.is_empty());
+    assert_eq!(rewind.memory_used(), 0);
+    assert!(rewind.step_back().is_none());
+}
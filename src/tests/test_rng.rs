@@ -0,0 +1,125 @@
+#[allow(unused_imports)]
+use crate::rng::{Random, RandomKind, Vip, Xorshift};
+
+#[test]
+fn same_seed_same_numbers() {
+    // arrange
+    let mut first = Xorshift::new(42);
+    let mut second = Xorshift::new(42);
+
+    // act
+    let first: Vec<u8> = (0..100).map(|_| first.next_byte()).collect();
+    let second: Vec<u8> = (0..100).map(|_| second.next_byte()).collect();
+
+    // assert
+    assert_eq!(first, second);
+}
+
+#[test]
+fn different_seeds() {
+    // arrange
+    let mut first = Xorshift::new(0);
+    let mut second = Xorshift::new(1);
+
+    // act
+    let first: Vec<u8> = (0..100).map(|_| first.next_byte()).collect();
+    let second: Vec<u8> = (0..100).map(|_| second.next_byte()).collect();
+
+    // assert
+    assert_ne!(first, second);
+}
+
+#[test]
+fn spread() {
+    // arrange
+    let mut rng = Xorshift::new(7);
+    let mut counts = [0; 0x100];
+
+    // act
+    for _ in 0..0x10000 {
+        counts[rng.next_byte() as usize] += 1;
+    }
+
+    // assert, every byte comes up and none much more than the 256 expected
+    assert!(counts.iter().all(|count| (128..512).contains(count)));
+}
+
+#[test]
+fn vip_routine() {
+    // arrange, an interpreter whose second page counts up from 0x10, r9 starts at 0x0203
+    let mut interpreter = vec![0; 0x200];
+    for (index, byte) in interpreter[0x100..].iter_mut().enumerate() {
+        *byte = (index as u8).wrapping_add(0x10);
+    }
+    let mut vip = Vip::with_interpreter(0x0203, &interpreter).unwrap();
+
+    // act
+    let first = vip.next_byte();
+    let second = vip.next_byte();
+
+    // assert, r9 steps to 0x0204: 0x14 + 0x02 = 0x16, 0x16 >> 1 = 0x0B, 0x0B + 0x16 = 0x21
+    // then 0x2105: 0x15 + 0x21 = 0x36, 0x1B + 0x36 = 0x51
+    assert_eq!(first, 0x21);
+    assert_eq!(second, 0x51);
+}
+
+#[test]
+fn vip_carry() {
+    // arrange, 0xF0 + 0x20 carries out, the carry comes back in at the top when shifting
+    let mut interpreter = vec![0; 0x200];
+    interpreter[0x101] = 0xF0;
+    let mut vip = Vip::with_interpreter(0x2000, &interpreter).unwrap();
+
+    // act
+    let number = vip.next_byte();
+
+    // assert, 0x10 with carry shifts to 0x88, 0x88 + 0x10 = 0x98
+    assert_eq!(number, 0x98);
+}
+
+#[test]
+fn vip_needs_the_whole_interpreter() {
+    // act
+    let vip = Vip::with_interpreter(0, &[0; 0x1FF]);
+
+    // assert
+    assert!(vip.is_none());
+}
+
+#[test]
+fn save_restore() {
+    // arrange
+    let mut sources: Vec<Box<dyn Random>> = vec![RandomKind::Xorshift.create(5), RandomKind::Vip.create(5)];
+
+    for source in sources.iter_mut() {
+        source.next_byte();
+
+        // act
+        let mut restored = crate::rng::restore(source.kind(), &source.save()).unwrap();
+
+        // assert, carries on with the same numbers
+        assert_eq!((0..20).map(|_| restored.next_byte()).collect::<Vec<u8>>(), (0..20).map(|_| source.next_byte()).collect::<Vec<u8>>());
+    }
+}
+
+#[test]
+fn restore_invalid() {
+    // act
+    let zero = crate::rng::restore(RandomKind::Xorshift, &[0; 8]);
+    let short = crate::rng::restore(RandomKind::Vip, &[0; 10]);
+
+    // assert
+    assert!(zero.is_none());
+    assert!(short.is_none());
+}
+
+#[test]
+fn from_name() {
+    // act
+    let vip = RandomKind::from_name("VIP");
+    let unknown = RandomKind::from_name("dice");
+
+    // assert
+    assert_eq!(vip, Some(RandomKind::Vip));
+    assert_eq!(unknown, None);
+}
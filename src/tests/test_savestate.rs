@@ -0,0 +1,124 @@
+#[allow(unused_imports)]
+use crate::emulator::{Emulator, Platform};
+#[allow(unused_imports)]
+use crate::savestate::SaveStateError;
+#[allow(unused_imports)]
+use crate::rng::Random;
+
+#[allow(dead_code)]
+fn busy_emulator() -> Emulator {
+    let mut emulator = Emulator::default();
+    emulator.set_platform(Platform::XoChip);
+    emulator.quirks = crate::quirks::Quirks::xochip();
+    emulator.set_hires(true);
+    emulator.pc = 0x2A4;
+    emulator.sp = 2;
+    emulator.stack[0] = 0x208;
+    emulator.stack[1] = 0x312;
+    emulator.v[0xF] = 1;
+    emulator.v[3] = 0x42;
+    emulator.i = 0xBEEF;
+    emulator.delay_timer = 30;
+    emulator.sound_timer = 4;
+    emulator.keys[0xA] = true;
+    emulator.ram[0xFFFF] = 0x99;
+    emulator.display_memory[5] = true;
+    emulator.second_plane[128 * 64 - 1] = true;
+    emulator.plane_mask = 3;
+    emulator.rpl[7] = 0x77;
+    emulator.audio_pattern[15] = 0xAA;
+    emulator.pitch = 100;
+    emulator.rng = Box::new(crate::rng::Vip::new(99));
+    return emulator;
+}
+
+#[test]
+fn save_load_round_trip() {
+    // arrange
+    let emulator = busy_emulator();
+    let data = crate::savestate::save(&emulator, 0x1234_5678);
+
+    // act
+    let loaded = crate::savestate::load(&data, 0x1234_5678).unwrap();
+
+    // assert
+    assert_eq!(loaded.platform, Platform::XoChip);
+    assert_eq!(loaded.quirks, crate::quirks::Quirks::xochip());
+    assert_eq!(loaded.ram.len(), 0x10000);
+    assert!(loaded.hires);
+    assert_eq!(loaded.pc, 0x2A4);
+    assert_eq!(loaded.stack[..2], [0x208, 0x312]);
+    assert_eq!(loaded.i, 0xBEEF);
+    assert!(loaded.keys[0xA]);
+    assert!(loaded.second_plane[128 * 64 - 1]);
+    assert_eq!(loaded.pitch, 100);
+    assert_eq!(crate::savestate::save(&loaded, 0x1234_5678), data);
+    assert_eq!(loaded.rng.kind(), crate::rng::RandomKind::Vip);
+    assert_eq!(loaded.rng.save(), crate::rng::Vip::new(99).save());
+}
+
+#[test]
+fn load_wrong_rom() {
+    // arrange
+    let data = crate::savestate::save(&Emulator::default(), 1);
+
+    // act
+    let result = crate::savestate::load(&data, 2);
+
+    // assert
+    assert_eq!(result.unwrap_err(), SaveStateError::WrongRom { expected: 2, found: 1 });
+}
+
+#[test]
+fn load_wrong_version() {
+    // arrange, an older version with a valid checksum
+    let mut data = crate::savestate::save(&Emulator::default(), 1);
+    data[4..6].copy_from_slice(&0u16.to_be_bytes());
+    let len = data.len();
+    let checksum = crate::hex_util::crc32(&data[..len - 4]);
+    data[len - 4..].copy_from_slice(&checksum.to_be_bytes());
+
+    // act
+    let result = crate::savestate::load(&data, 1);
+
+    // assert
+    assert_eq!(result.unwrap_err(), SaveStateError::WrongVersion { found: 0 });
+}
+
+#[test]
+fn load_damaged() {
+    // arrange
+    let mut data = crate::savestate::save(&Emulator::default(), 1);
+    data[100] ^= 0xFF;
+
+    // act
+    let flipped = crate::savestate::load(&data, 1);
+    let truncated = crate::savestate::load(&data[..50], 1);
+    let not_a_state = crate::savestate::load(b"\x00\xE0\x12\x00", 1);
+
+    // assert
+    assert_eq!(flipped.unwrap_err(), SaveStateError::ChecksumMismatch);
+    assert_eq!(truncated.unwrap_err(), SaveStateError::ChecksumMismatch);
+    assert_eq!(not_a_state.unwrap_err(), SaveStateError::NotASaveState);
+}
+
+#[test]
+fn load_stack_pointer_past_the_stack() {
+    // arrange, sp follows the magic, version, rom crc, platform, quirks and pc
+    let mut data = crate::savestate::save(&Emulator::default(), 1);
+    data[18] = 16;
+    let len = data.len();
+    let checksum = crate::hex_util::crc32(&data[..len - 4]);
+    data[len - 4..].copy_from_slice(&checksum.to_be_bytes());
+
+    // act
+    let result = crate::savestate::load(&data, 1);
+
+    // assert
+    assert_eq!(result.unwrap_err(), SaveStateError::Corrupt);
+}
+
+#[test]
+fn slot_path() {
+    assert_eq!(crate::savestate::slot_path("roms/pong.ch8", 3), "roms/pong.ch8.state3");
+}
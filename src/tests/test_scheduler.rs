@@ -0,0 +1,81 @@
+#[allow(unused_imports)]
+use crate::scheduler::Scheduler;
+
+#[test]
+fn frames_due() {
+    // arrange
+    let mut scheduler = Scheduler::new(11);
+
+    // act
+    let short = scheduler.frames_due(std::time::Duration::from_millis(10));
+    let rest = scheduler.frames_due(std::time::Duration::from_millis(40));
+
+    // assert, 50ms is 3 frames in total, the 10ms before the first frame isn't lost
+    assert_eq!(short, 0);
+    assert_eq!(rest, 3);
+}
+
+#[test]
+fn frames_due_no_drift() {
+    // arrange, frame times that don't divide evenly into 1/60s
+    let mut scheduler = Scheduler::new(11);
+    let mut frames = 0;
+
+    // act
+    for _ in 0..1000 {
+        frames += scheduler.frames_due(std::time::Duration::from_micros(16_000));
+    }
+    frames += scheduler.frames_due(std::time::Duration::from_millis(4));
+
+    // assert, 16.004 seconds
+    assert_eq!(frames, 960);
+}
+
+#[test]
+fn frames_due_drops_frames_after_a_stall() {
+    // arrange
+    let mut scheduler = Scheduler::new(11);
+
+    // act
+    let stalled = scheduler.frames_due(std::time::Duration::from_secs(2));
+    let after = scheduler.frames_due(std::time::Duration::from_millis(1));
+
+    // assert
+    assert_eq!(stalled, crate::scheduler::MAX_CATCH_UP_FRAMES);
+    assert_eq!(after, 0);
+}
+
+#[test]
+fn until_next_frame() {
+    // arrange
+    let mut scheduler = Scheduler::new(11);
+    let full = scheduler.until_next_frame();
+
+    // act
+    scheduler.frames_due(std::time::Duration::from_millis(10));
+
+    // assert
+    assert_eq!(full, std::time::Duration::from_nanos(16_666_667));
+    assert_eq!(scheduler.until_next_frame(), std::time::Duration::from_nanos(6_666_667));
+}
+
+#[test]
+fn speed() {
+    // arrange
+    let mut scheduler = Scheduler::new(12);
+
+    // act / assert, steps onto the list from a speed that isn't on it
+    scheduler.faster();
+    assert_eq!(scheduler.instructions_per_frame, 15);
+    scheduler.slower();
+    scheduler.slower();
+    assert_eq!(scheduler.instructions_per_frame, 8);
+
+    // stops at the ends of the list
+    scheduler.instructions_per_frame = 1;
+    scheduler.slower();
+    assert_eq!(scheduler.instructions_per_frame, 1);
+    scheduler.instructions_per_frame = 10000;
+    scheduler.faster();
+    assert_eq!(scheduler.instructions_per_frame, 10000);
+}
@@ -0,0 +1,123 @@
+#[allow(unused_imports)]
+use crate::emulator::Emulator;
+#[allow(unused_imports)]
+use crate::screen::{Palette, Screen};
+
+#[test]
+fn palette_from_name() {
+    assert_eq!(Palette::from_name("classic"), Some(Palette::default()));
+    assert_eq!(Palette::from_name("DARK").unwrap().colors[0], [0, 0, 0]);
+    assert_eq!(Palette::from_name("nope"), None);
+}
+
+#[test]
+fn palette_from_hex() {
+    // act
+    let two = Palette::from_name("000000,#FFFFFF").unwrap();
+    let four = Palette::from_name("102030,405060,708090,A0B0C0").unwrap();
+
+    // assert
+    assert_eq!(two.colors, [[0, 0, 0], [255, 255, 255], [170, 170, 170], [85, 85, 85]]);
+    assert_eq!(four.colors[3], [0xA0, 0xB0, 0xC0]);
+    assert_eq!(Palette::from_name("000000"), None);
+    assert_eq!(Palette::from_name("000000,FFFFFF,123456"), None);
+    assert_eq!(Palette::from_name("000000,GGGGGG"), None);
+}
+
+#[test]
+fn update_without_phosphor() {
+    // arrange
+    let mut emulator = Emulator::default();
+    let mut screen = Screen::new(Palette::default(), false, 0);
+    emulator.display_memory[3] = true;
+    emulator.second_plane[4] = true;
+
+    // act
+    screen.update(&emulator);
+
+    // assert
+    assert_eq!((screen.width(), screen.height()), (64, 32));
+    assert_eq!(screen.color(0), [255, 255, 255]);
+    assert_eq!(screen.color(3), [0, 0, 0]);
+    assert_eq!(screen.color(4), [170, 170, 170]);
+
+    // act, cleared pixels go straight back to the background
+    emulator.display_memory[3] = false;
+    screen.update(&emulator);
+
+    // assert
+    assert_eq!(screen.color(3), [255, 255, 255]);
+}
+
+#[test]
+fn update_with_phosphor() {
+    // arrange
+    let palette = Palette::from_name("000000,FFFFFF").unwrap();
+    let mut emulator = Emulator::default();
+    let mut screen = Screen::new(palette, false, 3);
+    emulator.display_memory[0] = true;
+    screen.update(&emulator);
+    emulator.display_memory[0] = false;
+
+    // act / assert, fades out over 3 frames
+    screen.update(&emulator);
+    assert_eq!(screen.color(0), [170, 170, 170]);
+    screen.update(&emulator);
+    assert_eq!(screen.color(0), [85, 85, 85]);
+    screen.update(&emulator);
+    assert_eq!(screen.color(0), [0, 0, 0]);
+}
+
+#[test]
+fn update_resolution_change() {
+    // arrange
+    let mut emulator = Emulator::default();
+    let mut screen = Screen::new(Palette::default(), false, 10);
+    emulator.display_memory[0] = true;
+    screen.update(&emulator);
+
+    // act
+    emulator.set_hires(true);
+    screen.update(&emulator);
+
+    // assert, nothing fades in from the old resolution
+    assert_eq!((screen.width(), screen.height()), (128, 64));
+    assert_eq!(screen.color(0), [255, 255, 255]);
+}
+
+#[test]
+fn render() {
+    // arrange
+    let mut emulator = Emulator::default();
+    let mut screen = Screen::new(Palette::default(), false, 0);
+    let mut rgb = Vec::new();
+    emulator.display_memory[1] = true;
+    screen.update(&emulator);
+
+    // act
+    let size = screen.render(2, &mut rgb);
+
+    // assert, pixel 1 covers image pixels 2 and 3 of the first two rows
+    assert_eq!(size, (128, 64));
+    assert_eq!(rgb.len(), 128 * 64 * 3);
+    assert_eq!(&rgb[..12], &[255, 255, 255, 255, 255, 255, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(&rgb[128 * 3 + 6..128 * 3 + 12], &[0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn render_grid() {
+    // arrange
+    let mut emulator = Emulator::default();
+    let mut screen = Screen::new(Palette::default(), true, 0);
+    let mut rgb = Vec::new();
+    emulator.display_memory[0] = true;
+    screen.update(&emulator);
+
+    // act
+    screen.render(3, &mut rgb);
+
+    // assert, the last column of each cell is darkened
+    assert_eq!(&rgb[..3], &[0, 0, 0]);
+    assert_eq!(&rgb[3 * 3..4 * 3], &[255, 255, 255]);
+    assert_eq!(&rgb[5 * 3..6 * 3], &[191, 191, 191]);
+}
@@ -0,0 +1,18 @@
+#[allow(unused_imports)]
+use crate::emulator::Emulator;
+
+#[test]
+fn tick() {
+    // arrange
+    let mut emulator = Emulator::default();
+    emulator.delay_timer = 2;
+    emulator.sound_timer = 1;
+
+    // act
+    crate::timer::tick(&mut emulator);
+    crate::timer::tick(&mut emulator);
+
+    // assert, both stop at zero
+    assert_eq!(emulator.delay_timer, 0);
+    assert_eq!(emulator.sound_timer, 0);
+}
@@ -0,0 +1,171 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+#[allow(unused_imports)]
+use crate::emulator::Emulator;
+#[allow(unused_imports)]
+use crate::instruction::Instruction;
+#[allow(unused_imports)]
+use crate::trace::{binary_record, text_line, Class, Filter, Format, Tracer, MAGIC, RECORD_SIZE, VERSION};
+
+// a writer the test can still read after handing it to a tracer
+#[allow(dead_code)]
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(data);
+        return Ok(data.len());
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return Ok(());
+    }
+}
+
+#[allow(dead_code)]
+struct Broken;
+
+impl Write for Broken {
+    fn write(&mut self, _data: &[u8]) -> io::Result<usize> {
+        return Err(io::Error::other("disk full"));
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return Ok(());
+    }
+}
+
+#[allow(dead_code)]
+fn emulator() -> Emulator {
+    let mut emulator = Emulator::default();
+    emulator.i = 0x0ABC;
+    emulator.v[0] = 0x12;
+    emulator.v[0xF] = 0x01;
+    emulator.delay_timer = 0x3C;
+    emulator.sound_timer = 0x05;
+    return emulator;
+}
+
+#[test]
+fn names() {
+    assert_eq!(Format::from_name("Binary"), Some(Format::Binary));
+    assert_eq!(Format::from_name("json"), None);
+    assert_eq!(Class::from_name("display"), Some(Class::Display));
+    assert_eq!(Class::from_name("draw"), None);
+}
+
+#[test]
+fn classes() {
+    assert_eq!(Class::of(Instruction::Call { nnn: 0x300 }), Class::Flow);
+    assert_eq!(Class::of(Instruction::Snexy { x: 1, y: 2 }), Class::Skip);
+    assert_eq!(Class::of(Instruction::Rnd { x: 1, kk: 0xFF }), Class::Alu);
+    assert_eq!(Class::of(Instruction::Ldil), Class::Memory);
+    assert_eq!(Class::of(Instruction::Drw { x: 0, y: 1, n: 5 }), Class::Display);
+    assert_eq!(Class::of(Instruction::Lddt { x: 0 }), Class::Timer);
+    assert_eq!(Class::of(Instruction::Skp { x: 0 }), Class::Input);
+    assert_eq!(Class::of(Instruction::Ldst { x: 0 }), Class::Sound);
+}
+
+#[test]
+fn filter_matches() {
+    // arrange
+    let filter = Filter { start: 0x200, end: 0x2FF, classes: vec![Class::Display, Class::Flow] };
+    let draw = Instruction::Drw { x: 0, y: 0, n: 1 };
+
+    // assert, both the range and the class have to match
+    assert!(Filter::default().matches(0xFFFF, Instruction::Cls));
+    assert!(filter.matches(0x200, draw));
+    assert!(filter.matches(0x2FF, Instruction::Ret));
+    assert!(!filter.matches(0x300, draw));
+    assert!(!filter.matches(0x210, Instruction::Ldx { x: 0, kk: 1 }));
+}
+
+#[test]
+fn text() {
+    // act
+    let line = text_line(&emulator(), 0x204, 0x6005);
+
+    // assert
+    assert_eq!(line, "0204: 6005  LD V0, 0x05              I=0ABC V=12 00 00 00 00 00 00 00 00 00 00 00 00 00 00 01 DT=3C ST=05");
+}
+
+#[test]
+fn text_long_address() {
+    // arrange
+    let mut emulator = emulator();
+    emulator.ram[0x202..0x206].copy_from_slice(&[0xF0, 0x00, 0x12, 0x34]);
+
+    // act
+    let line = text_line(&emulator, 0x202, 0xF000);
+
+    // assert
+    assert!(line.starts_with("0202: F000  LD I, LONG 0x1234 "), "{}", line);
+}
+
+#[test]
+fn binary() {
+    // act
+    let record = binary_record(&emulator(), 0x204, 0x6005);
+
+    // assert
+    assert_eq!(&record[..6], &[0x02, 0x04, 0x60, 0x05, 0x0A, 0xBC]);
+    assert_eq!(record[6], 0x12);
+    assert_eq!(record[21], 0x01);
+    assert_eq!(&record[22..], &[0x3C, 0x05]);
+}
+
+#[test]
+fn tracer_filters_records() {
+    // arrange
+    let output = Shared::default();
+    let filter = Filter { start: 0x200, end: 0x201, ..Filter::default() };
+    let mut tracer = Tracer::new(Box::new(output.clone()), Format::Binary, filter);
+
+    // act
+    tracer.record(&emulator(), 0x200, 0x00E0);
+    tracer.record(&emulator(), 0x202, 0x00E0);
+    tracer.finish().unwrap();
+
+    // assert, the header and the one record in range
+    let data = output.0.borrow();
+    assert_eq!(data.len(), MAGIC.len() + 1 + RECORD_SIZE);
+    assert_eq!(&data[..4], MAGIC);
+    assert_eq!(data[4], VERSION);
+    assert_eq!(&data[5..9], &[0x02, 0x00, 0x00, 0xE0]);
+}
+
+#[test]
+fn tracer_write_error() {
+    // arrange
+    let mut tracer = Tracer::new(Box::new(Broken), Format::Text, Filter::default());
+
+    // act
+    tracer.record(&emulator(), 0x200, 0x00E0);
+
+    // assert
+    assert_eq!(tracer.finish().unwrap_err().to_string(), "disk full");
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn cpu_records_each_instruction() {
+    // arrange, LD V0, 1 / ADD V0, 1 / JP 0x202
+    let output = Shared::default();
+    let mut emulator = Emulator::default();
+    emulator.load_rom(&[0x60, 0x01, 0x70, 0x01, 0x12, 0x02], 0x200).unwrap();
+    emulator.tracer = Some(Box::new(Tracer::new(Box::new(output.clone()), Format::Text, Filter::default())));
+
+    // act
+    emulator.run_frame(4).unwrap();
+
+    // assert, each line has the registers from before its instruction ran
+    let text = String::from_utf8(output.0.borrow().clone()).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("0200: 6001  LD V0, 0x01"));
+    assert!(lines[3].starts_with("0202: 7001  ADD V0, 0x01"));
+    assert!(lines[3].contains("V=02 "), "{}", lines[3]);
+}
@@ -0,0 +1,85 @@
+#[allow(unused_imports)]
+use crate::emulator::Emulator;
+#[allow(unused_imports)]
+use crate::screen::{Palette, Screen};
+#[allow(unused_imports)]
+use crate::tty::{render, Glyphs};
+
+// white on black, so the escapes are easy to read
+#[allow(dead_code)]
+fn screen(lit: &[usize]) -> Screen {
+    let mut emulator = Emulator::default();
+    for index in lit {
+        emulator.display_memory[*index] = true;
+    }
+    let mut screen = Screen::new(Palette::from_name("000000,FFFFFF").unwrap(), false, 0);
+    screen.update(&emulator);
+    return screen;
+}
+
+#[test]
+fn glyphs_from_name() {
+    assert_eq!(Glyphs::from_name("half"), Some(Glyphs::HalfBlock));
+    assert_eq!(Glyphs::from_name("Braille"), Some(Glyphs::Braille));
+    assert_eq!(Glyphs::from_name("ascii"), None);
+}
+
+#[test]
+fn half_block_size() {
+    // act
+    let lines = render(&screen(&[]), Glyphs::HalfBlock);
+
+    // assert, a blank line is the background once then spaces
+    assert_eq!(lines.len(), 16);
+    assert_eq!(lines[0], format!("\x1b[48;2;0;0;0m{}\x1b[0m", " ".repeat(64)));
+}
+
+#[test]
+fn half_block_pixels() {
+    // arrange, top of the first cell, bottom of the second and both of the third
+    let lit = [0, 64 + 1, 2, 64 + 2];
+
+    // act
+    let lines = render(&screen(&lit), Glyphs::HalfBlock);
+
+    // assert, colours are only written when they change
+    let expected = concat!(
+        "\x1b[38;2;255;255;255m\x1b[48;2;0;0;0m\u{2580}",
+        "\x1b[38;2;0;0;0m\x1b[48;2;255;255;255m\u{2580}",
+        " ",
+        "\x1b[48;2;0;0;0m ",
+    );
+    assert!(lines[0].starts_with(expected), "{:?}", lines[0]);
+}
+
+#[test]
+fn braille_pixels() {
+    // arrange, the top left and bottom right dots of the first cell, the whole left column of the second
+    let lit = [0, 3 * 64 + 1, 2, 64 + 2, 2 * 64 + 2, 3 * 64 + 2];
+
+    // act
+    let lines = render(&screen(&lit), Glyphs::Braille);
+
+    // assert
+    assert_eq!(lines.len(), 8);
+    let expected = "\x1b[38;2;255;255;255m\x1b[48;2;0;0;0m\u{2881}\u{2847} ";
+    assert!(lines[0].starts_with(expected), "{:?}", lines[0]);
+    assert_eq!(lines[1], format!("\x1b[48;2;0;0;0m{}\x1b[0m", " ".repeat(32)));
+}
+
+#[test]
+fn braille_most_common_colour() {
+    // arrange, two dots of plane 2 and one of plane 1
+    let mut emulator = Emulator::default();
+    emulator.display_memory[0] = true;
+    emulator.second_plane[1] = true;
+    emulator.second_plane[64] = true;
+    let mut screen = Screen::new(Palette::from_name("000000,FFFFFF,FF0000,00FF00").unwrap(), false, 0);
+    screen.update(&emulator);
+
+    // act
+    let lines = render(&screen, Glyphs::Braille);
+
+    // assert, the cell is drawn in plane 2's colour
+    assert!(lines[0].starts_with("\x1b[38;2;255;0;0m\x1b[48;2;0;0;0m\u{280B}"), "{:?}", lines[0]);
+}
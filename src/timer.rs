@@ -0,0 +1,10 @@
+use crate::emulator::Emulator;
+
+// the delay and sound timers always count down at 60hz, no matter how many instructions run per frame
+pub const TIMER_FREQUENCY: u64 = 60;
+
+// decrements both timers by one, stopping at zero
+pub fn tick(emulator: &mut Emulator) {
+    emulator.delay_timer = emulator.delay_timer.saturating_sub(1);
+    emulator.sound_timer = emulator.sound_timer.saturating_sub(1);
+}
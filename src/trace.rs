@@ -0,0 +1,216 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::emulator::Emulator;
+use crate::instruction::{self, Instruction};
+
+// Writes a record of every instruction the cpu runs: its address, the opcode, the mnemonic and I, V0-VF and
+// the timers as they were before it ran. Only builds with the trace feature run the cpu through a tracer,
+// without it the emulator has nowhere to keep one and nothing is checked per instruction.
+//
+// The binary format is MAGIC and VERSION, then a RECORD_SIZE record per instruction, big endian:
+//   pc u16, opcode u16, i u16, v0-vf 16 x u8, delay timer u8, sound timer u8
+// the mnemonic is left out, it can be decoded again from the opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Binary,
+}
+
+pub const FORMAT_NAMES: [&str; 2] = ["text", "binary"];
+
+pub const MAGIC: &[u8; 4] = b"C8TR";
+pub const VERSION: u8 = 1;
+pub const RECORD_SIZE: usize = 24;
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name.to_ascii_lowercase().as_str() {
+            "text" => Some(Format::Text),
+            "binary" => Some(Format::Binary),
+            _ => None,
+        };
+    }
+}
+
+// what an instruction works on, for narrowing a trace down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+    // jumps, calls, returns and anything that isn't an instruction
+    Flow,
+    // the conditional skips on registers
+    Skip,
+    // arithmetic and logic on registers, and random numbers
+    Alu,
+    // I, loads and stores, fonts and bcd
+    Memory,
+    // clearing, drawing, scrolling and changing resolution or plane
+    Display,
+    Timer,
+    // keypad skips and the wait for a key
+    Input,
+    // the sound timer and xo-chip audio
+    Sound,
+}
+
+pub const CLASS_NAMES: [&str; 8] = ["flow", "skip", "alu", "memory", "display", "timer", "input", "sound"];
+
+impl Class {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name.to_ascii_lowercase().as_str() {
+            "flow" => Some(Class::Flow),
+            "skip" => Some(Class::Skip),
+            "alu" => Some(Class::Alu),
+            "memory" => Some(Class::Memory),
+            "display" => Some(Class::Display),
+            "timer" => Some(Class::Timer),
+            "input" => Some(Class::Input),
+            "sound" => Some(Class::Sound),
+            _ => None,
+        };
+    }
+
+    pub fn of(instruction: Instruction) -> Self {
+        return match instruction {
+            Instruction::Sys { .. } | Instruction::Ret | Instruction::Jp { .. } | Instruction::Call { .. } | Instruction::Jpv { .. }
+                | Instruction::Exit | Instruction::Unknown { .. } => Class::Flow,
+            Instruction::Se { .. } | Instruction::Sne { .. } | Instruction::Sev { .. } | Instruction::Snexy { .. } => Class::Skip,
+            Instruction::Ldx { .. } | Instruction::Addx { .. } | Instruction::Ldxy { .. } | Instruction::Orxy { .. } | Instruction::Andxy { .. }
+                | Instruction::Xorxy { .. } | Instruction::Addxy { .. } | Instruction::Subxy { .. } | Instruction::Shrxy { .. }
+                | Instruction::Subnxy { .. } | Instruction::Shlxy { .. } | Instruction::Rnd { .. } => Class::Alu,
+            Instruction::Ldi { .. } | Instruction::Addi { .. } | Instruction::Ldiv { .. } | Instruction::Ldb { .. } | Instruction::Ldii { .. }
+                | Instruction::Ldvi { .. } | Instruction::Ldhf { .. } | Instruction::Ldrv { .. } | Instruction::Ldvr { .. }
+                | Instruction::Ldrange { .. } | Instruction::Ldvrange { .. } | Instruction::Ldil => Class::Memory,
+            Instruction::Cls | Instruction::Drw { .. } | Instruction::Scd { .. } | Instruction::Scr | Instruction::Scl | Instruction::Low
+                | Instruction::High | Instruction::Scu { .. } | Instruction::Plane { .. } => Class::Display,
+            Instruction::Ldxdt { .. } | Instruction::Lddt { .. } => Class::Timer,
+            Instruction::Skp { .. } | Instruction::Sknp { .. } | Instruction::Ldk { .. } => Class::Input,
+            Instruction::Ldst { .. } | Instruction::Ldaudio | Instruction::Ldpitch { .. } => Class::Sound,
+        };
+    }
+}
+
+// which instructions make it into the trace, everything by default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    // addresses from start to end, both included
+    pub start: u16,
+    pub end: u16,
+    // empty for every class
+    pub classes: Vec<Class>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        return Filter { start: 0, end: 0xFFFF, classes: Vec::new() };
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, pc: u16, instruction: Instruction) -> bool {
+        return (self.start..=self.end).contains(&pc) && (self.classes.is_empty() || self.classes.contains(&Class::of(instruction)));
+    }
+}
+
+// one line of a text trace
+pub fn text_line(emulator: &Emulator, pc: u16, opcode: u16) -> String {
+    let decoded = instruction::decode(opcode);
+    let mnemonic = match decoded {
+        // the address is in the word after the opcode
+        Instruction::Ldil => {
+            let at = pc as usize + 2;
+            match emulator.ram.get(at..at + 2) {
+                Some(long) => format!("LD I, LONG {:#06X}", u16::from_be_bytes([long[0], long[1]])),
+                None => decoded.to_string(),
+            }
+        }
+        _ => decoded.to_string(),
+    };
+    let v: Vec<String> = emulator.v.iter().map(|value| format!("{:02X}", value)).collect();
+    return format!(
+        "{:04X}: {:04X}  {:<24} I={:04X} V={} DT={:02X} ST={:02X}",
+        pc, opcode, mnemonic, emulator.i, v.join(" "), emulator.delay_timer, emulator.sound_timer
+    );
+}
+
+// one record of a binary trace
+pub fn binary_record(emulator: &Emulator, pc: u16, opcode: u16) -> [u8; RECORD_SIZE] {
+    let mut record = [0; RECORD_SIZE];
+    record[0..2].copy_from_slice(&pc.to_be_bytes());
+    record[2..4].copy_from_slice(&opcode.to_be_bytes());
+    record[4..6].copy_from_slice(&emulator.i.to_be_bytes());
+    record[6..22].copy_from_slice(&emulator.v);
+    record[22] = emulator.delay_timer;
+    record[23] = emulator.sound_timer;
+    return record;
+}
+
+// Writes records for the instructions that pass the filter. A failed write stops the trace rather than the
+// emulator, the error comes back from finish
+pub struct Tracer {
+    writer: Box<dyn Write>,
+    format: Format,
+    filter: Filter,
+    error: Option<io::Error>,
+}
+
+impl fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return f.debug_struct("Tracer").field("format", &self.format).field("filter", &self.filter).field("error", &self.error).finish();
+    }
+}
+
+impl Tracer {
+    // a binary trace starts with its header straight away
+    pub fn new(writer: Box<dyn Write>, format: Format, filter: Filter) -> Self {
+        let mut tracer = Tracer { writer, format, filter, error: None };
+        if format == Format::Binary {
+            tracer.write(&[&MAGIC[..], &[VERSION]].concat());
+        }
+        return tracer;
+    }
+
+    // a buffered trace file, made again if it's there
+    pub fn create(path: &str, format: Format, filter: Filter) -> io::Result<Self> {
+        let file = File::create(path)?;
+        return Ok(Tracer::new(Box::new(BufWriter::new(file)), format, filter));
+    }
+
+    // the instruction at pc, about to run on the emulator as it is
+    pub fn record(&mut self, emulator: &Emulator, pc: u16, opcode: u16) {
+        if self.error.is_some() || !self.filter.matches(pc, instruction::decode(opcode)) {
+            return;
+        }
+        match self.format {
+            Format::Text => {
+                let line = text_line(emulator, pc, opcode) + "\n";
+                self.write(line.as_bytes());
+            }
+            Format::Binary => self.write(&binary_record(emulator, pc, opcode)),
+        }
+    }
+
+    // flushes what's still buffered, the error from the first write that failed if one did
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        return self.writer.flush();
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        if let Err(error) = self.writer.write_all(data) {
+            self.error = Some(error);
+        }
+    }
+}
+
+// records the instruction the cpu is about to run if the emulator has a tracer, the pc is already past it
+#[cfg(feature = "trace")]
+pub fn record(emulator: &mut Emulator, opcode: u16) {
+    if let Some(mut tracer) = emulator.tracer.take() {
+        tracer.record(emulator, emulator.pc.wrapping_sub(2), opcode);
+        emulator.tracer = Some(tracer);
+    }
+}
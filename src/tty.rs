@@ -0,0 +1,140 @@
+use std::fmt::Write;
+
+use crate::screen::Screen;
+
+// Draws the screen as text for a terminal, in 24 bit ansi colours. Each character covers a few pixels:
+// half blocks draw 1x2 pixels with the top pixel as the foreground and the bottom one as the background,
+// braille draws 2x4 pixels as dots in a single colour, smaller but losing the xo-chip colours where they meet.
+// Only the text is made here, the terminal itself is the binary's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyphs {
+    HalfBlock,
+    Braille,
+}
+
+pub const GLYPH_NAMES: [&str; 2] = ["half", "braille"];
+
+const UPPER_HALF_BLOCK: char = '\u{2580}';
+const BRAILLE_BLANK: u32 = 0x2800;
+// bit of each dot of a braille character, by row then column
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+const RESET: &str = "\x1b[0m";
+
+impl Glyphs {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name.to_ascii_lowercase().as_str() {
+            "half" => Some(Glyphs::HalfBlock),
+            "braille" => Some(Glyphs::Braille),
+            _ => None,
+        };
+    }
+
+    // pixels across and down each character covers
+    pub fn cell_size(self) -> (usize, usize) {
+        return match self {
+            Glyphs::HalfBlock => (1, 2),
+            Glyphs::Braille => (2, 4),
+        };
+    }
+}
+
+// the screen as lines of text, each ending with the colours reset. colours are only written when they change
+pub fn render(screen: &Screen, glyphs: Glyphs) -> Vec<String> {
+    let (cell_width, cell_height) = glyphs.cell_size();
+    let columns = screen.width().div_ceil(cell_width);
+    let rows = screen.height().div_ceil(cell_height);
+
+    let mut lines = Vec::with_capacity(rows);
+    for row in 0..rows {
+        let mut line = String::new();
+        let mut colors = Colors::default();
+        for column in 0..columns {
+            let (x, y) = (column * cell_width, row * cell_height);
+            match glyphs {
+                Glyphs::HalfBlock => {
+                    let (top, bottom) = (pixel(screen, x, y), pixel(screen, x, y + 1));
+                    // a cell of one colour is a space, so only the background has to be right
+                    if top == bottom {
+                        colors.set(&mut line, None, bottom);
+                        line.push(' ');
+                    } else {
+                        colors.set(&mut line, Some(top), bottom);
+                        line.push(UPPER_HALF_BLOCK);
+                    }
+                }
+                Glyphs::Braille => {
+                    let (dots, color) = braille_cell(screen, x, y);
+                    let background = screen.palette.colors[0];
+                    match color {
+                        Some(color) => {
+                            colors.set(&mut line, Some(color), background);
+                            line.push(char::from_u32(BRAILLE_BLANK | dots).expect("braille is in the unicode range"));
+                        }
+                        None => {
+                            colors.set(&mut line, None, background);
+                            line.push(' ');
+                        }
+                    }
+                }
+            }
+        }
+        line.push_str(RESET);
+        lines.push(line);
+    }
+    return lines;
+}
+
+// colour of a pixel, the background past the edge of the screen
+fn pixel(screen: &Screen, x: usize, y: usize) -> [u8; 3] {
+    if x >= screen.width() || y >= screen.height() {
+        return screen.palette.colors[0];
+    }
+    return screen.color(y * screen.width() + x);
+}
+
+// the dots of a braille character and the colour most of them are, None when no pixel is lit
+fn braille_cell(screen: &Screen, x: usize, y: usize) -> (u32, Option<[u8; 3]>) {
+    let background = screen.palette.colors[0];
+    let mut dots = 0;
+    let mut counts: Vec<([u8; 3], usize)> = Vec::new();
+    for (dy, bits) in BRAILLE_DOTS.iter().enumerate() {
+        for (dx, bit) in bits.iter().enumerate() {
+            let color = pixel(screen, x + dx, y + dy);
+            if color == background {
+                continue;
+            }
+            dots |= bit;
+            match counts.iter_mut().find(|(counted, _)| *counted == color) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((color, 1)),
+            }
+        }
+    }
+    // on a tie the colour seen first wins
+    let color = counts.iter().rev().max_by_key(|(_, count)| *count).map(|(color, _)| *color);
+    return (dots, color);
+}
+
+// the colours last written on a line, so the same escape isn't written for every character
+#[derive(Default)]
+struct Colors {
+    foreground: Option<[u8; 3]>,
+    background: Option<[u8; 3]>,
+}
+
+impl Colors {
+    // a foreground of None leaves it as it is, for characters that don't draw any
+    fn set(&mut self, line: &mut String, foreground: Option<[u8; 3]>, background: [u8; 3]) {
+        if let Some([r, g, b]) = foreground {
+            if self.foreground != foreground {
+                write!(line, "\x1b[38;2;{};{};{}m", r, g, b).expect("writing to a string can't fail");
+                self.foreground = foreground;
+            }
+        }
+        if self.background != Some(background) {
+            let [r, g, b] = background;
+            write!(line, "\x1b[48;2;{};{};{}m", r, g, b).expect("writing to a string can't fail");
+            self.background = Some(background);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// The sdl frontend: a window, the keyboard and the beeper. Part of the grep_chip8 binary rather than
+// the library, and only built with the sdl feature.
+
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::render::{Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+use sdl2::EventPump;
+
+use grep_chip8::frontend::{self, AudioSink, Input, InputSource, Session, VideoSink};
+use grep_chip8::screen::Screen;
+use grep_chip8::{beeper, display, keymap};
+
+// draws the screen into an rgb texture that is stretched over the window,
+// the texture is made again whenever the resolution changes
+struct SdlVideo<'a> {
+    canvas: Canvas<Window>,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    texture: Option<Texture<'a>>,
+    texture_size: (usize, usize),
+    rgb: Vec<u8>,
+    // the window keeps its size, pixels are drawn bigger or smaller as the resolution changes
+    window_width: usize,
+}
+
+impl VideoSink for SdlVideo<'_> {
+    // each pixel as big as fits in the window
+    fn present(&mut self, screen: &Screen) {
+        let cell = (self.window_width / screen.width()).max(1);
+        let (width, height) = screen.render(cell, &mut self.rgb);
+        if self.texture.is_none() || self.texture_size != (width, height) {
+            self.texture = Some(self.texture_creator.create_texture_streaming(PixelFormatEnum::RGB24, width as u32, height as u32).expect("couldnt create texture"));
+            self.texture_size = (width, height);
+        }
+        if let Some(texture) = &mut self.texture {
+            texture.update(None, &self.rgb, width * 3).expect("couldnt update texture");
+            self.canvas.copy(texture, None, None).expect("couldnt draw texture");
+        }
+        self.canvas.present();
+    }
+
+    // shown in the title and printed, so there's a record of it in the terminal
+    fn set_status(&mut self, status: Option<&str>) {
+        let title = match status {
+            Some(status) => {
+                println!("{}", status);
+                format!("Grep Chip8 - {}", status)
+            }
+            None => "Grep Chip8".to_string(),
+        };
+        self.canvas.window_mut().set_title(&title).expect("couldnt set title");
+    }
+}
+
+// plays the beeper on the sdl audio thread
+struct BeeperCallback(beeper::Beeper);
+
+impl AudioCallback for BeeperCallback {
+    type Channel = f32;
+
+    fn callback(&mut self, samples: &mut [f32]) {
+        self.0.fill(samples);
+    }
+}
+
+// sound is optional, without an audio device the emulator runs silently
+fn open_beeper(sdl_context: &sdl2::Sdl, tone: beeper::Tone) -> Option<AudioDevice<BeeperCallback>> {
+    let desired = AudioSpecDesired { freq: Some(44_100), channels: Some(1), samples: None };
+    let device = sdl_context.audio().and_then(|audio| {
+        audio.open_playback(None, &desired, |spec| BeeperCallback(beeper::Beeper::new(tone, spec.freq as u32)))
+    });
+    return match device {
+        Ok(device) => {
+            device.resume();
+            Some(device)
+        }
+        Err(error) => {
+            eprintln!("No sound, couldn't open an audio device: {}", error);
+            None
+        }
+    };
+}
+
+// None without sound
+struct SdlAudio(Option<AudioDevice<BeeperCallback>>);
+
+impl AudioSink for SdlAudio {
+    fn set_playing(&mut self, playing: bool) {
+        if let Some(device) = &mut self.0 {
+            device.lock().0.set_playing(playing);
+        }
+    }
+
+    fn set_pattern(&mut self, pattern: Option<beeper::Pattern>) {
+        if let Some(device) = &mut self.0 {
+            device.lock().0.set_pattern(pattern);
+        }
+    }
+}
+
+// F5 saves to the current slot, F9 loads from it, F6 / F7 pick the slot. page up / page down change the speed.
+// holding backspace rewinds, enter resets after an error. every other key goes through the keymap
+struct SdlInput {
+    event_pump: EventPump,
+    keymap: keymap::Keymap,
+}
+
+impl InputSource for SdlInput {
+    fn poll(&mut self) -> Vec<Input> {
+        let mut inputs = Vec::new();
+        for event in self.event_pump.poll_iter() {
+            let input = match event {
+                Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => Input::Quit,
+                Event::KeyDown { keycode: Some(Keycode::Return), .. } => Input::Reset,
+                Event::KeyDown { keycode: Some(Keycode::F5), .. } => Input::SaveState,
+                Event::KeyDown { keycode: Some(Keycode::F9), .. } => Input::LoadState,
+                Event::KeyDown { keycode: Some(Keycode::F6), .. } => Input::PreviousSlot,
+                Event::KeyDown { keycode: Some(Keycode::F7), .. } => Input::NextSlot,
+                Event::KeyDown { keycode: Some(Keycode::PageUp), .. } => Input::Faster,
+                Event::KeyDown { keycode: Some(Keycode::PageDown), .. } => Input::Slower,
+                Event::KeyDown { keycode: Some(Keycode::Backspace), .. } => Input::Rewind { held: true },
+                Event::KeyUp { keycode: Some(Keycode::Backspace), .. } => Input::Rewind { held: false },
+                Event::KeyDown { keycode: Some(keycode), .. } | Event::KeyUp { keycode: Some(keycode), .. } => {
+                    let Some(pad) = self.keymap.pad_key(&keycode.name()) else {
+                        continue;
+                    };
+                    Input::Key { pad, held: matches!(event, Event::KeyDown { .. }) }
+                }
+                _ => continue,
+            };
+            inputs.push(input);
+        }
+        return inputs;
+    }
+}
+
+// opens the window and runs the session in it until it's closed
+pub fn run_window(mut session: Session, keymap: keymap::Keymap, tone: Option<beeper::Tone>, scale: u32) {
+    let sdl_context = sdl2::init().unwrap();
+    let video_subsystem = sdl_context.video().unwrap();
+    let window_width = display::LORES_WIDTH as u32 * scale;
+    let window = video_subsystem.window("Grep Chip8", window_width, display::LORES_HEIGHT as u32 * scale)
+        .position_centered()
+        .build()
+        .unwrap();
+
+    let mut canvas = window.into_canvas().build().unwrap();
+    let [r, g, b] = session.screen.palette.colors[0];
+    canvas.set_draw_color(Color::RGB(r, g, b));
+    canvas.clear();
+    canvas.present();
+
+    let texture_creator = canvas.texture_creator();
+    let mut video = SdlVideo { canvas, texture_creator: &texture_creator, texture: None, texture_size: (0, 0), rgb: Vec::new(), window_width: window_width as usize };
+    let mut audio = SdlAudio(tone.and_then(|tone| open_beeper(&sdl_context, tone)));
+    let mut input = SdlInput { event_pump: sdl_context.event_pump().unwrap(), keymap };
+
+    frontend::run(&mut session, &mut video, &mut audio, &mut input);
+    crate::finish(session);
+}